@@ -1,7 +1,9 @@
 use async_trait::async_trait;
 use serenity::{
     builder::{CreateApplicationCommands},
-    model::prelude::interaction::application_command::ApplicationCommandInteraction,
+    model::prelude::interaction::{
+        application_command::ApplicationCommandInteraction, autocomplete::AutocompleteInteraction,
+    },
     prelude::Context,
 };
 
@@ -14,7 +16,7 @@ pub trait SlashCommand: Send + Sync {
     fn register<'a>(
         &'a self,
         commands: &'a mut CreateApplicationCommands,
-    ) -> &mut CreateApplicationCommands;
+    ) -> &'a mut CreateApplicationCommands;
 
     async fn dispatch(
         &self,
@@ -22,5 +24,16 @@ pub trait SlashCommand: Send + Sync {
         ctx: &Context,
         config: &Self::Config,
     ) -> Result<(), CommandError>;
+
+    /// Answers autocomplete requests for options registered with `set_autocomplete`.
+    async fn autocomplete(
+        &self,
+        _interaction: &AutocompleteInteraction,
+        _ctx: &Context,
+        _config: &Self::Config,
+    ) -> Result<(), CommandError> {
+        Ok(())
+    }
+
     fn name(&self) -> String;
 }
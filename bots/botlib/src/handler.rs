@@ -51,6 +51,23 @@ impl<T: Clone + Sync + Send> EventHandler for BotHandler<T> {
                     error!("Error during command interaction: {:?}", why);
                 }
             }
+        } else if let Interaction::Autocomplete(autocomplete_interaction) = interaction {
+            if autocomplete_interaction.guild_id.is_none() {
+                return;
+            }
+
+            for command in self
+                .commands
+                .iter()
+                .filter(|x| x.name() == autocomplete_interaction.data.name)
+            {
+                if let Err(why) = command
+                    .autocomplete(&autocomplete_interaction, &ctx, &self.config)
+                    .await
+                {
+                    error!("Error during autocomplete interaction: {:?}", why);
+                }
+            }
         }
     }
 
@@ -1,4 +1,3 @@
-use monitoring_core::client::SysInfoClient;
 use serenity::{model::prelude::interaction::application_command::ApplicationCommandInteraction, prelude::Context};
 use tracing::log::info;
 
@@ -8,7 +7,7 @@ pub struct CommandUsageLogger;
 
 impl CommandUsageLogger {
     pub async fn log(
-        ctx: &Context,
+        _ctx: &Context,
         interaction: &ApplicationCommandInteraction,
     ) -> Result<(), CommandError> {
         info!(
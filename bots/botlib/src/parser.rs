@@ -2,7 +2,7 @@ use chrono::{NaiveDateTime, NaiveDate};
 use serenity::model::{
     prelude::{
         interaction::application_command::{CommandDataOption, CommandDataOptionValue},
-        ChannelId,
+        ChannelId, Role,
     },
     user::User,
 };
@@ -19,9 +19,9 @@ pub struct NamedOptionParser;
 impl NamedOptionParser {
     pub fn parse_string(options: &[CommandDataOption], name: &str) -> Result<Option<String>, ParserError> {
         let options: Vec<&CommandDataOption> =
-            options.into_iter().filter(|x| x.name == name).collect();
+            options.iter().filter(|x| x.name == name).collect();
 
-        if options.len() == 0 {
+        if options.is_empty() {
             return Ok(None)
         }
 
@@ -29,10 +29,8 @@ impl NamedOptionParser {
             return Err(ParserError::InvalidAmount(name.to_string(), options.len()));
         }
 
-        if let Some(value) = options[0].resolved.as_ref() {
-            if let CommandDataOptionValue::String(data) = value {
-                return Ok(Some(data.clone()));
-            }
+        if let Some(CommandDataOptionValue::String(data)) = options[0].resolved.as_ref() {
+            return Ok(Some(data.clone()));
         }
 
         Ok(None)
@@ -48,14 +46,12 @@ impl PositionalOptionParser {
         index: usize,
     ) -> Result<User, ParserError> {
         if let Some(option) = options.get(index) {
-            if let Some(value) = option.resolved.as_ref() {
-                if let CommandDataOptionValue::User(data, _) = value {
-                    return Ok(data.clone());
-                }
+            if let Some(CommandDataOptionValue::User(data, _)) = option.resolved.as_ref() {
+                return Ok(data.clone());
             }
         }
 
-        return Err(ParserError::NotFound(format!("index: {index}")));
+        Err(ParserError::NotFound(format!("index: {index}")))
     }
 
     pub fn parse_date(options: &[CommandDataOption]) -> Result<NaiveDateTime, ParserError> {
@@ -83,22 +79,28 @@ impl PositionalOptionParser {
         index: usize,
     ) -> Result<ChannelId, ParserError> {
         if let Some(option) = options.get(index) {
-            if let Some(value) = option.resolved.as_ref() {
-                if let CommandDataOptionValue::Channel(data) = value {
-                    return Ok(data.id);
-                }
+            if let Some(CommandDataOptionValue::Channel(data)) = option.resolved.as_ref() {
+                return Ok(data.id);
             }
         }
 
         Err(ParserError::NotFound(format!("index:{index}!")))
     }
 
+    pub fn parse_role(options: &[CommandDataOption], index: usize) -> Result<Role, ParserError> {
+        if let Some(option) = options.get(index) {
+            if let Some(CommandDataOptionValue::Role(data)) = option.resolved.as_ref() {
+                return Ok(data.clone());
+            }
+        }
+
+        Err(ParserError::NotFound(format!("index: {index}")))
+    }
+
     pub fn parser_integer(options: &[CommandDataOption], index: usize) -> Result<i64, ParserError> {
         if let Some(option) = options.get(index) {
-            if let Some(value) = option.resolved.as_ref() {
-                if let CommandDataOptionValue::Integer(data) = value {
-                    return Ok(*data);
-                }
+            if let Some(CommandDataOptionValue::Integer(data)) = option.resolved.as_ref() {
+                return Ok(*data);
             }
         }
 
@@ -110,10 +112,8 @@ impl PositionalOptionParser {
         index: usize,
     ) -> Result<String, ParserError> {
         if let Some(option) = options.get(index) {
-            if let Some(value) = option.resolved.as_ref() {
-                if let CommandDataOptionValue::String(data) = value {
-                    return Ok(data.clone());
-                }
+            if let Some(CommandDataOptionValue::String(data)) = option.resolved.as_ref() {
+                return Ok(data.clone());
             }
         }
 
@@ -0,0 +1,271 @@
+use async_trait::async_trait;
+use botlib::{commands::SlashCommand, error::CommandError, parser::PositionalOptionParser};
+use serenity::{
+    builder::{
+        CreateApplicationCommand, CreateApplicationCommandOption, CreateApplicationCommands,
+    },
+    model::{
+        prelude::{
+            command::CommandOptionType,
+            interaction::{
+                application_command::{ApplicationCommandInteraction, CommandDataOption},
+                InteractionResponseType,
+            },
+        },
+        Permissions,
+    },
+    prelude::Context,
+};
+use tracing::log::warn;
+
+use crate::{config::BotConfig, permissions::is_administrator};
+
+static COMMAND_NAME: &str = "admin";
+
+/// Manages which roles may use the other commands of the bot, only administrators may use it.
+pub struct AdminCommand {
+    commands: Vec<String>,
+}
+
+impl AdminCommand {
+    /// `commands` are the names of the commands the permissions can be changed of.
+    pub fn new(commands: Vec<String>) -> Self {
+        Self { commands }
+    }
+}
+
+#[async_trait]
+impl SlashCommand for AdminCommand {
+    type Config = BotConfig;
+
+    fn register<'a>(
+        &'a self,
+        commands: &'a mut CreateApplicationCommands,
+    ) -> &'a mut CreateApplicationCommands {
+        commands.create_application_command(|command| self.build(command));
+
+        commands
+    }
+
+    async fn dispatch(
+        &self,
+        command: &ApplicationCommandInteraction,
+        ctx: &Context,
+        config: &Self::Config,
+    ) -> Result<(), CommandError> {
+        command
+            .create_interaction_response(ctx, |m| {
+                m.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            })
+            .await?;
+
+        // Guilds can allow the command to other members, the permissions stay with administrators.
+        if !command.member.as_ref().is_some_and(is_administrator) {
+            command
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content("You are not allowed to use this command!")
+                })
+                .await?;
+
+            return Ok(());
+        }
+
+        let group = &command.data.options[0];
+
+        match (group.name.as_str(), group.options.first()) {
+            ("permissions", Some(sub_command)) => match sub_command.name.as_str() {
+                "allow" => run_allow(command, ctx, config, &sub_command.options).await,
+                "deny" => run_deny(command, ctx, config, &sub_command.options).await,
+                "view" => run_view(command, ctx, config).await,
+                _ => invalid_option(command, ctx).await,
+            },
+            _ => invalid_option(command, ctx).await,
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from(COMMAND_NAME)
+    }
+}
+
+impl AdminCommand {
+    fn build<'a>(
+        &self,
+        command: &'a mut CreateApplicationCommand,
+    ) -> &'a mut CreateApplicationCommand {
+        command
+            .name(COMMAND_NAME)
+            .description("Commands for administrating the bot.")
+            .default_member_permissions(Permissions::ADMINISTRATOR)
+            .dm_permission(false)
+            .create_option(|group| {
+                group
+                    .name("permissions")
+                    .description("Manages the roles which may use a command.")
+                    .kind(CommandOptionType::SubCommandGroup)
+                    .create_sub_option(|sub_command| {
+                        sub_command
+                            .name("allow")
+                            .description("Allows a role to use a command.")
+                            .kind(CommandOptionType::SubCommand)
+                            .create_sub_option(|option| self.command_option(option))
+                            .create_sub_option(|option| {
+                                role_option(option, "The role which may use the command.")
+                            })
+                    })
+                    .create_sub_option(|sub_command| {
+                        sub_command
+                            .name("deny")
+                            .description("Stops a role from using a command.")
+                            .kind(CommandOptionType::SubCommand)
+                            .create_sub_option(|option| self.command_option(option))
+                            .create_sub_option(|option| {
+                                role_option(option, "The role which may no longer use the command.")
+                            })
+                    })
+                    .create_sub_option(|sub_command| {
+                        sub_command
+                            .name("view")
+                            .description("Shows the roles which may use each command.")
+                            .kind(CommandOptionType::SubCommand)
+                    })
+            })
+    }
+
+    fn command_option<'a>(
+        &self,
+        option: &'a mut CreateApplicationCommandOption,
+    ) -> &'a mut CreateApplicationCommandOption {
+        option
+            .name("command")
+            .description("The command.")
+            .kind(CommandOptionType::String)
+            .required(true);
+
+        for command in &self.commands {
+            option.add_string_choice(command, command);
+        }
+
+        option
+    }
+}
+
+fn role_option<'a>(
+    option: &'a mut CreateApplicationCommandOption,
+    description: &str,
+) -> &'a mut CreateApplicationCommandOption {
+    option
+        .name("role")
+        .description(description)
+        .kind(CommandOptionType::Role)
+        .required(true)
+}
+
+pub async fn run_allow(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+    config: &BotConfig,
+    options: &[CommandDataOption],
+) -> Result<(), CommandError> {
+    let guild_id = match command.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+
+    let name = PositionalOptionParser::parse_string(options, 0)?;
+    let role = PositionalOptionParser::parse_role(options, 1)?;
+
+    let content = if config.permissions().allow(guild_id, &name, role.id).await? {
+        format!("<@&{}> may now use `/{name}`!", role.id)
+    } else {
+        format!("<@&{}> may already use `/{name}`!", role.id)
+    };
+
+    command
+        .edit_original_interaction_response(ctx, |m| {
+            m.content(content).allowed_mentions(|a| a.empty_roles())
+        })
+        .await?;
+
+    Ok(())
+}
+
+pub async fn run_deny(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+    config: &BotConfig,
+    options: &[CommandDataOption],
+) -> Result<(), CommandError> {
+    let guild_id = match command.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+
+    let name = PositionalOptionParser::parse_string(options, 0)?;
+    let role = PositionalOptionParser::parse_role(options, 1)?;
+
+    let content = if config.permissions().deny(guild_id, &name, role.id).await? {
+        format!("<@&{}> may no longer use `/{name}`!", role.id)
+    } else {
+        format!("<@&{}> wasn't allowed to use `/{name}`!", role.id)
+    };
+
+    command
+        .edit_original_interaction_response(ctx, |m| {
+            m.content(content).allowed_mentions(|a| a.empty_roles())
+        })
+        .await?;
+
+    Ok(())
+}
+
+pub async fn run_view(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+    config: &BotConfig,
+) -> Result<(), CommandError> {
+    let guild_id = match command.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+
+    let guild_roles = config.permissions().guild_roles(guild_id).await;
+
+    command
+        .edit_original_interaction_response(ctx, |m| {
+            m.embed(|e| {
+                e.title("Command permissions")
+                    .description("Administrators may use every command.");
+
+                for (name, roles) in &guild_roles {
+                    let roles: Vec<String> = roles.iter().map(|x| format!("<@&{x}>")).collect();
+                    e.field(format!("/{name}"), roles.join(" "), false);
+                }
+
+                if guild_roles.is_empty() {
+                    e.field(
+                        "No roles",
+                        "Only administrators may use the commands.",
+                        false,
+                    );
+                }
+
+                e
+            })
+        })
+        .await?;
+
+    Ok(())
+}
+
+async fn invalid_option(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+) -> Result<(), CommandError> {
+    warn!("Invalid command option found!");
+    command
+        .edit_original_interaction_response(ctx, |m| m.content("Invalid command option!"))
+        .await?;
+
+    Ok(())
+}
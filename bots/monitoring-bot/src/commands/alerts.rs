@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use botlib::{commands::SlashCommand, error::CommandError, parser::PositionalOptionParser};
+use serenity::{
+    builder::{CreateApplicationCommand, CreateApplicationCommands},
+    model::prelude::{
+        command::CommandOptionType,
+        interaction::{
+            application_command::ApplicationCommandInteraction, InteractionResponseType,
+        },
+        ChannelType,
+    },
+    prelude::Context,
+};
+use tracing::log::warn;
+
+use crate::{commands::check_permission, config::BotConfig};
+
+static COMMAND_NAME: &str = "alerts";
+
+pub struct AlertsCommand;
+
+#[async_trait]
+impl SlashCommand for AlertsCommand {
+    type Config = BotConfig;
+
+    fn register<'a>(
+        &'a self,
+        commands: &'a mut CreateApplicationCommands,
+    ) -> &'a mut CreateApplicationCommands {
+        commands.create_application_command(|command| Self::build(command));
+
+        commands
+    }
+
+    async fn dispatch(
+        &self,
+        command: &ApplicationCommandInteraction,
+        ctx: &Context,
+        config: &Self::Config,
+    ) -> Result<(), CommandError> {
+        command
+            .create_interaction_response(ctx, |m| {
+                m.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            })
+            .await?;
+
+        if !check_permission(command, ctx, config).await? {
+            return Ok(());
+        }
+
+        match command.data.options[0].name.as_str() {
+            "subscribe" => run_subscribe(command, ctx, config).await,
+            "unsubscribe" => run_unsubscribe(command, ctx, config).await,
+            _ => {
+                warn!("Invalid command option found!");
+                command
+                    .edit_original_interaction_response(ctx, |m| {
+                        m.content("Invalid command option!")
+                    })
+                    .await?;
+
+                Ok(())
+            }
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from(COMMAND_NAME)
+    }
+}
+
+impl AlertsCommand {
+    fn build(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+        command
+            .name(COMMAND_NAME)
+            .description("Commands for alert notifications.")
+            .create_option(|sub_command| {
+                sub_command
+                    .name("subscribe")
+                    .description("Posts alerts of all device profiles into a channel.")
+                    .kind(CommandOptionType::SubCommand)
+                    .create_sub_option(|option| {
+                        option
+                            .name("channel")
+                            .description("The channel alerts are posted to.")
+                            .kind(CommandOptionType::Channel)
+                            .channel_types(&[ChannelType::Text])
+                            .required(true)
+                    })
+            })
+            .create_option(|sub_command| {
+                sub_command
+                    .name("unsubscribe")
+                    .description("Stops posting alerts in this server.")
+                    .kind(CommandOptionType::SubCommand)
+            })
+    }
+}
+
+pub async fn run_subscribe(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+    config: &BotConfig,
+) -> Result<(), CommandError> {
+    let guild_id = match command.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+
+    let channel_id = PositionalOptionParser::parse_channel_id(&command.data.options[0].options, 0)?;
+
+    config
+        .subscriptions()
+        .subscribe(guild_id, channel_id)
+        .await?;
+
+    command
+        .edit_original_interaction_response(ctx, |m| {
+            m.content(format!("Alerts will be posted in <#{channel_id}>!"))
+        })
+        .await?;
+
+    Ok(())
+}
+
+pub async fn run_unsubscribe(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+    config: &BotConfig,
+) -> Result<(), CommandError> {
+    let guild_id = match command.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+
+    let content = if config.subscriptions().unsubscribe(guild_id).await? {
+        "Alerts will no longer be posted in this server!"
+    } else {
+        "This server isn't subscribed to alerts!"
+    };
+
+    command
+        .edit_original_interaction_response(ctx, |m| m.content(content))
+        .await?;
+
+    Ok(())
+}
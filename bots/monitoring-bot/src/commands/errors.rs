@@ -0,0 +1,314 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use botlib::{commands::SlashCommand, error::CommandError, parser::PositionalOptionParser};
+use monitoring_core::{
+    api::models::{DeviceProfile, ErrorLogPage, ErrorLogQuery, Scope},
+    client::SysInfoClient,
+    Severity,
+};
+use serenity::{
+    builder::{CreateApplicationCommand, CreateApplicationCommands, CreateComponents, CreateEmbed},
+    futures::StreamExt,
+    http::StatusCode,
+    model::prelude::{
+        command::CommandOptionType,
+        component::ButtonStyle,
+        interaction::{
+            application_command::ApplicationCommandInteraction,
+            autocomplete::AutocompleteInteraction, InteractionResponseType,
+        },
+    },
+    prelude::Context,
+    utils::Colour,
+};
+use tracing::log::warn;
+
+use crate::{
+    commands::{authorize, user_client},
+    config::BotConfig,
+    permissions::has_permission,
+};
+
+use super::profile::{autocomplete_profile, find_profile};
+
+static COMMAND_NAME: &str = "errors";
+/// Prefix of the acknowledge buttons, followed by the id of the error log.
+const ACKNOWLEDGE_BUTTON: &str = "errors-acknowledge-";
+/// Number of errors shown, Discord allows at most 5 buttons per row.
+const ERROR_COUNT: u32 = 5;
+/// Characters of the message shown per error, a field is limited to 1024.
+const MESSAGE_LENGTH: usize = 300;
+/// Seconds the acknowledge buttons can be used.
+const ACKNOWLEDGE_TIMEOUT: u64 = 300;
+const READ_SCOPES: [Scope; 2] = [Scope::ProfilesRead, Scope::MetricsRead];
+const ACKNOWLEDGE_SCOPES: [Scope; 2] = [Scope::ProfilesRead, Scope::ErrorsManage];
+
+pub struct ErrorsCommand;
+
+#[async_trait]
+impl SlashCommand for ErrorsCommand {
+    type Config = BotConfig;
+
+    fn register<'a>(
+        &'a self,
+        commands: &'a mut CreateApplicationCommands,
+    ) -> &'a mut CreateApplicationCommands {
+        commands.create_application_command(|command| Self::build(command));
+
+        commands
+    }
+
+    async fn dispatch(
+        &self,
+        command: &ApplicationCommandInteraction,
+        ctx: &Context,
+        config: &Self::Config,
+    ) -> Result<(), CommandError> {
+        command
+            .create_interaction_response(ctx, |m| {
+                m.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            })
+            .await?;
+
+        let client = match authorize(command, ctx, config, &READ_SCOPES).await? {
+            Some(client) => client,
+            None => return Ok(()),
+        };
+
+        run_errors(command, ctx, config, &client).await
+    }
+
+    async fn autocomplete(
+        &self,
+        interaction: &AutocompleteInteraction,
+        ctx: &Context,
+        config: &Self::Config,
+    ) -> Result<(), CommandError> {
+        autocomplete_profile(interaction, ctx, config).await
+    }
+
+    fn name(&self) -> String {
+        String::from(COMMAND_NAME)
+    }
+}
+
+impl ErrorsCommand {
+    fn build(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+        command
+            .name(COMMAND_NAME)
+            .description("Shows the latest unacknowledged errors of a device.")
+            .create_option(|option| {
+                option
+                    .name("profile")
+                    .description("The device profile.")
+                    .kind(CommandOptionType::String)
+                    .set_autocomplete(true)
+                    .required(true)
+            })
+    }
+}
+
+pub async fn run_errors(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+    config: &BotConfig,
+    client: &SysInfoClient,
+) -> Result<(), CommandError> {
+    let input = PositionalOptionParser::parse_string(&command.data.options, 0)?;
+
+    let profiles = client.get_profiles().await?.0.unwrap_or_default();
+
+    let profile = match find_profile(&profiles, &input) {
+        Some(profile) => profile,
+        None => {
+            command
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content(format!("There is no device profile '{input}'!"))
+                })
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let mut page = match get_unacknowledged(client, profile).await? {
+        Some(page) => page,
+        None => {
+            command
+                .edit_original_interaction_response(ctx, |m| m.content("Failed to get error logs!"))
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    if page.errors.is_empty() {
+        command
+            .edit_original_interaction_response(ctx, |m| {
+                m.content(format!(
+                    "'{}' has no unacknowledged errors!",
+                    profile.device_name
+                ))
+            })
+            .await?;
+
+        return Ok(());
+    }
+
+    let message = command
+        .edit_original_interaction_response(ctx, |m| {
+            m.set_embed(errors_embed(profile, &page))
+                .components(|c| acknowledge_buttons(c, &page))
+        })
+        .await?;
+
+    // Roles allowed when the command was used, the filter can't wait for the permissions.
+    let roles = match command.guild_id {
+        Some(guild_id) => config.permissions().roles(guild_id, COMMAND_NAME).await,
+        None => Vec::new(),
+    };
+    let mut interactions = message
+        .await_component_interactions(ctx)
+        .filter(move |x| {
+            x.member
+                .as_ref()
+                .is_some_and(|member| has_permission(member, &roles))
+        })
+        .timeout(Duration::from_secs(ACKNOWLEDGE_TIMEOUT))
+        .build();
+
+    while let Some(interaction) = interactions.next().await {
+        let error_id = match interaction
+            .data
+            .custom_id
+            .strip_prefix(ACKNOWLEDGE_BUTTON)
+            .and_then(|x| x.parse::<i32>().ok())
+        {
+            Some(error_id) => error_id,
+            None => continue,
+        };
+
+        interaction
+            .create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::DeferredUpdateMessage)
+            })
+            .await?;
+
+        // Acknowledged as the user who clicked, not the one who used the command.
+        match user_client(config, interaction.user.id, &ACKNOWLEDGE_SCOPES).await? {
+            Some(user_client) => {
+                let status = user_client.acknowledge_error_log(error_id).await?;
+
+                // Someone else may have acknowledged it already, the refreshed list shows it either way.
+                if !status.is_success() && status != StatusCode::CONFLICT {
+                    warn!("Failed to acknowledge error log '{error_id}': {status}");
+                }
+            }
+            None => warn!("Failed to acknowledge error log '{error_id}' without a service token."),
+        }
+
+        if let Some(refreshed) = get_unacknowledged(client, profile).await? {
+            page = refreshed;
+        }
+
+        interaction
+            .edit_original_interaction_response(ctx, |m| {
+                if page.errors.is_empty() {
+                    m.content(format!(
+                        "All errors of '{}' are acknowledged!",
+                        profile.device_name
+                    ))
+                    .set_embeds(Vec::new())
+                    .components(|c| c)
+                } else {
+                    m.set_embed(errors_embed(profile, &page))
+                        .components(|c| acknowledge_buttons(c, &page))
+                }
+            })
+            .await?;
+
+        if page.errors.is_empty() {
+            return Ok(());
+        }
+    }
+
+    command
+        .edit_original_interaction_response(ctx, |m| m.components(|c| c))
+        .await?;
+
+    Ok(())
+}
+
+async fn get_unacknowledged(
+    client: &SysInfoClient,
+    profile: &DeviceProfile,
+) -> Result<Option<ErrorLogPage>, CommandError> {
+    let query = ErrorLogQuery {
+        per_page: Some(ERROR_COUNT),
+        acknowledged: Some(false),
+        ..Default::default()
+    };
+
+    match client
+        .get_error_logs(profile.id_device_profile, &query)
+        .await?
+    {
+        (Some(page), _) => Ok(Some(page)),
+        (None, status) => {
+            warn!(
+                "Failed to get error logs of profile '{}': {status}",
+                profile.id_device_profile
+            );
+
+            Ok(None)
+        }
+    }
+}
+
+fn errors_embed(profile: &DeviceProfile, page: &ErrorLogPage) -> CreateEmbed {
+    let colour = match page.errors.iter().map(|x| x.severity).max() {
+        Some(Severity::Critical) => Colour::DARK_RED,
+        Some(Severity::Error) => Colour::RED,
+        Some(Severity::Warning) => Colour::ORANGE,
+        _ => Colour::BLUE,
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(&profile.device_name)
+        .colour(colour)
+        .description(format!("{} unacknowledged errors", page.total));
+
+    for error in &page.errors {
+        let message: String = error.message.chars().take(MESSAGE_LENGTH).collect();
+
+        embed.field(
+            format!(
+                "#{} {} in `{}`",
+                error.id_error_log, error.severity, error.source
+            ),
+            format!("{message}\n<t:{}:R>", error.timestamp.timestamp()),
+            false,
+        );
+    }
+
+    embed
+}
+
+fn acknowledge_buttons<'a>(
+    components: &'a mut CreateComponents,
+    page: &ErrorLogPage,
+) -> &'a mut CreateComponents {
+    components.create_action_row(|row| {
+        for error in &page.errors {
+            row.create_button(|b| {
+                b.custom_id(format!("{ACKNOWLEDGE_BUTTON}{}", error.id_error_log))
+                    .label(format!("Acknowledge #{}", error.id_error_log))
+                    .style(ButtonStyle::Primary)
+            });
+        }
+
+        row
+    })
+}
@@ -0,0 +1,416 @@
+use std::borrow::Cow;
+
+use async_trait::async_trait;
+use botlib::{commands::SlashCommand, error::CommandError, parser::PositionalOptionParser};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use monitoring_core::{
+    api::models::{MetricKind, MetricPoint, Scope, Series},
+    client::SysInfoClient,
+};
+use serenity::{
+    builder::{CreateApplicationCommand, CreateApplicationCommands},
+    model::prelude::{
+        command::CommandOptionType,
+        interaction::{
+            application_command::ApplicationCommandInteraction,
+            autocomplete::AutocompleteInteraction, InteractionResponseType,
+        },
+        AttachmentType,
+    },
+    prelude::Context,
+};
+use tracing::log::warn;
+
+use crate::{
+    commands::authorize,
+    config::BotConfig,
+    graph::{Chart, Points},
+};
+
+use super::profile::{autocomplete_profile, find_profile};
+
+static COMMAND_NAME: &str = "graph";
+static GRAPH_FILE: &str = "graph.png";
+const SCOPES: [Scope; 2] = [Scope::ProfilesRead, Scope::MetricsRead];
+/// The metrics are averaged into buckets so a chart has about this many points.
+const POINT_COUNT: i64 = 200;
+/// Maximum number of mounts drawn, ordered by usage.
+const MOUNT_COUNT: usize = 8;
+
+const METRICS: [(&str, &str); 6] = [
+    ("CPU load", "cpu"),
+    ("Memory", "memory"),
+    ("Load average", "load-average"),
+    ("Network throughput", "network"),
+    ("Disk usage", "disk"),
+    ("Disk I/O", "disk-io"),
+];
+
+const RANGES: [(&str, i64); 5] = [
+    ("1h", 1),
+    ("6h", 6),
+    ("24h", 24),
+    ("7d", 24 * 7),
+    ("30d", 24 * 30),
+];
+
+pub struct GraphCommand;
+
+#[async_trait]
+impl SlashCommand for GraphCommand {
+    type Config = BotConfig;
+
+    fn register<'a>(
+        &'a self,
+        commands: &'a mut CreateApplicationCommands,
+    ) -> &'a mut CreateApplicationCommands {
+        commands.create_application_command(|command| Self::build(command));
+
+        commands
+    }
+
+    async fn dispatch(
+        &self,
+        command: &ApplicationCommandInteraction,
+        ctx: &Context,
+        config: &Self::Config,
+    ) -> Result<(), CommandError> {
+        command
+            .create_interaction_response(ctx, |m| {
+                m.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            })
+            .await?;
+
+        let client = match authorize(command, ctx, config, &SCOPES).await? {
+            Some(client) => client,
+            None => return Ok(()),
+        };
+
+        run_graph(command, ctx, &client).await
+    }
+
+    async fn autocomplete(
+        &self,
+        interaction: &AutocompleteInteraction,
+        ctx: &Context,
+        config: &Self::Config,
+    ) -> Result<(), CommandError> {
+        autocomplete_profile(interaction, ctx, config).await
+    }
+
+    fn name(&self) -> String {
+        String::from(COMMAND_NAME)
+    }
+}
+
+impl GraphCommand {
+    fn build(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+        command
+            .name(COMMAND_NAME)
+            .description("Draws a chart of a metric of a device.")
+            .create_option(|option| {
+                option
+                    .name("profile")
+                    .description("The device profile.")
+                    .kind(CommandOptionType::String)
+                    .set_autocomplete(true)
+                    .required(true)
+            })
+            .create_option(|option| {
+                option
+                    .name("metric")
+                    .description("The metric to draw.")
+                    .kind(CommandOptionType::String)
+                    .required(true);
+
+                for (name, value) in METRICS {
+                    option.add_string_choice(name, value);
+                }
+
+                option
+            })
+            .create_option(|option| {
+                option
+                    .name("range")
+                    .description("The time range up to now.")
+                    .kind(CommandOptionType::String)
+                    .required(true);
+
+                for (name, _) in RANGES {
+                    option.add_string_choice(name, name);
+                }
+
+                option
+            })
+    }
+}
+
+pub async fn run_graph(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+    client: &SysInfoClient,
+) -> Result<(), CommandError> {
+    let input = PositionalOptionParser::parse_string(&command.data.options, 0)?;
+    let metric = PositionalOptionParser::parse_string(&command.data.options, 1)?;
+    let range = PositionalOptionParser::parse_string(&command.data.options, 2)?;
+
+    let hours = match RANGES.iter().find(|(name, _)| *name == range) {
+        Some((_, hours)) => *hours,
+        None => {
+            command
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content(format!("Invalid range '{range}'!"))
+                })
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let profiles = client.get_profiles().await?.0.unwrap_or_default();
+
+    let profile = match find_profile(&profiles, &input) {
+        Some(profile) => profile,
+        None => {
+            command
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content(format!("There is no device profile '{input}'!"))
+                })
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let to = Utc::now().naive_utc();
+    let from = to - Duration::hours(hours);
+    let query = MetricQuery {
+        client,
+        profile_id: profile.id_device_profile,
+        from,
+        to,
+        step: (hours * 3600 / POINT_COUNT) as u32,
+    };
+
+    let title = format!("{} - {range}", profile.device_name);
+    let chart = match metric.as_str() {
+        "cpu" => cpu_chart(&query, &title).await?,
+        "memory" => memory_chart(&query, &title).await?,
+        "load-average" => load_average_chart(&query, &title).await?,
+        "network" => network_chart(&query, &title).await?,
+        "disk" => disk_chart(&query, &title).await?,
+        "disk-io" => disk_io_chart(&query, &title).await?,
+        _ => {
+            command
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content(format!("Invalid metric '{metric}'!"))
+                })
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    if chart.is_empty() {
+        command
+            .edit_original_interaction_response(ctx, |m| {
+                m.content(format!(
+                    "'{}' has no data for this range!",
+                    profile.device_name
+                ))
+            })
+            .await?;
+
+        return Ok(());
+    }
+
+    let png = match chart.render_png(to_utc(from), to_utc(to)) {
+        Ok(png) => png,
+        Err(why) => {
+            warn!("Failed to render {metric} chart: {why}");
+            command
+                .edit_original_interaction_response(ctx, |m| m.content("Failed to draw the chart!"))
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    command
+        .create_followup_message(ctx, |m| {
+            m.add_file(AttachmentType::Bytes {
+                data: Cow::from(png),
+                filename: String::from(GRAPH_FILE),
+            })
+        })
+        .await?;
+
+    Ok(())
+}
+
+struct MetricQuery<'a> {
+    client: &'a SysInfoClient,
+    profile_id: i32,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+    step: u32,
+}
+
+impl MetricQuery<'_> {
+    async fn get(&self, metric: MetricKind) -> Result<Vec<Series>, CommandError> {
+        let (series, status) = self
+            .client
+            .get_metrics(self.profile_id, metric, self.from, self.to, Some(self.step))
+            .await?;
+
+        if series.is_none() {
+            warn!(
+                "Failed to get {metric} metrics of profile '{}': {status}",
+                self.profile_id
+            );
+        }
+
+        Ok(series.map(|x| x.series).unwrap_or_default())
+    }
+}
+
+async fn cpu_chart(query: &MetricQuery<'_>, title: &str) -> Result<Chart, CommandError> {
+    let mut chart = Chart::new(&format!("CPU load {title}"), "%", Some(100.0));
+
+    for series in query.get(MetricKind::Cpu).await? {
+        chart.add_line(
+            "used",
+            map_points(&series, |x| (1.0 - value(x, "idle")) * 100.0),
+        );
+        chart.add_line("user", map_points(&series, |x| value(x, "user") * 100.0));
+        chart.add_line(
+            "system",
+            map_points(&series, |x| value(x, "system") * 100.0),
+        );
+    }
+
+    Ok(chart)
+}
+
+async fn memory_chart(query: &MetricQuery<'_>, title: &str) -> Result<Chart, CommandError> {
+    let mut chart = Chart::new(&format!("Memory usage {title}"), "%", Some(100.0));
+
+    for series in query.get(MetricKind::Memory).await? {
+        chart.add_line("memory", used_points(&series, "available"));
+    }
+
+    for series in query.get(MetricKind::Swap).await? {
+        let points = used_points(&series, "free");
+
+        if !points.is_empty() {
+            chart.add_line("swap", points);
+        }
+    }
+
+    Ok(chart)
+}
+
+async fn load_average_chart(query: &MetricQuery<'_>, title: &str) -> Result<Chart, CommandError> {
+    let mut chart = Chart::new(&format!("Load average {title}"), "load", None);
+
+    for series in query.get(MetricKind::LoadAverage).await? {
+        for name in ["one", "five", "fifteen"] {
+            chart.add_line(name, map_points(&series, |x| value(x, name)));
+        }
+    }
+
+    Ok(chart)
+}
+
+async fn network_chart(query: &MetricQuery<'_>, title: &str) -> Result<Chart, CommandError> {
+    let mut chart = Chart::new(&format!("Network throughput {title}"), "KiB/s", None);
+
+    for series in query.get(MetricKind::NetworkRate).await? {
+        if series.name == "lo" {
+            continue;
+        }
+
+        for (key, direction) in [("rx_bytes", "rx"), ("tx_bytes", "tx")] {
+            chart.add_line(
+                &format!("{} {direction}", series.name),
+                map_points(&series, |x| value(x, key) / 1024.0),
+            );
+        }
+    }
+
+    Ok(chart)
+}
+
+async fn disk_chart(query: &MetricQuery<'_>, title: &str) -> Result<Chart, CommandError> {
+    let mut chart = Chart::new(&format!("Disk usage {title}"), "%", Some(100.0));
+
+    let mut mounts: Vec<(String, Points)> = query
+        .get(MetricKind::Filesystem)
+        .await?
+        .iter()
+        .map(|x| (x.name.clone(), used_points(x, "avail")))
+        .filter(|(_, points)| !points.is_empty())
+        .collect();
+
+    mounts.sort_by(|(_, a), (_, b)| {
+        let a = a.last().map(|(_, x)| *x).unwrap_or_default();
+        let b = b.last().map(|(_, x)| *x).unwrap_or_default();
+
+        b.total_cmp(&a)
+    });
+
+    for (name, points) in mounts.into_iter().take(MOUNT_COUNT) {
+        chart.add_line(&name, points);
+    }
+
+    Ok(chart)
+}
+
+async fn disk_io_chart(query: &MetricQuery<'_>, title: &str) -> Result<Chart, CommandError> {
+    let mut chart = Chart::new(&format!("Disk I/O {title}"), "KiB/s", None);
+
+    for series in query.get(MetricKind::DiskIo).await? {
+        for (key, direction) in [("read_bytes", "read"), ("write_bytes", "write")] {
+            chart.add_line(
+                &format!("{} {direction}", series.name),
+                map_points(&series, |x| value(x, key) / 1024.0),
+            );
+        }
+    }
+
+    Ok(chart)
+}
+
+fn value(point: &MetricPoint, key: &str) -> f64 {
+    point.values.get(key).copied().unwrap_or_default()
+}
+
+fn map_points(series: &Series, f: impl Fn(&MetricPoint) -> f64) -> Points {
+    series
+        .points
+        .iter()
+        .map(|x| (to_utc(x.timestamp), f(x)))
+        .collect()
+}
+
+/// Usage in percent of points with a `total` and a free value in `free_key`.
+/// Points without a total are skipped.
+fn used_points(series: &Series, free_key: &str) -> Points {
+    series
+        .points
+        .iter()
+        .filter(|x| value(x, "total") > 0.0)
+        .map(|x| {
+            let total = value(x, "total");
+
+            (
+                to_utc(x.timestamp),
+                (total - value(x, free_key)) / total * 100.0,
+            )
+        })
+        .collect()
+}
+
+fn to_utc(date: NaiveDateTime) -> DateTime<Utc> {
+    DateTime::from_utc(date, Utc)
+}
@@ -1 +1,88 @@
+use botlib::error::CommandError;
+use monitoring_core::{api::models::Scope, client::SysInfoClient};
+use serenity::{
+    model::prelude::{interaction::application_command::ApplicationCommandInteraction, UserId},
+    prelude::Context,
+};
+use tracing::log::warn;
+
+use crate::config::BotConfig;
+
+pub mod admin;
+pub mod alerts;
+pub mod errors;
+pub mod graph;
 pub mod profile;
+pub mod status;
+
+/// Returns true if the member may use the command in this guild, otherwise the deferred
+/// response is edited to tell them.
+pub async fn check_permission(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+    config: &BotConfig,
+) -> Result<bool, CommandError> {
+    let allowed = match (command.guild_id, &command.member) {
+        (Some(guild_id), Some(member)) => {
+            config
+                .permissions()
+                .is_allowed(guild_id, &command.data.name, member)
+                .await
+        }
+        _ => false,
+    };
+
+    if !allowed {
+        command
+            .edit_original_interaction_response(ctx, |m| {
+                m.content("You are not allowed to use this command!")
+            })
+            .await?;
+    }
+
+    Ok(allowed)
+}
+
+/// Checks the permission of the member and returns a client acting as them with the scopes.
+/// `None` if the command can't be used, the deferred response was already edited then.
+pub async fn authorize(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+    config: &BotConfig,
+    scopes: &[Scope],
+) -> Result<Option<SysInfoClient>, CommandError> {
+    if !check_permission(command, ctx, config).await? {
+        return Ok(None);
+    }
+
+    let client = user_client(config, command.user.id, scopes).await?;
+
+    if client.is_none() {
+        command
+            .edit_original_interaction_response(ctx, |m| {
+                m.content("Failed to authorize with the monitoring service!")
+            })
+            .await?;
+    }
+
+    Ok(client)
+}
+
+/// Returns a client with the service token of the Discord user, the permission has to be
+/// checked before.
+pub async fn user_client(
+    config: &BotConfig,
+    user_id: UserId,
+    scopes: &[Scope],
+) -> Result<Option<SysInfoClient>, CommandError> {
+    let client = config
+        .identities()
+        .client(&config.client(), config.server_url(), user_id, scopes)
+        .await?;
+
+    if client.is_none() {
+        warn!("Failed to get a service token for user '{user_id}'.");
+    }
+
+    Ok(client)
+}
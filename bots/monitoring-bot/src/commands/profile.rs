@@ -1,22 +1,40 @@
-use std::path::PathBuf;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    time::Duration,
+};
 
 use async_trait::async_trait;
-use botlib::{commands::SlashCommand, error::CommandError, parser::PositionalOptionParser};
-use monitoring_core::{client::{SysInfoClient, ClientConfig}, api::models::InsertDeviceProfile};
-use rand::Rng;
+use botlib::{
+    commands::SlashCommand, error::CommandError, parser::PositionalOptionParser,
+    utils::get_icon_url,
+};
+use monitoring_core::{client::SysInfoClient, api::models::{DeviceProfile, InsertDeviceProfile, NewProfileKey, RotateProfileKey, Scope}};
 use serenity::{
-    builder::{CreateApplicationCommand, CreateApplicationCommands},
+    builder::{CreateApplicationCommand, CreateApplicationCommands, CreateComponents, CreateEmbed},
+    futures::StreamExt,
     model::prelude::{interaction::{
-        application_command::{ApplicationCommandInteraction, CommandDataOption}, InteractionResponseType,
-    }, command::CommandOptionType},
+        application_command::ApplicationCommandInteraction, autocomplete::AutocompleteInteraction,
+        InteractionResponseType,
+    }, command::CommandOptionType, component::ButtonStyle, UserId},
+    model::user::User,
     prelude::Context,
 };
-use tokio::fs::read_dir;
-use tracing::log::{info, warn};
+use tracing::log::warn;
 
-use crate::config::BotConfig;
+use crate::{commands::{authorize, user_client}, config::BotConfig};
 
 static COMMAND_NAME: &str = "profile";
+/// Discord allows at most 10 embeds per message.
+const PAGE_SIZE: usize = 10;
+const PREVIOUS_BUTTON: &str = "profile-view-previous";
+const NEXT_BUTTON: &str = "profile-view-next";
+/// Seconds the page buttons can be used.
+const PAGINATION_TIMEOUT: u64 = 120;
+/// Discord allows at most 25 autocomplete choices.
+const MAX_CHOICES: usize = 25;
+/// `view` only reads the profiles, `create` and `rotate-key` also change them.
+const READ_SCOPES: [Scope; 1] = [Scope::ProfilesRead];
+const WRITE_SCOPES: [Scope; 2] = [Scope::ProfilesRead, Scope::ProfilesWrite];
 
 pub struct ProfileCommand;
 
@@ -27,7 +45,7 @@ impl SlashCommand for ProfileCommand {
     fn register<'a>(
         &'a self,
         commands: &'a mut CreateApplicationCommands,
-    ) -> &mut CreateApplicationCommands {
+    ) -> &'a mut CreateApplicationCommands {
         commands.create_application_command(|command| Self::build(command));
 
         commands
@@ -45,19 +63,21 @@ impl SlashCommand for ProfileCommand {
             })
             .await?;
 
-        if !config.allowed_users().iter().any(|x| *x == command.user.id.0) {
-            command
-                .edit_original_interaction_response(ctx, |m| {
-                    m.content("You are not allowed to use this command!")
-                })
-                .await?;
-    
-            return Ok(());
-        }
+        let sub_command = command.data.options[0].name.as_str();
+        let scopes: &[Scope] = match sub_command {
+            "create" | "rotate-key" => &WRITE_SCOPES,
+            _ => &READ_SCOPES,
+        };
+
+        let client = match authorize(command, ctx, config, scopes).await? {
+            Some(client) => client,
+            None => return Ok(()),
+        };
 
-        match command.data.options[0].name.as_str() {
-            "create" => run_create(command, ctx, config).await,
-            "view" => run_view(command, ctx, config).await,
+        match sub_command {
+            "create" => run_create(command, ctx, &client).await,
+            "view" => run_view(command, ctx, config, &client).await,
+            "rotate-key" => run_rotate_key(command, ctx, &client).await,
             _ => {
                 warn!("Invalid command option found!");
                 command
@@ -71,6 +91,15 @@ impl SlashCommand for ProfileCommand {
         }
     }
 
+    async fn autocomplete(
+        &self,
+        interaction: &AutocompleteInteraction,
+        ctx: &Context,
+        config: &Self::Config,
+    ) -> Result<(), CommandError> {
+        autocomplete_profile(interaction, ctx, config).await
+    }
+
     fn name(&self) -> String {
         String::from(COMMAND_NAME)
     }
@@ -92,14 +121,29 @@ impl ProfileCommand {
                             .description("The name of the device-profile.")
                             .kind(CommandOptionType::String)
                             .required(true)
-                    })  
+                    })
+            })
+            .create_option(|sub_command| {
+                sub_command
+                    .name("rotate-key")
+                    .description("Generates a new profile key, the old key stays valid for a grace period.")
+                    .kind(CommandOptionType::SubCommand)
                     .create_sub_option(|option| {
                         option
-                            .name("profile-key")
-                            .description("The profile-key for this device profile.")
+                            .name("profile")
+                            .description("The device profile.")
                             .kind(CommandOptionType::String)
+                            .set_autocomplete(true)
                             .required(true)
                     })
+                    .create_sub_option(|option| {
+                        option
+                            .name("grace-hours")
+                            .description("Hours the old key stays valid, defaults to 24.")
+                            .kind(CommandOptionType::Integer)
+                            .min_int_value(0)
+                            .max_int_value(RotateProfileKey::MAX_GRACE_PERIOD / (60 * 60))
+                    })
             })
             .create_option(|sub_command| {
                 sub_command
@@ -113,39 +157,381 @@ impl ProfileCommand {
 pub async fn run_create(
     command: &ApplicationCommandInteraction,
     ctx: &Context,
-    config: &BotConfig,
+    client: &SysInfoClient,
 ) -> Result<(), CommandError> {
-    let client_config = ClientConfig::new(config.api_token(), config.server_url());
-    let client = SysInfoClient::new(client_config);
-
     let device_name = PositionalOptionParser::parse_string(&command.data.options[0].options, 0)?;
-    let profile_key = PositionalOptionParser::parse_string(&command.data.options[0].options, 1)?;
 
-    let insert_profile = InsertDeviceProfile {
-        device_name,
-        profile_key,
-        create_user: command.user.id.0 as i64,
-    };
+    let insert_profile = InsertDeviceProfile { device_name };
 
-    client.post_profile(insert_profile).await?;
+    let key = match client.post_profile(insert_profile).await? {
+        (Some(key), _) => key,
+        (None, status) => {
+            warn!("Failed to create device profile: {status}");
+            command
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content("Failed to create device profile!")
+                })
+                .await?;
+
+            return Ok(());
+        }
+    };
 
     command
         .edit_original_interaction_response(ctx, |m| {
-            m.content("Created new device profile!")
+            m.content(format!("Created new device profile with id {}!", key.profile_id))
         })
         .await?;
 
-    Ok(())
+    send_key(command, ctx, &key).await
+}
+
+pub async fn run_rotate_key(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+    client: &SysInfoClient,
+) -> Result<(), CommandError> {
+    let options = &command.data.options[0].options;
+    let input = PositionalOptionParser::parse_string(options, 0)?;
+    let grace_hours = PositionalOptionParser::parser_integer(options, 1).ok();
+
+    let grace_period = match grace_hours.map(|x| x.checked_mul(60 * 60)) {
+        Some(None) => {
+            command
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content("The grace period is too long!")
+                })
+                .await?;
+
+            return Ok(());
+        }
+        grace_period => grace_period.flatten(),
+    };
+
+    let profiles = client
+        .get_profiles()
+        .await?
+        .0
+        .unwrap_or_default();
+
+    let profile = match find_profile(&profiles, &input) {
+        Some(profile) => profile,
+        None => {
+            command
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content(format!("There is no device profile '{input}'!"))
+                })
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let rotation = RotateProfileKey {
+        grace_period,
+    };
+
+    let key = match client
+        .rotate_profile_key(profile.id_device_profile, rotation)
+        .await?
+    {
+        (Some(key), _) => key,
+        (None, status) => {
+            warn!(
+                "Failed to rotate the key of profile '{}': {status}",
+                profile.id_device_profile
+            );
+            command
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content("Failed to rotate the profile key!")
+                })
+                .await?;
 
+            return Ok(());
+        }
+    };
+
+    let content = match key.previous_expire_date {
+        Some(expire_date) => format!(
+            "Rotated the key of '{}', the old key stays valid until <t:{}:f>.",
+            profile.device_name,
+            expire_date.timestamp()
+        ),
+        None => format!("Rotated the key of '{}'.", profile.device_name),
+    };
+
+    command
+        .edit_original_interaction_response(ctx, |m| m.content(content))
+        .await?;
+
+    send_key(command, ctx, &key).await
+}
+
+/// Shows the key only to the user of the command, it can't be retrieved again.
+async fn send_key(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+    key: &NewProfileKey,
+) -> Result<(), CommandError> {
+    command
+        .create_followup_message(ctx, |m| {
+            m.ephemeral(true).content(format!(
+                "Profile key of profile {}, it won't be shown again:\n`{}`",
+                key.profile_id, key.profile_key
+            ))
+        })
+        .await?;
+
+    Ok(())
 }
 
 pub async fn run_view(
     command: &ApplicationCommandInteraction,
     ctx: &Context,
     config: &BotConfig,
+    client: &SysInfoClient,
 ) -> Result<(), CommandError> {
-    let client_config = ClientConfig::new(config.api_token(), config.server_url());
-    let client = SysInfoClient::new(client_config);
+    let profiles = match client.get_profiles().await? {
+        (Some(profiles), _) => profiles,
+        (None, status) => {
+            warn!("Failed to get device profiles: {status}");
+            command
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content("Failed to get device profiles!")
+                })
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    if profiles.is_empty() {
+        command
+            .edit_original_interaction_response(ctx, |m| {
+                m.content("There are no device profiles yet!")
+            })
+            .await?;
+
+        return Ok(());
+    }
+
+    // The profiles only know the service user, the Discord user is looked up with the admin token.
+    let users = match config.client().get_users().await? {
+        (Some(users), _) => users,
+        (None, status) => {
+            warn!("Failed to get service users: {status}");
+            Vec::new()
+        }
+    };
+
+    let mut creators = HashMap::new();
+
+    for profile in &profiles {
+        if let Entry::Vacant(entry) = creators.entry(profile.create_user) {
+            let user = match users.iter().find(|x| i64::from(x.id_user) == profile.create_user) {
+                Some(user) => user,
+                None => continue,
+            };
+
+            let creator = match user.discord_id {
+                Some(discord_id) => match UserId(discord_id as u64).to_user(ctx).await {
+                    Ok(discord_user) => Creator::Discord(discord_user),
+                    Err(why) => {
+                        warn!("Failed to get user '{discord_id}': {why}");
+                        Creator::Service(user.name.clone())
+                    },
+                },
+                None => Creator::Service(user.name.clone()),
+            };
+
+            entry.insert(creator);
+        }
+    }
+
+    let page_count = profiles.len().div_ceil(PAGE_SIZE);
+    let pages: Vec<Vec<CreateEmbed>> = profiles
+        .chunks(PAGE_SIZE)
+        .enumerate()
+        .map(|(page, chunk)| {
+            let mut embeds: Vec<CreateEmbed> = chunk
+                .iter()
+                .map(|profile| profile_embed(profile, creators.get(&profile.create_user)))
+                .collect();
+
+            if let Some(last) = embeds.last_mut() {
+                last.footer(|f| f.text(format!("Page {}/{page_count}", page + 1)));
+            }
+
+            embeds
+        })
+        .collect();
+
+    let mut page = 0;
+
+    let message = command
+        .edit_original_interaction_response(ctx, |m| {
+            m.set_embeds(pages[page].clone());
+
+            if page_count > 1 {
+                m.components(|c| page_buttons(c, page, page_count));
+            }
+
+            m
+        })
+        .await?;
+
+    if page_count == 1 {
+        return Ok(());
+    }
+
+    let mut interactions = message
+        .await_component_interactions(ctx)
+        .author_id(command.user.id)
+        .timeout(Duration::from_secs(PAGINATION_TIMEOUT))
+        .build();
+
+    while let Some(interaction) = interactions.next().await {
+        match interaction.data.custom_id.as_str() {
+            PREVIOUS_BUTTON => page = page.saturating_sub(1),
+            NEXT_BUTTON => page = (page + 1).min(page_count - 1),
+            _ => continue,
+        }
+
+        interaction
+            .create_interaction_response(ctx, |r| {
+                r.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| {
+                        d.set_embeds(pages[page].clone())
+                            .components(|c| page_buttons(c, page, page_count))
+                    })
+            })
+            .await?;
+    }
+
+    command
+        .edit_original_interaction_response(ctx, |m| m.components(|c| c))
+        .await?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// The user who created a profile, a Discord user or a service user like the admin.
+enum Creator {
+    Discord(User),
+    Service(String),
+}
+
+fn profile_embed(profile: &DeviceProfile, creator: Option<&Creator>) -> CreateEmbed {
+    let last_seen = match profile.last_seen {
+        Some(last_seen) => format!("<t:{}:R>", last_seen.timestamp()),
+        None => String::from("Never"),
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title(&profile.device_name)
+        .field("Id", profile.id_device_profile, true)
+        .field(
+            "Created",
+            format!("<t:{}:f>", profile.create_date.timestamp()),
+            true,
+        )
+        .field("Last seen", last_seen, true);
+
+    match creator {
+        Some(Creator::Discord(user)) => embed.author(|a| a.name(user.tag()).icon_url(get_icon_url(user))),
+        Some(Creator::Service(name)) => embed.author(|a| a.name(name)),
+        None => embed.author(|a| a.name(format!("Unknown user {}", profile.create_user))),
+    };
+
+    embed
+}
+
+fn page_buttons(
+    components: &mut CreateComponents,
+    page: usize,
+    page_count: usize,
+) -> &mut CreateComponents {
+    components.create_action_row(|row| {
+        row.create_button(|b| {
+            b.custom_id(PREVIOUS_BUTTON)
+                .label("Previous")
+                .style(ButtonStyle::Secondary)
+                .disabled(page == 0)
+        })
+        .create_button(|b| {
+            b.custom_id(NEXT_BUTTON)
+                .label("Next")
+                .style(ButtonStyle::Secondary)
+                .disabled(page + 1 >= page_count)
+        })
+    })
+}
+
+/// Answers the autocomplete of a profile option with the matching device names.
+/// The value of each choice is the id of the profile.
+pub async fn autocomplete_profile(
+    interaction: &AutocompleteInteraction,
+    ctx: &Context,
+    config: &BotConfig,
+) -> Result<(), CommandError> {
+    let input = interaction
+        .data
+        .options
+        .iter()
+        .flat_map(|x| std::iter::once(x).chain(x.options.iter()))
+        .find(|x| x.focused)
+        .and_then(|x| x.value.as_ref())
+        .and_then(|x| x.as_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let allowed = match (interaction.guild_id, &interaction.member) {
+        (Some(guild_id), Some(member)) => {
+            config
+                .permissions()
+                .is_allowed(guild_id, &interaction.data.name, member)
+                .await
+        }
+        _ => false,
+    };
+
+    let client = if allowed {
+        user_client(config, interaction.user.id, &READ_SCOPES).await?
+    } else {
+        None
+    };
+
+    let profiles = match client {
+        Some(client) => client.get_profiles().await?.0.unwrap_or_default(),
+        None => Vec::new(),
+    };
+
+    interaction
+        .create_autocomplete_response(ctx, |r| {
+            for profile in profiles
+                .iter()
+                .filter(|x| x.device_name.to_lowercase().contains(&input))
+                .take(MAX_CHOICES)
+            {
+                r.add_string_choice(&profile.device_name, profile.id_device_profile);
+            }
+
+            r
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Finds a profile by its id, or by its device name if the input wasn't picked from the autocomplete.
+pub fn find_profile<'a>(profiles: &'a [DeviceProfile], input: &str) -> Option<&'a DeviceProfile> {
+    profiles
+        .iter()
+        .find(|x| x.id_device_profile.to_string() == input)
+        .or_else(|| {
+            profiles
+                .iter()
+                .find(|x| x.device_name.eq_ignore_ascii_case(input))
+        })
+}
@@ -0,0 +1,405 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use botlib::{commands::SlashCommand, error::CommandError, parser::PositionalOptionParser};
+use chrono::{DateTime, Utc};
+use monitoring_core::{
+    api::models::Scope,
+    client::SysInfoClient,
+    models::{CheckStatus, SystemInformation},
+};
+use serenity::{
+    builder::{CreateApplicationCommand, CreateApplicationCommands, CreateEmbed},
+    model::prelude::{
+        command::CommandOptionType,
+        interaction::{
+            application_command::ApplicationCommandInteraction,
+            autocomplete::AutocompleteInteraction, InteractionResponseType,
+        },
+    },
+    prelude::Context,
+    utils::Colour,
+};
+use tracing::log::warn;
+
+use crate::{commands::authorize, config::BotConfig};
+
+use super::profile::{autocomplete_profile, find_profile};
+
+static COMMAND_NAME: &str = "status";
+/// Number of mounts shown, ordered by usage.
+const MOUNT_COUNT: usize = 3;
+const SCOPES: [Scope; 2] = [Scope::ProfilesRead, Scope::MetricsRead];
+/// Number of processes shown, ordered by cpu load.
+const PROCESS_COUNT: usize = 3;
+/// Number of temperature sensors shown, hottest first.
+const SENSOR_COUNT: usize = 3;
+/// Number of failed checks shown.
+const CHECK_COUNT: usize = 5;
+/// Characters of the output shown per check, the whole field is limited to 1024.
+const CHECK_OUTPUT_LENGTH: usize = 150;
+const BAR_LENGTH: usize = 10;
+
+pub struct StatusCommand;
+
+#[async_trait]
+impl SlashCommand for StatusCommand {
+    type Config = BotConfig;
+
+    fn register<'a>(
+        &'a self,
+        commands: &'a mut CreateApplicationCommands,
+    ) -> &'a mut CreateApplicationCommands {
+        commands.create_application_command(|command| Self::build(command));
+
+        commands
+    }
+
+    async fn dispatch(
+        &self,
+        command: &ApplicationCommandInteraction,
+        ctx: &Context,
+        config: &Self::Config,
+    ) -> Result<(), CommandError> {
+        command
+            .create_interaction_response(ctx, |m| {
+                m.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+            })
+            .await?;
+
+        let client = match authorize(command, ctx, config, &SCOPES).await? {
+            Some(client) => client,
+            None => return Ok(()),
+        };
+
+        run_status(command, ctx, &client).await
+    }
+
+    async fn autocomplete(
+        &self,
+        interaction: &AutocompleteInteraction,
+        ctx: &Context,
+        config: &Self::Config,
+    ) -> Result<(), CommandError> {
+        autocomplete_profile(interaction, ctx, config).await
+    }
+
+    fn name(&self) -> String {
+        String::from(COMMAND_NAME)
+    }
+}
+
+impl StatusCommand {
+    fn build(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+        command
+            .name(COMMAND_NAME)
+            .description("Shows the latest health of a device.")
+            .create_option(|option| {
+                option
+                    .name("profile")
+                    .description("The device profile.")
+                    .kind(CommandOptionType::String)
+                    .set_autocomplete(true)
+                    .required(true)
+            })
+    }
+}
+
+pub async fn run_status(
+    command: &ApplicationCommandInteraction,
+    ctx: &Context,
+    client: &SysInfoClient,
+) -> Result<(), CommandError> {
+    let input = PositionalOptionParser::parse_string(&command.data.options, 0)?;
+
+    let profiles = client.get_profiles().await?.0.unwrap_or_default();
+
+    let profile = match find_profile(&profiles, &input) {
+        Some(profile) => profile,
+        None => {
+            command
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content(format!("There is no device profile '{input}'!"))
+                })
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let info = match client
+        .get_latest_sys_info(profile.id_device_profile)
+        .await?
+    {
+        (Some(info), _) => info,
+        (None, status) => {
+            warn!(
+                "Failed to get system info of profile '{}': {status}",
+                profile.id_device_profile
+            );
+            command
+                .edit_original_interaction_response(ctx, |m| {
+                    m.content(format!(
+                        "'{}' didn't send any data yet!",
+                        profile.device_name
+                    ))
+                })
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let mut embed = status_embed(&info);
+    embed.title(&profile.device_name);
+
+    if let Some(last_seen) = profile.last_seen {
+        embed.timestamp(DateTime::<Utc>::from_utc(last_seen, Utc));
+    }
+
+    command
+        .edit_original_interaction_response(ctx, |m| m.add_embed(embed))
+        .await?;
+
+    Ok(())
+}
+
+fn status_embed(info: &SystemInformation) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed
+        .colour(Colour::DARK_GREEN)
+        .description(format!("`{}`", info.hostname.to_string_lossy()))
+        .field("Uptime", format_duration(info.uptime), true);
+
+    if let Some(os_info) = info.os_info.as_ref() {
+        embed.field("OS", os_info, true);
+    }
+
+    if let Some(cpu) = info.cpu.as_ref() {
+        let mut value = usage_bar(1.0 - cpu.aggregate_load.idle as f64);
+
+        if let Some(temperature) = cpu.temperature {
+            value.push_str(&format!("\n{temperature:.1} °C"));
+        }
+
+        embed.field("CPU", value, false);
+    }
+
+    if let Some(l_avg) = info.load_avg.as_ref() {
+        embed.field(
+            "Load average",
+            format!(
+                "{:.2} / {:.2} / {:.2}",
+                l_avg.one, l_avg.five, l_avg.fifteen
+            ),
+            true,
+        );
+    }
+
+    if let Some(memory) = info.memory.as_ref() {
+        embed.field(
+            "Memory",
+            format!(
+                "{}\n{} buffers, {} cached, {} shared, {} slab",
+                usage_with_bytes(memory.total.saturating_sub(memory.available), memory.total),
+                format_bytes(memory.buffers),
+                format_bytes(memory.cached),
+                format_bytes(memory.shared),
+                format_bytes(memory.slab)
+            ),
+            false,
+        );
+
+        if memory.dirty > 0 || memory.writeback > 0 {
+            embed.field(
+                "Dirty / writeback",
+                format!(
+                    "{} / {}",
+                    format_bytes(memory.dirty),
+                    format_bytes(memory.writeback)
+                ),
+                true,
+            );
+        }
+
+        if memory.hugepages_total > 0 {
+            embed.field(
+                "Hugepages",
+                usage_with_bytes(
+                    memory.hugepages_total - memory.hugepages_free,
+                    memory.hugepages_total,
+                ),
+                true,
+            );
+        }
+    }
+
+    if let Some(swap) = info.swap.as_ref().filter(|x| x.total > 0) {
+        embed.field(
+            "Swap",
+            usage_with_bytes(swap.total.saturating_sub(swap.free), swap.total),
+            false,
+        );
+    }
+
+    let mut mounts: Vec<_> = info.mounts.iter().filter(|x| x.total > 0).collect();
+    mounts.sort_by(|a, b| {
+        let a = a.total.saturating_sub(a.avail) as f64 / a.total as f64;
+        let b = b.total.saturating_sub(b.avail) as f64 / b.total as f64;
+
+        b.total_cmp(&a)
+    });
+
+    if !mounts.is_empty() {
+        let mounts = mounts
+            .iter()
+            .take(MOUNT_COUNT)
+            .map(|x| {
+                format!(
+                    "`{}`\n{}",
+                    x.fs_mounted_on,
+                    usage_with_bytes(x.total.saturating_sub(x.avail), x.total)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        embed.field("Mounts", mounts, false);
+    }
+
+    if let Some(processes) = info.processes.as_ref().filter(|x| !x.by_cpu.is_empty()) {
+        let processes = processes
+            .by_cpu
+            .iter()
+            .take(PROCESS_COUNT)
+            .map(|x| {
+                format!(
+                    "`{}` ({}) {:.1}% cpu, {}",
+                    x.name,
+                    x.pid,
+                    x.cpu_usage * 100.0,
+                    format_bytes(x.rss)
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        embed.field("Top processes", processes, false);
+    }
+
+    if let Some(thermals) = info
+        .thermals
+        .as_ref()
+        .filter(|x| !x.temperatures.is_empty())
+    {
+        let mut sensors: Vec<(&str, &str, f32)> = thermals
+            .temperatures
+            .iter()
+            .filter_map(|x| Some((x.chip.as_str(), x.label.as_str(), x.current?)))
+            .collect();
+        sensors.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+        let sensors = sensors
+            .iter()
+            .take(SENSOR_COUNT)
+            .map(|(chip, label, current)| format!("`{chip}/{label}` {current:.1} °C"))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        if !sensors.is_empty() {
+            embed.field("Temperatures", sensors, false);
+        }
+    }
+
+    if !info.checks.is_empty() {
+        let failed: Vec<String> = info
+            .checks
+            .iter()
+            .filter(|x| x.status != CheckStatus::Ok)
+            .map(|x| {
+                let output: String = x.output.chars().take(CHECK_OUTPUT_LENGTH).collect();
+
+                format!("`{}` {}: {output}", x.name, x.status)
+            })
+            .collect();
+
+        let checks = if failed.is_empty() {
+            format!("All {} checks ok", info.checks.len())
+        } else {
+            failed
+                .into_iter()
+                .take(CHECK_COUNT)
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+
+        embed.field("Checks", checks, false);
+    }
+
+    if let Some(battery) = info.battery_life.as_ref() {
+        embed.field(
+            "Battery",
+            format!(
+                "{:.0}% ({} left)",
+                battery.remaining_capacity * 100.0,
+                format_duration(battery.remaining_time)
+            ),
+            true,
+        );
+    }
+
+    embed
+}
+
+fn usage_with_bytes(used: u64, total: u64) -> String {
+    format!(
+        "{} ({} / {})",
+        usage_bar(used as f64 / total as f64),
+        format_bytes(used),
+        format_bytes(total)
+    )
+}
+
+/// Renders a fraction between 0 and 1 as a bar followed by the percentage.
+fn usage_bar(usage: f64) -> String {
+    let usage = if usage.is_finite() {
+        usage.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let filled = (usage * BAR_LENGTH as f64).round() as usize;
+
+    format!(
+        "`{}{}` {:.0}%",
+        "█".repeat(filled),
+        "░".repeat(BAR_LENGTH - filled),
+        usage * 100.0
+    )
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
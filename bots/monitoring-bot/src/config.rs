@@ -1,28 +1,46 @@
-use std::{env, num::ParseIntError};
+use std::env;
+
+use monitoring_core::client::{ClientConfig, SysInfoClient};
+
+use crate::{
+    identities::ServiceIdentities, permissions::CommandPermissions,
+    subscriptions::AlertSubscriptions,
+};
 
 pub struct AppConfigurations {
     pub bot_token: String,
     pub server_url: String,
     pub api_token: String,
-    pub read_token: String,
-    pub allowed_users: Vec<u64>,
+    pub subscriptions_file: String,
+    pub permissions_file: String,
+    pub identities_file: String,
+    pub alert_interval: u64,
+    pub graph_font: String,
 }
 
 #[derive(Clone)]
 pub struct BotConfig {
     server_url: String,
     api_token: String,
-    read_token: String,
-    allowed_users: Vec<u64>,
+    subscriptions: AlertSubscriptions,
+    permissions: CommandPermissions,
+    identities: ServiceIdentities,
 }
 
 impl BotConfig {
-    pub fn new(api_token: &str, read_token: &str, server_url: &str, allowed_users: &[u64]) -> Self {
+    pub fn new(
+        api_token: &str,
+        server_url: &str,
+        subscriptions: AlertSubscriptions,
+        permissions: CommandPermissions,
+        identities: ServiceIdentities,
+    ) -> Self {
         Self {
             api_token: String::from(api_token),
-            read_token: String::from(read_token),
             server_url: String::from(server_url),
-            allowed_users: allowed_users.to_vec(),
+            subscriptions,
+            permissions,
+            identities,
         }
     }
 
@@ -30,16 +48,25 @@ impl BotConfig {
         self.api_token.as_ref()
     }
 
-    pub fn read_token(&self) -> &str {
-        self.read_token.as_ref()
-    }
-
     pub fn server_url(&self) -> &str {
         self.server_url.as_ref()
     }
 
-    pub fn allowed_users(&self) -> &[u64] {
-        self.allowed_users.as_ref()
+    /// A client with the token of the bot, for requests not made on behalf of a user.
+    pub fn client(&self) -> SysInfoClient {
+        SysInfoClient::new(ClientConfig::new(self.api_token(), self.server_url()))
+    }
+
+    pub fn subscriptions(&self) -> &AlertSubscriptions {
+        &self.subscriptions
+    }
+
+    pub fn permissions(&self) -> &CommandPermissions {
+        &self.permissions
+    }
+
+    pub fn identities(&self) -> &ServiceIdentities {
+        &self.identities
     }
 }
 
@@ -48,23 +75,31 @@ impl AppConfigurations {
         let token = env::var("MONITORING_BOT_TOKEN").expect("Expected a token in the environment!");
         let server_url =
             env::var("MONITORING_SERVER_URL").expect("Expected server url in environment!");
-        let api_token = env::var("MONITORING_API_KEY").expect("Expected api token in environment!");
-        let read_token =
-            env::var("MONITORING_READ_KEY").expect("Expected read key in environment!");
-        let allowed_users =
-            env::var("MONITORING_ALLOWED_USERS").expect("Expected allowed users in environment!");
-        let allowed_users = allowed_users
-            .split(';')
-            .map(|x| x.parse::<u64>())
-            .collect::<Result<Vec<u64>, ParseIntError>>()
-            .expect("Invalid users in allowed users!");
+        let api_token =
+            env::var("MONITORING_API_TOKEN").expect("Expected api token in environment!");
+        let subscriptions_file = env::var("MONITORING_SUBSCRIPTIONS_FILE")
+            .unwrap_or_else(|_| String::from("alert_subscriptions.json"));
+        let permissions_file = env::var("MONITORING_PERMISSIONS_FILE")
+            .unwrap_or_else(|_| String::from("command_permissions.json"));
+        let identities_file = env::var("MONITORING_IDENTITIES_FILE")
+            .unwrap_or_else(|_| String::from("service_identities.json"));
+        let alert_interval = env::var("MONITORING_ALERT_POLL_INTERVAL")
+            .ok()
+            .and_then(|interval| interval.parse().ok())
+            .filter(|interval| *interval > 0)
+            .unwrap_or(60);
+        let graph_font = env::var("MONITORING_GRAPH_FONT")
+            .unwrap_or_else(|_| String::from("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf"));
 
         AppConfigurations {
             bot_token: token,
             server_url,
             api_token,
-            read_token,
-            allowed_users,
+            subscriptions_file,
+            permissions_file,
+            identities_file,
+            alert_interval,
+            graph_font,
         }
     }
 }
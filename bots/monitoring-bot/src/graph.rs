@@ -0,0 +1,137 @@
+use std::{fs, io::Cursor};
+
+use chrono::{DateTime, Utc};
+use image::{ImageOutputFormat, RgbImage};
+use plotters::{prelude::*, style::register_font};
+
+const WIDTH: u32 = 1000;
+const HEIGHT: u32 = 500;
+
+pub type Points = Vec<(DateTime<Utc>, f64)>;
+
+/// Loads the ttf font used for all text in charts.
+/// Has to be called once before rendering a chart.
+pub fn load_font(path: &str) -> Result<(), String> {
+    let bytes = fs::read(path).map_err(|why| format!("Failed to read font '{path}': {why}"))?;
+
+    register_font(
+        "sans-serif",
+        FontStyle::Normal,
+        Box::leak(bytes.into_boxed_slice()),
+    )
+    .map_err(|_| format!("Invalid font '{path}'."))
+}
+
+/// A line chart with one line per series, rendered to a png.
+pub struct Chart {
+    pub title: String,
+    pub y_desc: String,
+    /// Fixes the y axis to 0..=max, otherwise it's fitted to the values.
+    pub y_max: Option<f64>,
+    pub lines: Vec<Line>,
+}
+
+pub struct Line {
+    pub name: String,
+    pub points: Points,
+}
+
+impl Chart {
+    pub fn new(title: &str, y_desc: &str, y_max: Option<f64>) -> Self {
+        Self {
+            title: String::from(title),
+            y_desc: String::from(y_desc),
+            y_max,
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn add_line(&mut self, name: &str, points: Points) {
+        self.lines.push(Line {
+            name: String::from(name),
+            points,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(|x| x.points.is_empty())
+    }
+
+    pub fn render_png(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<u8>, String> {
+        let mut buffer = vec![0; (WIDTH * HEIGHT * 3) as usize];
+
+        self.draw(&mut buffer, from, to)
+            .map_err(|why| why.to_string())?;
+
+        let image = RgbImage::from_raw(WIDTH, HEIGHT, buffer)
+            .ok_or_else(|| String::from("Invalid image buffer size."))?;
+        let mut png = Cursor::new(Vec::new());
+
+        image
+            .write_to(&mut png, ImageOutputFormat::Png)
+            .map_err(|why| why.to_string())?;
+
+        Ok(png.into_inner())
+    }
+
+    fn draw(
+        &self,
+        buffer: &mut [u8],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<(), DrawingAreaErrorKind<<BitMapBackend<'_> as DrawingBackend>::ErrorType>> {
+        let root = BitMapBackend::with_buffer(buffer, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&WHITE)?;
+
+        let y_max = self.y_max.unwrap_or_else(|| {
+            let max = self
+                .lines
+                .iter()
+                .flat_map(|x| x.points.iter().map(|(_, value)| *value))
+                .fold(0.0, f64::max);
+
+            if max > 0.0 {
+                max * 1.1
+            } else {
+                1.0
+            }
+        });
+
+        let mut chart = ChartBuilder::on(&root)
+            .caption(&self.title, ("sans-serif", 24))
+            .margin(16)
+            .x_label_area_size(40)
+            .y_label_area_size(70)
+            .build_cartesian_2d(from..to, 0.0..y_max)?;
+
+        chart
+            .configure_mesh()
+            .x_labels(8)
+            .x_label_formatter(&|x| x.format("%d.%m %H:%M").to_string())
+            .y_desc(&self.y_desc)
+            .draw()?;
+
+        for (index, line) in self.lines.iter().enumerate() {
+            let colour = Palette99::pick(index).to_rgba();
+
+            chart
+                .draw_series(LineSeries::new(
+                    line.points.iter().copied(),
+                    colour.stroke_width(2),
+                ))?
+                .label(&line.name)
+                .legend(move |(x, y)| {
+                    PathElement::new(vec![(x, y), (x + 20, y)], colour.stroke_width(2))
+                });
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+
+        root.present()
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use botlib::error::CommandError;
+use monitoring_core::{
+    api::models::{InsertApiToken, InsertUser, Scope},
+    client::{ClientConfig, SysInfoClient},
+};
+use serde::{Deserialize, Serialize};
+use serenity::{http::StatusCode, model::prelude::UserId};
+use tokio::{fs, io::AsyncWriteExt, sync::Mutex};
+use tracing::log::{info, warn};
+
+/// Name of the api tokens the bot creates.
+const TOKEN_NAME: &str = "monitoring-bot";
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Identity {
+    user_id: i32,
+    id_api_token: i32,
+    token: String,
+    scopes: Vec<Scope>,
+}
+
+/// The service user and api token of each Discord user, so the service records who did what.
+/// The tokens are written to a json file, which has to be protected like the token of the bot.
+#[derive(Clone)]
+pub struct ServiceIdentities {
+    path: PathBuf,
+    identities: Arc<Mutex<HashMap<u64, Identity>>>,
+}
+
+impl ServiceIdentities {
+    pub async fn load(path: &str) -> Result<Self, io::Error> {
+        let identities = match fs::read(path).await {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(why) if why.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(why) => return Err(why),
+        };
+
+        Ok(Self {
+            path: PathBuf::from(path),
+            identities: Arc::new(Mutex::new(identities)),
+        })
+    }
+
+    /// Returns a client acting as the Discord user with at least the given scopes.
+    /// The service user is created on first use and the token is replaced if it lacks a scope,
+    /// `admin` has to use the admin token of the bot. `None` if the service refused.
+    pub async fn client(
+        &self,
+        admin: &SysInfoClient,
+        server_url: &str,
+        discord_user: UserId,
+        scopes: &[Scope],
+    ) -> Result<Option<SysInfoClient>, CommandError> {
+        let mut identities = self.identities.lock().await;
+        let current = identities.get(&discord_user.0).cloned();
+
+        if let Some(identity) = &current {
+            if scopes.iter().all(|x| identity.scopes.contains(x)) {
+                return Ok(Some(SysInfoClient::new(ClientConfig::new(
+                    &identity.token,
+                    server_url,
+                ))));
+            }
+        }
+
+        let user_id = match &current {
+            Some(identity) => identity.user_id,
+            None => match find_or_create_user(admin, discord_user).await? {
+                Some(user_id) => user_id,
+                None => return Ok(None),
+            },
+        };
+
+        let mut token_scopes = current
+            .as_ref()
+            .map(|x| x.scopes.clone())
+            .unwrap_or_default();
+        for scope in scopes {
+            if !token_scopes.contains(scope) {
+                token_scopes.push(*scope);
+            }
+        }
+
+        let insert_token = InsertApiToken {
+            name: String::from(TOKEN_NAME),
+            scopes: token_scopes.clone(),
+            profile_ids: None,
+            expire_date: None,
+        };
+
+        let token = match admin.post_api_token(user_id, insert_token).await? {
+            (Some(token), _) => token,
+            (None, status) => {
+                warn!("Failed to create api token for user '{user_id}': {status}");
+
+                return Ok(None);
+            }
+        };
+
+        identities.insert(
+            discord_user.0,
+            Identity {
+                user_id,
+                id_api_token: token.id_api_token,
+                token: token.token.clone(),
+                scopes: token_scopes,
+            },
+        );
+        self.save(&identities).await?;
+
+        if let Some(identity) = current {
+            let status = admin.revoke_api_token(identity.id_api_token).await?;
+
+            if !status.is_success() && status != StatusCode::NOT_FOUND {
+                warn!(
+                    "Failed to revoke api token '{}': {status}",
+                    identity.id_api_token
+                );
+            }
+        }
+
+        Ok(Some(SysInfoClient::new(ClientConfig::new(
+            &token.token,
+            server_url,
+        ))))
+    }
+
+    /// Only the owner may read the tokens. The file is replaced at once,
+    /// so a crash while writing doesn't lose the identities.
+    async fn save(&self, identities: &HashMap<u64, Identity>) -> Result<(), io::Error> {
+        let mut temp_path = OsString::from(&self.path);
+        temp_path.push(".tmp");
+
+        // A file left over by a crash may have other permissions.
+        match fs::remove_file(&temp_path).await {
+            Err(why) if why.kind() != ErrorKind::NotFound => return Err(why),
+            _ => {}
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&temp_path)
+            .await?;
+
+        file.write_all(&serde_json::to_vec(identities)?).await?;
+        file.sync_all().await?;
+
+        fs::rename(&temp_path, &self.path).await
+    }
+}
+
+/// Returns the id of the service user of the Discord user, it is created if there is none.
+async fn find_or_create_user(
+    admin: &SysInfoClient,
+    discord_user: UserId,
+) -> Result<Option<i32>, CommandError> {
+    let insert_user = InsertUser {
+        name: format!("discord-{}", discord_user.0),
+        discord_id: Some(discord_user.0 as i64),
+    };
+
+    match admin.post_user(insert_user).await? {
+        (Some(user), _) => {
+            info!("Created service user '{}'.", user.name);
+
+            return Ok(Some(user.id_user));
+        }
+        // The user exists if the bot lost its identities or was migrated from the legacy user ids.
+        (None, StatusCode::CONFLICT) => {}
+        (None, status) => {
+            warn!("Failed to create service user of '{discord_user}': {status}");
+
+            return Ok(None);
+        }
+    }
+
+    match admin.get_users().await? {
+        (Some(users), _) => Ok(users
+            .into_iter()
+            .find(|x| x.discord_id == Some(discord_user.0 as i64))
+            .map(|x| x.id_user)),
+        (None, status) => {
+            warn!("Failed to get service users: {status}");
+
+            Ok(None)
+        }
+    }
+}
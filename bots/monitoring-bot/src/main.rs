@@ -1,13 +1,21 @@
 use std::sync::Arc;
 
 use botlib::{commands::SlashCommand, handler::BotHandler};
-use commands::profile::ProfileCommand;
+use commands::{admin::AdminCommand, alerts::AlertsCommand, errors::ErrorsCommand, graph::GraphCommand, profile::ProfileCommand, status::StatusCommand};
 use config::{AppConfigurations, BotConfig};
+use identities::ServiceIdentities;
+use permissions::CommandPermissions;
 use serenity::{prelude::GatewayIntents, Client};
+use subscriptions::AlertSubscriptions;
 use tracing::{instrument, log::error};
 
 mod config;
 mod commands;
+mod graph;
+mod identities;
+mod notifier;
+mod permissions;
+mod subscriptions;
 
 #[tokio::main]
 #[instrument]
@@ -15,17 +23,33 @@ async fn main() {
     tracing_subscriber::fmt::init();
 
     let app_config = AppConfigurations::from_env();
-    let bot_config = BotConfig::new(&app_config.api_token, &app_config.read_token, &app_config.server_url, &app_config.allowed_users);
 
-    let mut commands: Vec<Arc<dyn SlashCommand<Config = BotConfig>>> = Vec::new();
-    commands.push(Arc::new(ProfileCommand));
+    if let Err(why) = graph::load_font(&app_config.graph_font) {
+        error!("{why} Charts can't be drawn.");
+    }
+    let subscriptions = AlertSubscriptions::load(&app_config.subscriptions_file)
+        .await
+        .expect("Failed to load alert subscriptions!");
+    let permissions = CommandPermissions::load(&app_config.permissions_file)
+        .await
+        .expect("Failed to load command permissions!");
+    let identities = ServiceIdentities::load(&app_config.identities_file)
+        .await
+        .expect("Failed to load service identities!");
+    let bot_config = BotConfig::new(&app_config.api_token, &app_config.server_url, subscriptions, permissions, identities);
+
+    let mut commands: Vec<Arc<dyn SlashCommand<Config = BotConfig>>> = vec![Arc::new(ProfileCommand), Arc::new(AlertsCommand), Arc::new(StatusCommand), Arc::new(GraphCommand), Arc::new(ErrorsCommand)];
+    let command_names = commands.iter().map(|x| x.name()).collect();
+    commands.push(Arc::new(AdminCommand::new(command_names)));
 
     let intents = GatewayIntents::default() | GatewayIntents::MESSAGE_CONTENT | GatewayIntents::GUILD_MESSAGES;
     let mut client = Client::builder(app_config.bot_token, intents)
-        .event_handler(BotHandler::new(&commands, bot_config))
+        .event_handler(BotHandler::new(&commands, bot_config.clone()))
         .await
         .expect("Err creating client");
 
+    tokio::spawn(notifier::run(client.cache_and_http.http.clone(), bot_config, app_config.alert_interval));
+
     if let Err(why) = client.start().await {
         error!("Client error: {:?}", why);
     }
@@ -0,0 +1,140 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use monitoring_core::api::models::Alert;
+use serenity::{http::Http, model::prelude::ChannelId, utils::Colour};
+use tracing::log::{error, info, warn};
+
+use crate::config::BotConfig;
+
+/// Seconds each poll reaches back before the latest change seen, alerts are stored with the time
+/// their evaluation started and may show up late.
+const POLL_OVERLAP: i64 = 300;
+
+/// Polls the alerts which changed since the last poll and posts an embed into every subscribed
+/// channel for each alert which fired or resolved, also if both happened between two polls.
+/// Alerts which changed while the bot was offline are not posted.
+pub async fn run(http: Arc<Http>, config: BotConfig, interval: u64) {
+    let client = config.client();
+    let mut timer = tokio::time::interval(Duration::from_secs(interval));
+    let started = Utc::now().naive_utc();
+    let mut latest = started;
+    // The posted changes as (alert id, resolved), with the date of the change.
+    let mut posted: HashMap<(i32, bool), NaiveDateTime> = HashMap::new();
+
+    loop {
+        timer.tick().await;
+
+        let since = latest - chrono::Duration::seconds(POLL_OVERLAP);
+
+        let alerts = match client
+            .get_alerts(None, false, Some(since.timestamp()))
+            .await
+        {
+            Ok((Some(alerts), _)) => alerts,
+            Ok((None, status)) => {
+                warn!("Failed to get changed alerts: {status}");
+                continue;
+            }
+            Err(why) => {
+                error!("Failed to get changed alerts: {:?}", why);
+                continue;
+            }
+        };
+
+        posted.retain(|_, date| *date >= since);
+
+        let mut changes: Vec<(&Alert, bool, NaiveDateTime)> = Vec::new();
+
+        for alert in &alerts {
+            changes.push((alert, false, alert.fired_date));
+
+            if let Some(resolved_date) = alert.resolved_date {
+                changes.push((alert, true, resolved_date));
+            }
+        }
+
+        changes.retain(|(alert, resolved, date)| {
+            *date >= started && *date >= since && !posted.contains_key(&(alert.id_alert, *resolved))
+        });
+        changes.sort_by_key(|(_, _, date)| *date);
+
+        if changes.is_empty() {
+            continue;
+        }
+
+        let channels = config.subscriptions().channels().await;
+
+        for (alert, resolved, date) in changes {
+            if resolved {
+                info!(
+                    "Alert '{}' resolved for '{}'.",
+                    alert.id_alert, alert.device_name
+                );
+            } else {
+                info!(
+                    "Alert '{}' fired for '{}'.",
+                    alert.id_alert, alert.device_name
+                );
+            }
+
+            post_alert(&http, &channels, alert, resolved).await;
+            posted.insert((alert.id_alert, resolved), date);
+            latest = latest.max(date);
+        }
+    }
+}
+
+async fn post_alert(http: &Http, channels: &[ChannelId], alert: &Alert, resolved: bool) {
+    let rule = &alert.rule;
+    let metric = match rule.target.as_ref() {
+        Some(target) => format!("{} ({target})", rule.metric),
+        None => rule.metric.to_string(),
+    };
+
+    let (title, colour, value, date) = match (resolved, alert.resolved_date) {
+        (true, Some(resolved_date)) => (
+            format!("{} stopped alerting", alert.device_name),
+            Colour::DARK_GREEN,
+            alert.resolved_value,
+            resolved_date,
+        ),
+        _ => (
+            format!("{} started alerting", alert.device_name),
+            Colour::RED,
+            Some(alert.value),
+            alert.fired_date,
+        ),
+    };
+    let value = match value {
+        Some(value) => format!("{value:.2}"),
+        None => String::from("-"),
+    };
+
+    for channel in channels {
+        let result = channel
+            .send_message(http, |m| {
+                m.embed(|e| {
+                    e.title(&title)
+                        .colour(colour)
+                        .field("Metric", &metric, true)
+                        .field("Value", &value, true)
+                        .field(
+                            "Threshold",
+                            format!("{} {}", rule.comparison, rule.threshold),
+                            true,
+                        )
+                        .timestamp(to_utc(date))
+                })
+            })
+            .await;
+
+        if let Err(why) = result {
+            error!("Failed to post alert into channel {}: {}", channel, why);
+        }
+    }
+}
+
+fn to_utc(date: NaiveDateTime) -> DateTime<Utc> {
+    DateTime::from_utc(date, Utc)
+}
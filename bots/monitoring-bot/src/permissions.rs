@@ -0,0 +1,139 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, ErrorKind},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use serenity::model::prelude::{GuildId, Member, RoleId};
+use tokio::{fs, sync::RwLock};
+
+/// The ids of the roles allowed to use each command of a guild.
+type GuildRoles = BTreeMap<String, Vec<u64>>;
+
+/// The roles of each guild which may use a command, members with the administrator
+/// permission may use every command.
+/// Every change is written to a json file, so permissions survive a restart.
+#[derive(Clone)]
+pub struct CommandPermissions {
+    path: PathBuf,
+    roles: Arc<RwLock<HashMap<u64, GuildRoles>>>,
+}
+
+impl CommandPermissions {
+    pub async fn load(path: &str) -> Result<Self, io::Error> {
+        let roles = match fs::read(path).await {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(why) if why.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(why) => return Err(why),
+        };
+
+        Ok(Self {
+            path: PathBuf::from(path),
+            roles: Arc::new(RwLock::new(roles)),
+        })
+    }
+
+    /// Returns false if the role was already allowed to use the command.
+    pub async fn allow(
+        &self,
+        guild_id: GuildId,
+        command: &str,
+        role_id: RoleId,
+    ) -> Result<bool, io::Error> {
+        let mut roles = self.roles.write().await;
+        let command_roles = roles
+            .entry(guild_id.0)
+            .or_default()
+            .entry(String::from(command))
+            .or_default();
+
+        if command_roles.contains(&role_id.0) {
+            return Ok(false);
+        }
+
+        command_roles.push(role_id.0);
+
+        fs::write(&self.path, serde_json::to_vec(&*roles)?).await?;
+
+        Ok(true)
+    }
+
+    /// Returns false if the role wasn't allowed to use the command.
+    pub async fn deny(
+        &self,
+        guild_id: GuildId,
+        command: &str,
+        role_id: RoleId,
+    ) -> Result<bool, io::Error> {
+        let mut roles = self.roles.write().await;
+
+        let guild_roles = match roles.get_mut(&guild_id.0) {
+            Some(guild_roles) => guild_roles,
+            None => return Ok(false),
+        };
+        let command_roles = match guild_roles.get_mut(command) {
+            Some(command_roles) => command_roles,
+            None => return Ok(false),
+        };
+
+        let count = command_roles.len();
+        command_roles.retain(|x| *x != role_id.0);
+
+        if command_roles.len() == count {
+            return Ok(false);
+        }
+
+        if command_roles.is_empty() {
+            guild_roles.remove(command);
+        }
+
+        fs::write(&self.path, serde_json::to_vec(&*roles)?).await?;
+
+        Ok(true)
+    }
+
+    pub async fn roles(&self, guild_id: GuildId, command: &str) -> Vec<RoleId> {
+        self.roles
+            .read()
+            .await
+            .get(&guild_id.0)
+            .and_then(|x| x.get(command))
+            .map(|x| x.iter().map(|id| RoleId(*id)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the allowed roles of every command of the guild, ordered by command.
+    pub async fn guild_roles(&self, guild_id: GuildId) -> Vec<(String, Vec<RoleId>)> {
+        self.roles
+            .read()
+            .await
+            .get(&guild_id.0)
+            .map(|x| {
+                x.iter()
+                    .map(|(command, roles)| {
+                        (
+                            command.clone(),
+                            roles.iter().map(|id| RoleId(*id)).collect(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub async fn is_allowed(&self, guild_id: GuildId, command: &str, member: &Member) -> bool {
+        has_permission(member, &self.roles(guild_id, command).await)
+    }
+}
+
+/// Returns true if the member is an administrator or has one of the roles.
+pub fn has_permission(member: &Member, roles: &[RoleId]) -> bool {
+    is_administrator(member) || member.roles.iter().any(|x| roles.contains(x))
+}
+
+pub fn is_administrator(member: &Member) -> bool {
+    member
+        .permissions
+        .is_some_and(|permissions| permissions.administrator())
+}
@@ -0,0 +1,65 @@
+use std::{
+    collections::HashMap,
+    io::{self, ErrorKind},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use serenity::model::prelude::{ChannelId, GuildId};
+use tokio::{fs, sync::RwLock};
+
+/// The channel of each guild alerts are posted to.
+/// Every change is written to a json file, so subscriptions survive a restart.
+#[derive(Clone)]
+pub struct AlertSubscriptions {
+    path: PathBuf,
+    channels: Arc<RwLock<HashMap<u64, u64>>>,
+}
+
+impl AlertSubscriptions {
+    pub async fn load(path: &str) -> Result<Self, io::Error> {
+        let channels = match fs::read(path).await {
+            Ok(data) => serde_json::from_slice(&data)?,
+            Err(why) if why.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(why) => return Err(why),
+        };
+
+        Ok(Self {
+            path: PathBuf::from(path),
+            channels: Arc::new(RwLock::new(channels)),
+        })
+    }
+
+    pub async fn subscribe(
+        &self,
+        guild_id: GuildId,
+        channel_id: ChannelId,
+    ) -> Result<(), io::Error> {
+        let mut channels = self.channels.write().await;
+        channels.insert(guild_id.0, channel_id.0);
+
+        fs::write(&self.path, serde_json::to_vec(&*channels)?).await
+    }
+
+    /// Returns false if the guild wasn't subscribed.
+    pub async fn unsubscribe(&self, guild_id: GuildId) -> Result<bool, io::Error> {
+        let mut channels = self.channels.write().await;
+
+        if channels.remove(&guild_id.0).is_none() {
+            return Ok(false);
+        }
+
+        fs::write(&self.path, serde_json::to_vec(&*channels)?).await?;
+
+        Ok(true)
+    }
+
+    pub async fn channels(&self) -> Vec<ChannelId> {
+        self.channels
+            .read()
+            .await
+            .values()
+            .map(|id| ChannelId(*id))
+            .collect()
+    }
+}
@@ -0,0 +1,2 @@
+ALTER TABLE filesystem_infos
+    ADD COLUMN files_avail INT NOT NULL DEFAULT 0;
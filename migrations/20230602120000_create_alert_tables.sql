@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS alert_rules (
+    id_alert_rule SERIAL PRIMARY KEY,
+    device_profile_id INT NOT NULL,
+    metric VARCHAR(50) NOT NULL,
+    target VARCHAR(255),
+    comparison VARCHAR(10) NOT NULL,
+    threshold FLOAT NOT NULL,
+    duration BIGINT NOT NULL,
+    create_user BIGINT NOT NULL,
+    create_date TIMESTAMP NOT NULL,
+    FOREIGN KEY (device_profile_id) REFERENCES device_profiles(id_device_profile)
+);
+
+CREATE TABLE IF NOT EXISTS alerts (
+    id_alert SERIAL PRIMARY KEY,
+    alert_rule_id INT NOT NULL,
+    value FLOAT NOT NULL,
+    fired_date TIMESTAMP NOT NULL,
+    resolved_date TIMESTAMP,
+    FOREIGN KEY (alert_rule_id) REFERENCES alert_rules(id_alert_rule) ON DELETE CASCADE
+);
@@ -0,0 +1,11 @@
+ALTER TABLE system_informations
+    ADD COLUMN sequence BIGINT,
+    ADD COLUMN receive_date TIMESTAMP;
+
+UPDATE system_informations SET receive_date = create_date;
+
+ALTER TABLE system_informations
+    ALTER COLUMN receive_date SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS system_informations_profile_sequence_idx
+    ON system_informations (device_profile_id, sequence);
@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS process_infos (
+    id_process_info SERIAL PRIMARY KEY,
+    system_information_id INT NOT NULL,
+    ranking VARCHAR(10) NOT NULL,
+    pid INT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    cmdline TEXT NOT NULL,
+    username VARCHAR(255) NOT NULL,
+    state VARCHAR(10) NOT NULL,
+    threads BIGINT NOT NULL,
+    open_fds BIGINT,
+    cpu_usage REAL NOT NULL,
+    rss BIGINT NOT NULL,
+    FOREIGN KEY (system_information_id) REFERENCES system_informations(id_system_information)
+);
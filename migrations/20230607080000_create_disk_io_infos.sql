@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS disk_io_infos (
+    id_disk_io_info SERIAL PRIMARY KEY,
+    system_information_id INT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    read_bytes DOUBLE PRECISION NOT NULL,
+    write_bytes DOUBLE PRECISION NOT NULL,
+    read_ops DOUBLE PRECISION NOT NULL,
+    write_ops DOUBLE PRECISION NOT NULL,
+    in_flight BIGINT NOT NULL,
+    io_time DOUBLE PRECISION NOT NULL,
+    FOREIGN KEY (system_information_id) REFERENCES system_informations(id_system_information)
+);
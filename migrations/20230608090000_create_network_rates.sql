@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS network_rates (
+    id_network_rate SERIAL PRIMARY KEY,
+    system_information_id INT NOT NULL,
+    network_id INT NOT NULL,
+    rx_bytes DOUBLE PRECISION NOT NULL,
+    tx_bytes DOUBLE PRECISION NOT NULL,
+    rx_packets DOUBLE PRECISION NOT NULL,
+    tx_packets DOUBLE PRECISION NOT NULL,
+    rx_errors DOUBLE PRECISION NOT NULL,
+    tx_errors DOUBLE PRECISION NOT NULL,
+    FOREIGN KEY (system_information_id) REFERENCES system_informations(id_system_information),
+    FOREIGN KEY (network_id) REFERENCES networks(id_network)
+);
@@ -0,0 +1,22 @@
+ALTER TABLE cpu_informations
+    ALTER COLUMN temperature DROP NOT NULL;
+
+CREATE TABLE IF NOT EXISTS temperature_infos (
+    id_temperature_info SERIAL PRIMARY KEY,
+    system_information_id INT NOT NULL,
+    chip VARCHAR(255) NOT NULL,
+    label VARCHAR(255) NOT NULL,
+    current FLOAT,
+    high FLOAT,
+    critical FLOAT,
+    FOREIGN KEY (system_information_id) REFERENCES system_informations(id_system_information)
+);
+
+CREATE TABLE IF NOT EXISTS fan_infos (
+    id_fan_info SERIAL PRIMARY KEY,
+    system_information_id INT NOT NULL,
+    chip VARCHAR(255) NOT NULL,
+    label VARCHAR(255) NOT NULL,
+    rpm BIGINT,
+    FOREIGN KEY (system_information_id) REFERENCES system_informations(id_system_information)
+);
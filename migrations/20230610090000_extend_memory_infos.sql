@@ -0,0 +1,15 @@
+ALTER TABLE memory_infos
+    ADD COLUMN available BIGINT,
+    ADD COLUMN buffers BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN cached BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN shared BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN slab BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN dirty BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN writeback BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN hugepages_total BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN hugepages_free BIGINT NOT NULL DEFAULT 0;
+
+UPDATE memory_infos SET available = free;
+
+ALTER TABLE memory_infos
+    ALTER COLUMN available SET NOT NULL;
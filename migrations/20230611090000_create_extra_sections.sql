@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS extra_sections (
+    id_extra_section SERIAL PRIMARY KEY,
+    system_information_id INT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    value JSONB NOT NULL,
+    FOREIGN KEY (system_information_id) REFERENCES system_informations(id_system_information)
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS check_results (
+    id_check_result SERIAL PRIMARY KEY,
+    system_information_id INT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    output TEXT NOT NULL,
+    perfdata JSONB NOT NULL,
+    executed_at TIMESTAMP NOT NULL,
+    duration DOUBLE PRECISION NOT NULL,
+    FOREIGN KEY (system_information_id) REFERENCES system_informations(id_system_information)
+);
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS probe_results (
+    id_probe_result SERIAL PRIMARY KEY,
+    device_profile_id INT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    kind VARCHAR(16) NOT NULL,
+    target TEXT NOT NULL,
+    success BOOLEAN NOT NULL,
+    latency DOUBLE PRECISION,
+    status_code INT,
+    body_match BOOLEAN,
+    cert_expiry_days BIGINT,
+    error TEXT,
+    executed_at TIMESTAMP NOT NULL,
+    receive_date TIMESTAMP NOT NULL,
+    FOREIGN KEY (device_profile_id) REFERENCES device_profiles(id_device_profile)
+);
+
+CREATE INDEX IF NOT EXISTS probe_results_profile_executed_idx
+    ON probe_results (device_profile_id, executed_at);
@@ -0,0 +1,13 @@
+ALTER TABLE error_logs
+    ADD COLUMN severity VARCHAR(20) NOT NULL DEFAULT 'error',
+    ADD COLUMN source VARCHAR(255) NOT NULL DEFAULT '',
+    ADD COLUMN occurred_at TIMESTAMP,
+    ADD COLUMN context JSONB NOT NULL DEFAULT '{}';
+
+UPDATE error_logs SET occurred_at = create_date;
+
+ALTER TABLE error_logs
+    ALTER COLUMN occurred_at SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS error_logs_profile_occurred_idx
+    ON error_logs (device_profile_id, occurred_at);
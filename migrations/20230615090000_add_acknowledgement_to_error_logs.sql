@@ -0,0 +1,3 @@
+ALTER TABLE error_logs
+    ADD COLUMN acknowledged_user BIGINT,
+    ADD COLUMN acknowledged_date TIMESTAMP;
@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS profile_keys (
+    id_profile_key SERIAL PRIMARY KEY,
+    device_profile_id INT NOT NULL,
+    key_hash VARCHAR(255) NOT NULL,
+    create_user BIGINT NOT NULL,
+    create_date TIMESTAMP NOT NULL,
+    expire_date TIMESTAMP,
+    revoke_date TIMESTAMP,
+    FOREIGN KEY (device_profile_id) REFERENCES device_profiles(id_device_profile)
+);
+
+CREATE INDEX IF NOT EXISTS profile_keys_profile_idx
+    ON profile_keys (device_profile_id);
+
+-- The plaintext keys are hashed into profile_keys by the service on startup.
+ALTER TABLE device_profiles
+    ALTER COLUMN profile_key DROP NOT NULL;
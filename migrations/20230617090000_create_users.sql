@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS users (
+    id_user SERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL UNIQUE,
+    create_date TIMESTAMP NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id_api_token SERIAL PRIMARY KEY,
+    user_id INT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL,
+    -- NULL allows every profile.
+    profile_ids INT[],
+    create_date TIMESTAMP NOT NULL,
+    expire_date TIMESTAMP,
+    revoke_date TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id_user) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS api_tokens_user_idx
+    ON api_tokens (user_id);
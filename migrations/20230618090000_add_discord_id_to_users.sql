@@ -0,0 +1,26 @@
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS discord_id BIGINT UNIQUE;
+
+-- The user columns held the Discord ids sent by the bot, they now reference users.
+INSERT INTO users (name, discord_id, create_date)
+SELECT 'discord-' || ids.discord_id, ids.discord_id, NOW()
+FROM (
+    SELECT create_user AS discord_id FROM device_profiles
+    UNION SELECT modify_user FROM device_profiles
+    UNION SELECT create_user FROM profile_keys
+    UNION SELECT create_user FROM alert_rules
+    UNION SELECT acknowledged_user FROM error_logs
+) ids
+WHERE ids.discord_id IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+UPDATE device_profiles p SET create_user = u.id_user
+    FROM users u WHERE u.discord_id = p.create_user;
+UPDATE device_profiles p SET modify_user = u.id_user
+    FROM users u WHERE u.discord_id = p.modify_user;
+UPDATE profile_keys k SET create_user = u.id_user
+    FROM users u WHERE u.discord_id = k.create_user;
+UPDATE alert_rules r SET create_user = u.id_user
+    FROM users u WHERE u.discord_id = r.create_user;
+UPDATE error_logs e SET acknowledged_user = u.id_user
+    FROM users u WHERE u.discord_id = e.acknowledged_user;
@@ -0,0 +1,3 @@
+-- The os info as sent by the cli, the other columns only hold its display strings.
+ALTER TABLE os_infos
+    ADD COLUMN IF NOT EXISTS info JSONB;
@@ -0,0 +1,12 @@
+-- A rule may only have one firing alert, ingest and the timer can evaluate it at the same time.
+UPDATE alerts a
+    SET resolved_date = a.fired_date
+    WHERE a.resolved_date IS NULL
+    AND EXISTS (
+        SELECT 1 FROM alerts o
+        WHERE o.alert_rule_id = a.alert_rule_id AND o.resolved_date IS NULL AND o.id_alert < a.id_alert
+    );
+
+CREATE UNIQUE INDEX IF NOT EXISTS alerts_active_rule_idx
+    ON alerts (alert_rule_id)
+    WHERE resolved_date IS NULL;
@@ -0,0 +1,6 @@
+-- Alert windows follow the receive time, the clock of the device may be off.
+CREATE INDEX IF NOT EXISTS system_informations_profile_received_idx
+    ON system_informations (device_profile_id, receive_date);
+
+CREATE INDEX IF NOT EXISTS probe_results_profile_received_idx
+    ON probe_results (device_profile_id, name, receive_date);
@@ -0,0 +1,6 @@
+ALTER TABLE alerts
+    ADD COLUMN IF NOT EXISTS resolved_value FLOAT;
+
+-- Lists the alerts which changed since the last poll of the bot.
+CREATE INDEX IF NOT EXISTS alerts_resolved_idx
+    ON alerts (resolved_date);
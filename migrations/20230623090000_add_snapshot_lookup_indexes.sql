@@ -0,0 +1,42 @@
+-- The metrics export loads the latest snapshot of every profile and its rows of each table.
+CREATE INDEX IF NOT EXISTS system_informations_profile_created_idx
+    ON system_informations (device_profile_id, create_date);
+
+CREATE INDEX IF NOT EXISTS cpu_informations_system_information_idx
+    ON cpu_informations (system_information_id);
+
+CREATE INDEX IF NOT EXISTS cpu_core_loads_cpu_information_idx
+    ON cpu_core_loads (cpu_information_id);
+
+CREATE INDEX IF NOT EXISTS load_averages_system_information_idx
+    ON load_averages (system_information_id);
+
+CREATE INDEX IF NOT EXISTS memory_infos_system_information_idx
+    ON memory_infos (system_information_id);
+
+CREATE INDEX IF NOT EXISTS swap_infos_system_information_idx
+    ON swap_infos (system_information_id);
+
+CREATE INDEX IF NOT EXISTS battery_lifes_system_information_idx
+    ON battery_lifes (system_information_id);
+
+CREATE INDEX IF NOT EXISTS filesystem_infos_system_information_idx
+    ON filesystem_infos (system_information_id);
+
+CREATE INDEX IF NOT EXISTS networks_statistics_system_information_idx
+    ON networks_statistics (system_information_id);
+
+CREATE INDEX IF NOT EXISTS socket_statistics_system_information_idx
+    ON socket_statistics (system_information_id);
+
+CREATE INDEX IF NOT EXISTS disk_io_infos_system_information_idx
+    ON disk_io_infos (system_information_id);
+
+CREATE INDEX IF NOT EXISTS temperature_infos_system_information_idx
+    ON temperature_infos (system_information_id);
+
+CREATE INDEX IF NOT EXISTS fan_infos_system_information_idx
+    ON fan_infos (system_information_id);
+
+CREATE INDEX IF NOT EXISTS check_results_system_information_idx
+    ON check_results (system_information_id);
@@ -0,0 +1,4 @@
+-- Acknowledging error logs moved from alerts:manage to its own scope, existing tokens keep it.
+UPDATE api_tokens
+    SET scopes = array_append(scopes, 'errors:manage')
+    WHERE 'alerts:manage' = ANY(scopes) AND NOT 'errors:manage' = ANY(scopes);
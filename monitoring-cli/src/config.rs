@@ -1,12 +1,14 @@
+use std::{net::SocketAddr, path::PathBuf};
+
 use clap::{Parser, Subcommand};
-use monitoring_core::client::ClientConfig;
+use monitoring_core::{checks::CheckConfig, client::ClientConfig};
 use serde::{Deserialize, Serialize};
 use tokio::{
     fs::{self, File},
     io::AsyncWriteExt,
 };
 
-use crate::error::CliError;
+use crate::{error::CliError, probes::ProbeConfig};
 
 #[derive(Parser)]
 #[command(name = "monitoring-cli")]
@@ -20,12 +22,12 @@ pub enum Cli {
     Run(RunOpt),
     #[command(about = "Configures the cli.")]
     Configure {
-        #[arg(short, long, help = "Sets the api key in the configuration file.")]
+        #[arg(short, long, help = "Sets the api token in the configuration file.")]
         api_key: Option<String>,
         #[arg(
             short,
             long,
-            help = "Sets the device profile key in the configuration file."
+            help = "Sets the device profile key, `<key id>.<secret>`, in the configuration file."
         )]
         profile_key: Option<String>,
         #[arg(
@@ -54,6 +56,19 @@ pub enum RunOpt {
         filesystem: bool,
         #[arg(short, long, help = "Includes/Excludes swap infos.")]
         swap: bool,
+        #[arg(short, long, help = "Includes/Excludes the top processes.")]
+        processes: bool,
+        #[arg(short = 'b', long, help = "Includes/Excludes disk io infos.")]
+        disk_io: bool,
+        #[arg(short, long, help = "Includes/Excludes temperature sensors and fans.")]
+        thermals: bool,
+        #[arg(
+            short,
+            long,
+            default_value = "./data",
+            help = "Directory of the sequence number of the system infos."
+        )]
+        data_dir: PathBuf,
     },
     Service {
         #[arg(short, long, help = "Includes/Excludes cpu infos.")]
@@ -68,8 +83,55 @@ pub enum RunOpt {
         filesystem: bool,
         #[arg(short, long, help = "Includes/Excludes swap infos.")]
         swap: bool,
+        #[arg(short, long, help = "Includes/Excludes the top processes.")]
+        processes: bool,
+        #[arg(short = 'b', long, help = "Includes/Excludes disk io infos.")]
+        disk_io: bool,
+        #[arg(short, long, help = "Includes/Excludes temperature sensors and fans.")]
+        thermals: bool,
         #[arg(short = 'i', long = "interval", help = "Sleep interval in seconds.")]
         sleep_interval: u64,
+        #[arg(
+            short,
+            long,
+            default_value = "./data",
+            help = "Directory of the sequence number and the spool for system infos that couldn't be sent."
+        )]
+        data_dir: PathBuf,
+        #[arg(
+            long,
+            default_value_t = 64 * 1024 * 1024,
+            help = "Maximum size of the spool in bytes, the oldest system infos are dropped first."
+        )]
+        spool_size: u64,
+    },
+    #[command(about = "Serves the system infos as Prometheus metrics.")]
+    Exporter {
+        #[arg(short, long, help = "Includes/Excludes cpu infos.")]
+        cpu: bool,
+        #[arg(short, long, help = "Includes/Excludes memory infos.")]
+        memory: bool,
+        #[arg(short, long, help = "Includes/Excludes os infos.")]
+        os: bool,
+        #[arg(short, long, help = "Includes/Excludes network infos.")]
+        network: bool,
+        #[arg(short, long, help = "Includes/Excludes filesystem infos.")]
+        filesystem: bool,
+        #[arg(short, long, help = "Includes/Excludes swap infos.")]
+        swap: bool,
+        #[arg(short, long, help = "Includes/Excludes the top processes.")]
+        processes: bool,
+        #[arg(short = 'b', long, help = "Includes/Excludes disk io infos.")]
+        disk_io: bool,
+        #[arg(short, long, help = "Includes/Excludes temperature sensors and fans.")]
+        thermals: bool,
+        #[arg(
+            short,
+            long,
+            default_value = "0.0.0.0:9101",
+            help = "Address the metrics are served on."
+        )]
+        listen: SocketAddr,
     },
 }
 
@@ -78,26 +140,32 @@ pub struct CliConfig {
     pub client: ClientConfig,
     pub profile_id: i32,
     pub profile_key: String,
+    /// Nagios plugin style commands run with every collection.
+    #[serde(default)]
+    pub checks: Vec<CheckConfig>,
+    /// Http and tcp probes, run at their own interval and sent apart from the system information.
+    #[serde(default)]
+    pub probes: Vec<ProbeConfig>,
 }
 
 impl CliConfig {
     pub async fn load(file_path: &str) -> Result<Self, CliError> {
         let contents = fs::read_to_string(file_path)
             .await
-            .map_err(|x| CliError::Io(x))?;
+            .map_err(CliError::Io)?;
         let config =
-            serde_json::from_str::<CliConfig>(&contents).map_err(|x| CliError::Serde(x))?;
+            serde_json::from_str::<CliConfig>(&contents).map_err(CliError::Serde)?;
 
         Ok(config)
     }
 
     pub async fn save(&self, file_path: &str) -> Result<(), CliError> {
-        let contents = serde_json::to_string(self).map_err(|x| CliError::Serde(x))?;
+        let contents = serde_json::to_string(self).map_err(CliError::Serde)?;
 
-        let mut file = File::create(file_path).await.map_err(|x| CliError::Io(x))?;
+        let mut file = File::create(file_path).await.map_err(CliError::Io)?;
         file.write_all(contents.as_bytes())
             .await
-            .map_err(|x| CliError::Io(x))?;
+            .map_err(CliError::Io)?;
 
         Ok(())
     }
@@ -1,5 +1,7 @@
+#[allow(dead_code)]
 #[derive(Debug)]
 pub enum CliError {
     Serde(serde_json::Error),
     Io(std::io::Error),
+    Hyper(hyper::Error),
 }
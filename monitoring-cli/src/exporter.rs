@@ -0,0 +1,103 @@
+use std::{convert::Infallible, net::SocketAddr, path::Path, sync::Arc};
+
+use chrono::Utc;
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use log::{error, info};
+use monitoring_core::{
+    models::SystemInformation,
+    options::CollectorOptions,
+    prometheus::{write_devices, Device, Exposition},
+};
+use systemstat::{Platform, System};
+
+use crate::{config::CliConfig, error::CliError, CONFIG_FILE_PATH};
+
+static METRICS_PATH: &str = "/metrics";
+static CONTENT_TYPE_TEXT: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Serves the system information as Prometheus metrics, collected on every scrape.
+/// The checks of the configuration file are run as well if there is one.
+pub async fn run_exporter(
+    mut options: CollectorOptions,
+    listen: SocketAddr,
+) -> Result<(), CliError> {
+    if Path::new(CONFIG_FILE_PATH).exists() {
+        options.set_checks(CliConfig::load(CONFIG_FILE_PATH).await?.checks);
+    }
+
+    let options = Arc::new(options);
+
+    let make_service = make_service_fn(move |_| {
+        let options = options.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_request(request, options.clone())
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&listen)
+        .map_err(CliError::Hyper)?
+        .serve(make_service);
+
+    info!("Serving metrics on http://{listen}{METRICS_PATH}");
+
+    server.await.map_err(CliError::Hyper)
+}
+
+async fn handle_request(
+    request: Request<Body>,
+    options: Arc<CollectorOptions>,
+) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::GET || request.uri().path() != METRICS_PATH {
+        return Ok(status_response(StatusCode::NOT_FOUND));
+    }
+
+    // Collecting the cpu load blocks for a second.
+    let metrics = match tokio::task::spawn_blocking(move || collect_metrics(&options)).await {
+        Ok(Ok(metrics)) => metrics,
+        Ok(Err(why)) => {
+            error!("Failed to collect system information: {why}");
+            return Ok(status_response(StatusCode::INTERNAL_SERVER_ERROR));
+        }
+        Err(why) => {
+            error!("Failed to collect system information: {why}");
+            return Ok(status_response(StatusCode::INTERNAL_SERVER_ERROR));
+        }
+    };
+
+    let response = Response::builder()
+        .header(CONTENT_TYPE, CONTENT_TYPE_TEXT)
+        .body(Body::from(metrics))
+        .unwrap_or_else(|_| status_response(StatusCode::INTERNAL_SERVER_ERROR));
+
+    Ok(response)
+}
+
+fn collect_metrics(options: &CollectorOptions) -> anyhow::Result<String> {
+    let system = System::new();
+    let hostname = hostname::get()?;
+
+    let device = Device {
+        labels: vec![("hostname", hostname.to_string_lossy().into_owned())],
+        timestamp: Utc::now().naive_utc(),
+        info: SystemInformation::collect(options, system, hostname)?,
+    };
+
+    let mut exposition = Exposition::default();
+    write_devices(&mut exposition, &[device]);
+
+    Ok(exposition.into_string())
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = status;
+
+    response
+}
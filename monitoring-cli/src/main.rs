@@ -1,15 +1,24 @@
 mod config;
 mod error;
+mod exporter;
+mod probes;
+mod sequence;
+mod spool;
 
-use std::{env, path::Path};
+use std::{collections::BTreeMap, env, path::Path};
 
-use chrono::Local;
+use chrono::{Local, Utc};
 use clap::Parser;
 use config::{Cli, CliConfig, RunOpt};
 use env_logger::Builder;
 use error::CliError;
+use exporter::run_exporter;
+use probes::{run_probes, send_probes, spawn_probes};
+use reqwest::StatusCode;
+use sequence::Sequence;
+use spool::Spool;
 use log::{error, info, warn, LevelFilter};
-use monitoring_core::{models::SystemInformation, options::CollectorOptions, client::{ClientConfig, SysInfoClient}};
+use monitoring_core::{models::SystemInformation, options::CollectorOptions, client::{ClientConfig, SysInfoClient}, collector::CollectorFailed, ErrorLog, Severity};
 use std::io::Write;
 use systemstat::{Duration, Platform, System};
 use tokio::time::sleep;
@@ -30,7 +39,7 @@ async fn main() -> Result<(), CliError> {
                 record.args()
             )
         })
-        .parse_env(&env::var("MONITORING_CLI_LOG").unwrap_or_default())
+        .parse_env(env::var("MONITORING_CLI_LOG").unwrap_or_default())
         .filter(None, LevelFilter::Info)
         .init();
 
@@ -54,10 +63,17 @@ async fn run(run_opt: RunOpt) -> Result<(), CliError> {
             network,
             filesystem,
             swap,
+            processes,
+            disk_io,
+            thermals,
+            data_dir,
         } => {
-            run_normal(CollectorOptions::new(
-                cpu, memory, os, network, filesystem, swap,
-            ))
+            run_normal(
+                CollectorOptions::new(
+                    cpu, memory, os, network, filesystem, swap, processes, disk_io, thermals,
+                ),
+                Sequence::open(&data_dir).await?,
+            )
             .await
         }
         RunOpt::Service {
@@ -67,50 +83,178 @@ async fn run(run_opt: RunOpt) -> Result<(), CliError> {
             network,
             filesystem,
             swap,
+            processes,
+            disk_io,
+            thermals,
             sleep_interval,
+            data_dir,
+            spool_size,
         } => {
             run_service(
-                CollectorOptions::new(cpu, memory, os, network, filesystem, swap),
+                CollectorOptions::new(
+                    cpu, memory, os, network, filesystem, swap, processes, disk_io, thermals,
+                ),
                 sleep_interval,
+                Sequence::open(&data_dir).await?,
+                Spool::open(&data_dir, spool_size).await?,
+            )
+            .await
+        }
+        RunOpt::Exporter {
+            cpu,
+            memory,
+            os,
+            network,
+            filesystem,
+            swap,
+            processes,
+            disk_io,
+            thermals,
+            listen,
+        } => {
+            run_exporter(
+                CollectorOptions::new(
+                    cpu, memory, os, network, filesystem, swap, processes, disk_io, thermals,
+                ),
+                listen,
             )
             .await
         }
     }
 }
 
-async fn run_normal(options: CollectorOptions) -> Result<(), CliError> {
+async fn run_normal(mut options: CollectorOptions, sequence: Sequence) -> Result<(), CliError> {
     let cli_config = CliConfig::load(CONFIG_FILE_PATH).await?;
+    options.set_checks(cli_config.checks.clone());
 
-    post_system_info(&options, &cli_config.client, cli_config.profile_id, &cli_config.profile_key).await
+    post_system_info(&options, &sequence, &cli_config.client, cli_config.profile_id, &cli_config.profile_key).await?;
+
+    if !cli_config.probes.is_empty() {
+        let results = run_probes(&cli_config.probes).await;
+        let client = SysInfoClient::new(cli_config.client.clone());
+
+        send_probes(&client, cli_config.profile_id, &cli_config.profile_key, &results).await;
+    }
+
+    Ok(())
 }
 
-async fn run_service(options: CollectorOptions, sleep_interval: u64) -> Result<(), CliError> {
+async fn run_service(
+    mut options: CollectorOptions,
+    sleep_interval: u64,
+    sequence: Sequence,
+    spool: Spool,
+) -> Result<(), CliError> {
     let cli_config = CliConfig::load(CONFIG_FILE_PATH).await?;
+    options.set_checks(cli_config.checks.clone());
+    let client = SysInfoClient::new(cli_config.client.clone());
+
+    spawn_probes(
+        cli_config.probes.clone(),
+        cli_config.client.clone(),
+        cli_config.profile_id,
+        cli_config.profile_key.clone(),
+    );
 
     loop {
-        post_system_info(&options, &cli_config.client, cli_config.profile_id, &cli_config.profile_key).await?;
+        let info = collect_system_info(
+            &options,
+            &sequence,
+            &client,
+            cli_config.profile_id,
+            &cli_config.profile_key,
+        )
+        .await;
+
+        let reachable = replay_spool(
+            &spool,
+            &client,
+            cli_config.profile_id,
+            &cli_config.profile_key,
+        )
+        .await;
+
+        if let Some(info) = info {
+            let delivery = if reachable {
+                send_system_info(&client, cli_config.profile_id, &cli_config.profile_key, &info)
+                    .await
+            } else {
+                Delivery::Unreachable
+            };
+
+            if let Delivery::Unreachable = delivery {
+                match spool.push(&info).await {
+                    Ok(_) => info!("Spooled system information {}", describe(&info)),
+                    Err(why) => error!("Failed to spool system information, error: {:?}", why),
+                }
+            }
+        }
 
         sleep(Duration::from_secs(sleep_interval)).await;
     }
 }
 
+/// Sends the spooled snapshots in the order they were collected.
+/// Returns false if the server isn't reachable, the remaining entries are kept for the next attempt.
+async fn replay_spool(
+    spool: &Spool,
+    client: &SysInfoClient,
+    profile_id: i32,
+    profile_key: &str,
+) -> bool {
+    let mut entries = match spool.load().await {
+        Ok(entries) => entries,
+        Err(why) => {
+            error!("Failed to load spool, error: {:?}", why);
+            return true;
+        }
+    };
+
+    if entries.is_empty() {
+        return true;
+    }
+
+    let mut sent = 0;
+    let mut reachable = true;
+
+    for entry in &entries {
+        match send_system_info(client, profile_id, profile_key, entry).await {
+            Delivery::Sent => sent += 1,
+            Delivery::Rejected => {
+                warn!("Dropping spooled system information {}", describe(entry));
+                sent += 1;
+            }
+            Delivery::Unreachable => {
+                reachable = false;
+                break;
+            }
+        }
+    }
+
+    if sent > 0 {
+        info!("Replayed {sent} of {} spooled snapshots", entries.len());
+        entries.drain(..sent);
+
+        if let Err(why) = spool.store(&entries).await {
+            error!("Failed to update spool, error: {:?}", why);
+        }
+    }
+
+    reachable
+}
+
 async fn post_system_info(
     options: &CollectorOptions,
+    sequence: &Sequence,
     client_config: &ClientConfig,
     profile_id: i32,
     profile_key: &str,
 ) -> Result<(), CliError> {
-    let system = System::new();
-    let hostname = hostname::get().map_err(|x| CliError::Io(x))?;
-    let os_info = if options.os() {
-        Some(os_info::get())
-    } else {
-        None
-    };
+    let client = SysInfoClient::new(client_config.clone());
 
-    if let Ok(info) = SystemInformation::collect(options, system, hostname, os_info) {
-        let client = SysInfoClient::new(client_config.clone());
+    let info = collect_system_info(options, sequence, &client, profile_id, profile_key).await;
 
+    if let Some(info) = info {
         match client.get_version().await {
             Ok((version, status)) => {
                 if !status.is_success() {
@@ -120,24 +264,145 @@ async fn post_system_info(
 
                 info!("Server version: {version}");
 
-                if let Err(why) = client.post_sys_info(profile_id, profile_key, info).await {
-                    error!("Failed to post system information, error: {:?}", why);
-                } else {
-                    info!(
-                        "Successfully send system information for profile {}",
-                        profile_id
-                    );
-                }
+                send_system_info(&client, profile_id, profile_key, &info).await;
             }
             Err(why) => error!("Failed to get server version, error: {:?}", why),
         }
-    } else {
-        warn!("Collecting of system information failed!");
     }
 
     Ok(())
 }
 
+/// Collects a snapshot, failures are reported to the service as error logs.
+async fn collect_system_info(
+    options: &CollectorOptions,
+    sequence: &Sequence,
+    client: &SysInfoClient,
+    profile_id: i32,
+    profile_key: &str,
+) -> Option<SystemInformation> {
+    let system = System::new();
+    let hostname = match hostname::get() {
+        Ok(hostname) => hostname,
+        Err(why) => {
+            warn!("Failed to get hostname, error: {:?}", why);
+            let message = format!("Failed to get hostname: {why}");
+            report_error(client, profile_id, profile_key, "hostname", message).await;
+            return None;
+        }
+    };
+
+    // Collecting the cpu load blocks for a second, the probes run on the same runtime.
+    let options = options.clone();
+    let collected =
+        tokio::task::spawn_blocking(move || SystemInformation::collect(&options, system, hostname))
+            .await;
+
+    let mut info = match collected {
+        Ok(Ok(info)) => info,
+        Ok(Err(why)) => {
+            warn!("Collecting of system information failed, error: {:?}", why);
+
+            let source = why
+                .downcast_ref::<CollectorFailed>()
+                .map(|x| x.collector.as_str())
+                .unwrap_or("system");
+            report_error(client, profile_id, profile_key, source, format!("{why:#}")).await;
+
+            return None;
+        }
+        Err(why) => {
+            warn!("Collecting of system information failed, error: {:?}", why);
+            let message = format!("Failed to collect system information: {why}");
+            report_error(client, profile_id, profile_key, "system", message).await;
+            return None;
+        }
+    };
+
+    match sequence.next().await {
+        Ok(sequence) => info.sequence = Some(sequence),
+        Err(why) => error!("Failed to get the next sequence number, error: {:?}", why),
+    }
+
+    Some(info)
+}
+
+fn describe(info: &SystemInformation) -> String {
+    match (info.sequence, info.collected_at) {
+        (Some(sequence), Some(collected_at)) => format!("'{sequence}' collected at {collected_at}"),
+        (None, Some(collected_at)) => format!("collected at {collected_at}"),
+        (Some(sequence), None) => format!("'{sequence}'"),
+        (None, None) => String::from("without collection time"),
+    }
+}
+
+/// Outcome of posting a snapshot to the service.
+enum Delivery {
+    Sent,
+    /// The service refused the payload itself, sending it again won't help.
+    Rejected,
+    Unreachable,
+}
+
+async fn send_system_info(
+    client: &SysInfoClient,
+    profile_id: i32,
+    profile_key: &str,
+    info: &SystemInformation,
+) -> Delivery {
+    match client.post_sys_info(profile_id, profile_key, info).await {
+        Ok(status) if status.is_success() => {
+            info!(
+                "Successfully send system information for profile {}",
+                profile_id
+            );
+            Delivery::Sent
+        }
+        Ok(status)
+            if status == StatusCode::BAD_REQUEST || status == StatusCode::UNPROCESSABLE_ENTITY =>
+        {
+            error!("System information was rejected, status code: {status}");
+            Delivery::Rejected
+        }
+        Ok(status) => {
+            error!("Failed to post system information, status code: {status}");
+            Delivery::Unreachable
+        }
+        Err(why) => {
+            error!("Failed to post system information, error: {:?}", why);
+            Delivery::Unreachable
+        }
+    }
+}
+
+async fn report_error(
+    client: &SysInfoClient,
+    profile_id: i32,
+    profile_key: &str,
+    source: &str,
+    message: String,
+) {
+    let context = BTreeMap::from([
+        (String::from("version"), String::from(env!("CARGO_PKG_VERSION"))),
+        (String::from("os"), String::from(env::consts::OS)),
+        (String::from("arch"), String::from(env::consts::ARCH)),
+    ]);
+    let error_log = ErrorLog {
+        profile_id: profile_id as u32,
+        severity: Severity::Error,
+        source: String::from(source),
+        message,
+        timestamp: Utc::now().naive_utc(),
+        context,
+    };
+
+    match client.post_error_log(profile_id, profile_key, &error_log).await {
+        Ok(status) if status.is_success() => info!("Reported error of '{source}' to the server"),
+        Ok(status) => error!("Failed to post error log, status code: {status}"),
+        Err(why) => error!("Failed to post error log, error: {:?}", why),
+    }
+}
+
 async fn configure(
     api_key: Option<String>,
     profile_key: Option<String>,
@@ -152,6 +417,8 @@ async fn configure(
             },
             profile_id: 0,
             profile_key: String::new(),
+            checks: Vec::new(),
+            probes: Vec::new(),
         };
 
         new_config.save(CONFIG_FILE_PATH).await?;
@@ -0,0 +1,699 @@
+use std::{
+    io::ErrorKind,
+    net::{IpAddr, SocketAddr, UdpSocket},
+    sync::atomic::{AtomicU16, Ordering},
+    time::{Duration, Instant},
+};
+
+use chrono::{NaiveDateTime, Utc};
+use log::{error, info};
+use monitoring_core::{
+    api::models::{ProbeKind, ProbeResult},
+    client::{ClientConfig, SysInfoClient},
+};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::{
+    net::{self, TcpStream},
+    sync::mpsc,
+    task::{self, JoinSet},
+    time::{self, timeout, MissedTickBehavior},
+};
+
+/// A probe against a service, run apart from the collection of the system information.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProbeConfig {
+    pub name: String,
+    #[serde(flatten)]
+    pub target: ProbeTarget,
+    /// Seconds until the probe fails.
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+    /// Seconds between two runs of the probe in service mode.
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ProbeTarget {
+    /// A GET request, successful for a 2xx status unless another status is expected.
+    Http {
+        url: String,
+        #[serde(default)]
+        expected_status: Option<u16>,
+        /// Text the body has to contain.
+        #[serde(default)]
+        contains: Option<String>,
+    },
+    /// A connection to `host:port`.
+    Tcp { address: String },
+    /// An ICMP echo request to the host.
+    Ping { host: String },
+}
+
+/// Identifier of the echo requests on raw sockets, unprivileged sockets get one from the kernel.
+const ECHO_ID: u16 = 0x6d70;
+
+/// Sequence number of the next echo request, tells the replies of concurrent pings apart.
+static ECHO_SEQUENCE: AtomicU16 = AtomicU16::new(0);
+
+fn default_timeout() -> u64 {
+    10
+}
+
+fn default_interval() -> u64 {
+    60
+}
+
+/// Runs every probe at its interval and posts the results, batching the ones that finish together.
+pub fn spawn_probes(
+    probes: Vec<ProbeConfig>,
+    client_config: ClientConfig,
+    profile_id: i32,
+    profile_key: String,
+) {
+    if probes.is_empty() {
+        return;
+    }
+
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+
+    for probe in probes {
+        let sender = sender.clone();
+
+        tokio::spawn(async move {
+            let mut interval = time::interval(Duration::from_secs(probe.interval.max(1)));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                interval.tick().await;
+
+                if sender.send(run_probe(&probe).await).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    tokio::spawn(async move {
+        let client = SysInfoClient::new(client_config);
+
+        while let Some(result) = receiver.recv().await {
+            let mut results = vec![result];
+
+            while let Ok(result) = receiver.try_recv() {
+                results.push(result);
+            }
+
+            send_probes(&client, profile_id, &profile_key, &results).await;
+        }
+    });
+}
+
+/// Runs all probes once at the same time.
+pub async fn run_probes(probes: &[ProbeConfig]) -> Vec<ProbeResult> {
+    let mut tasks = JoinSet::new();
+
+    for probe in probes {
+        let probe = probe.clone();
+        tasks.spawn(async move { run_probe(&probe).await });
+    }
+
+    let mut results = Vec::new();
+
+    while let Some(result) = tasks.join_next().await {
+        match result {
+            Ok(result) => results.push(result),
+            Err(why) => error!("Failed to run probe, error: {:?}", why),
+        }
+    }
+
+    results
+}
+
+pub async fn send_probes(
+    client: &SysInfoClient,
+    profile_id: i32,
+    profile_key: &str,
+    results: &[ProbeResult],
+) {
+    if results.is_empty() {
+        return;
+    }
+
+    match client.post_probes(profile_id, profile_key, results).await {
+        Ok(status) if status.is_success() => {
+            info!("Successfully send {} probe results", results.len())
+        }
+        Ok(status) => error!("Failed to post probe results, status code: {status}"),
+        Err(why) => error!("Failed to post probe results, error: {:?}", why),
+    }
+}
+
+async fn run_probe(config: &ProbeConfig) -> ProbeResult {
+    let executed_at = Utc::now().naive_utc();
+    let limit = Duration::from_secs(config.timeout);
+
+    let (kind, target) = match &config.target {
+        ProbeTarget::Http { url, .. } => (ProbeKind::Http, url.clone()),
+        ProbeTarget::Tcp { address } => (ProbeKind::Tcp, address.clone()),
+        ProbeTarget::Ping { host } => (ProbeKind::Ping, host.clone()),
+    };
+
+    let mut result = ProbeResult {
+        name: config.name.clone(),
+        kind,
+        target,
+        success: false,
+        latency: None,
+        status_code: None,
+        body_match: None,
+        cert_expiry_days: None,
+        error: None,
+        executed_at,
+    };
+
+    let outcome = match &config.target {
+        ProbeTarget::Http {
+            url,
+            expected_status,
+            contains,
+        } => {
+            let probe = http_probe(
+                &mut result,
+                url,
+                *expected_status,
+                contains.as_deref(),
+                limit,
+            );
+
+            timeout(limit, probe).await
+        }
+        ProbeTarget::Tcp { address } => timeout(limit, tcp_probe(&mut result, address)).await,
+        ProbeTarget::Ping { host } => timeout(limit, ping_probe(&mut result, host, limit)).await,
+    };
+
+    match outcome {
+        Ok(Ok(())) => {}
+        Ok(Err(why)) => result.error = Some(why),
+        Err(_) => result.error = Some(format!("Timed out after {} seconds", config.timeout)),
+    }
+
+    result
+}
+
+async fn http_probe(
+    result: &mut ProbeResult,
+    url: &str,
+    expected_status: Option<u16>,
+    contains: Option<&str>,
+    limit: Duration,
+) -> Result<(), String> {
+    let url = Url::parse(url).map_err(|why| format!("Invalid url: {why}"))?;
+    let client = reqwest::Client::builder()
+        .timeout(limit)
+        .build()
+        .map_err(|why| why.to_string())?;
+
+    let started = Instant::now();
+    let response = client
+        .get(url.clone())
+        .send()
+        .await
+        .map_err(|why| why.to_string())?;
+    result.latency = Some(started.elapsed().as_secs_f64());
+
+    let status = response.status();
+    result.status_code = Some(status.as_u16());
+    result.success = match expected_status {
+        Some(expected) => status.as_u16() == expected,
+        None => status.is_success(),
+    };
+
+    if let Some(contains) = contains {
+        let body = response.text().await.map_err(|why| why.to_string())?;
+        let body_match = body.contains(contains);
+
+        result.body_match = Some(body_match);
+        result.success &= body_match;
+    }
+
+    if url.scheme() == "https" {
+        match cert_expiry_days(&url).await {
+            Ok(days) => result.cert_expiry_days = Some(days),
+            Err(why) => result.error = Some(why),
+        }
+    }
+
+    Ok(())
+}
+
+async fn tcp_probe(result: &mut ProbeResult, address: &str) -> Result<(), String> {
+    let started = Instant::now();
+
+    TcpStream::connect(address)
+        .await
+        .map_err(|why| why.to_string())?;
+
+    result.latency = Some(started.elapsed().as_secs_f64());
+    result.success = true;
+
+    Ok(())
+}
+
+async fn ping_probe(result: &mut ProbeResult, host: &str, limit: Duration) -> Result<(), String> {
+    let address = net::lookup_host((host, 0))
+        .await
+        .map_err(|why| why.to_string())?
+        .next()
+        .ok_or_else(|| format!("No address found for '{host}'"))?;
+    let sequence = ECHO_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+
+    // The socket is blocking, waiting for the reply on the runtime would stall the other probes.
+    let latency = task::spawn_blocking(move || ping(address.ip(), sequence, limit))
+        .await
+        .map_err(|why| why.to_string())??;
+
+    result.latency = Some(latency.as_secs_f64());
+    result.success = true;
+
+    Ok(())
+}
+
+/// Sends an echo request and returns the time until its reply arrived.
+/// Unprivileged ICMP sockets are used if `net.ipv4.ping_group_range` allows them,
+/// otherwise raw sockets which need root or `CAP_NET_RAW`.
+fn ping(ip: IpAddr, sequence: u16, limit: Duration) -> Result<Duration, String> {
+    let (domain, protocol, request_type, reply_type) = match ip {
+        IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4, 8, 0),
+        IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6, 128, 129),
+    };
+
+    let (socket, raw) = match Socket::new(domain, Type::DGRAM, Some(protocol)) {
+        Ok(socket) => (socket, false),
+        Err(_) => match Socket::new(domain, Type::RAW, Some(protocol)) {
+            Ok(socket) => (socket, true),
+            Err(why) => return Err(format!("Failed to open an ICMP socket: {why}")),
+        },
+    };
+    // Only sends and receives whole packets, which std implements for any datagram socket.
+    let socket = UdpSocket::from(socket);
+    // Raw IPv4 sockets receive the packets with their IP header.
+    let has_ip_header = raw && ip.is_ipv4();
+
+    let started = Instant::now();
+    socket
+        .send_to(
+            &echo_request(request_type, sequence),
+            SocketAddr::new(ip, 0),
+        )
+        .map_err(|why| why.to_string())?;
+
+    let mut buffer = [0; 1024];
+
+    loop {
+        let remaining = limit.saturating_sub(started.elapsed());
+
+        if remaining.is_zero() {
+            return Err(String::from("No echo reply received"));
+        }
+
+        socket
+            .set_read_timeout(Some(remaining))
+            .map_err(|why| why.to_string())?;
+
+        let length = match socket.recv(&mut buffer) {
+            Ok(length) => length,
+            Err(why) if matches!(why.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                return Err(String::from("No echo reply received"));
+            }
+            Err(why) => return Err(why.to_string()),
+        };
+
+        // Raw sockets also receive the replies of other pings on the host.
+        let reply = parse_echo_reply(&buffer[..length], has_ip_header, reply_type);
+
+        if let Some((id, reply_sequence)) = reply {
+            if reply_sequence == sequence && (!raw || id == ECHO_ID) {
+                return Ok(started.elapsed());
+            }
+        }
+    }
+}
+
+/// An ICMP echo request with an empty payload.
+fn echo_request(request_type: u8, sequence: u16) -> Vec<u8> {
+    let mut packet = vec![request_type, 0, 0, 0];
+    packet.extend_from_slice(&ECHO_ID.to_be_bytes());
+    packet.extend_from_slice(&sequence.to_be_bytes());
+
+    // The kernel fills in the checksum of ICMPv6, which covers the IPv6 header as well.
+    let checksum = internet_checksum(&packet);
+    packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+
+    packet
+}
+
+/// The identifier and sequence number of an echo reply, `None` for other packets.
+fn parse_echo_reply(packet: &[u8], has_ip_header: bool, reply_type: u8) -> Option<(u16, u16)> {
+    let packet = if has_ip_header {
+        let header_length = (*packet.first()? & 0x0f) as usize * 4;
+        packet.get(header_length..)?
+    } else {
+        packet
+    };
+
+    if packet.len() < 8 || packet[0] != reply_type || packet[1] != 0 {
+        return None;
+    }
+
+    Some((
+        u16::from_be_bytes([packet[4], packet[5]]),
+        u16::from_be_bytes([packet[6], packet[7]]),
+    ))
+}
+
+/// The ones' complement of the ones' complement sum of the 16 bit words.
+fn internet_checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|x| u32::from(u16::from_be_bytes([x[0], *x.get(1).unwrap_or(&0)])))
+        .sum();
+
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+
+    !(sum as u16)
+}
+
+/// Days until the certificate of the server expires, negative if it already expired.
+/// The certificate isn't verified here, the request of the probe fails for invalid ones.
+async fn cert_expiry_days(url: &Url) -> Result<i64, String> {
+    let host = url.host_str().ok_or("Missing host in url")?;
+    let port = url.port_or_known_default().unwrap_or(443);
+
+    let stream = TcpStream::connect((host, port))
+        .await
+        .map_err(|why| why.to_string())?;
+    let connector = native_tls::TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .map_err(|why| why.to_string())?;
+    let stream = tokio_native_tls::TlsConnector::from(connector)
+        .connect(host, stream)
+        .await
+        .map_err(|why| why.to_string())?;
+
+    let certificate = stream
+        .get_ref()
+        .peer_certificate()
+        .map_err(|why| why.to_string())?
+        .ok_or("Server sent no certificate")?;
+    let der = certificate.to_der().map_err(|why| why.to_string())?;
+    let not_after = not_after(&der).ok_or("Invalid certificate")?;
+
+    Ok((not_after - Utc::now().naive_utc()).num_days())
+}
+
+/// The end of the validity period of a DER encoded X.509 certificate.
+fn not_after(der: &[u8]) -> Option<NaiveDateTime> {
+    let (_, certificate, _) = der_element(der)?;
+    let (_, tbs_certificate, _) = der_element(certificate)?;
+
+    // The version is optional and tagged [0], the serial number follows it.
+    let (tag, _, mut rest) = der_element(tbs_certificate)?;
+    if tag == 0xa0 {
+        rest = der_element(rest)?.2;
+    }
+
+    // Skip the signature algorithm and the issuer.
+    for _ in 0..2 {
+        rest = der_element(rest)?.2;
+    }
+
+    let (_, validity, _) = der_element(rest)?;
+    let (_, _, rest) = der_element(validity)?;
+    let (tag, time, _) = der_element(rest)?;
+    let time = std::str::from_utf8(time).ok()?;
+
+    match tag {
+        // UTCTime
+        0x17 => NaiveDateTime::parse_from_str(time, "%y%m%d%H%M%SZ").ok(),
+        // GeneralizedTime
+        0x18 => NaiveDateTime::parse_from_str(time, "%Y%m%d%H%M%SZ").ok(),
+        _ => None,
+    }
+}
+
+/// Splits a DER element into its tag, its contents and the data after it.
+fn der_element(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = data.split_first()?;
+    let (&length, mut rest) = rest.split_first()?;
+
+    let length = if length & 0x80 == 0 {
+        length as usize
+    } else {
+        let count = (length & 0x7f) as usize;
+
+        if count == 0 || count > 4 || rest.len() < count {
+            return None;
+        }
+
+        let (bytes, after) = rest.split_at(count);
+        rest = after;
+
+        bytes
+            .iter()
+            .fold(0, |length, x| (length << 8) | *x as usize)
+    };
+
+    if rest.len() < length {
+        return None;
+    }
+
+    let (contents, rest) = rest.split_at(length);
+
+    Some((tag, contents, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use monitoring_core::api::models::ProbeKind;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::{
+        echo_request, internet_checksum, parse_echo_reply, run_probe, ProbeConfig, ProbeTarget,
+        ECHO_ID,
+    };
+
+    /// Answers every connection with `response` after reading the request.
+    async fn http_server(response: &'static str) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).await;
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+
+        address
+    }
+
+    fn http(
+        address: SocketAddr,
+        expected_status: Option<u16>,
+        contains: Option<&str>,
+    ) -> ProbeConfig {
+        ProbeConfig {
+            name: String::from("web"),
+            target: ProbeTarget::Http {
+                url: format!("http://{address}/health"),
+                expected_status,
+                contains: contains.map(String::from),
+            },
+            timeout: 5,
+            interval: 60,
+        }
+    }
+
+    fn tcp(address: SocketAddr) -> ProbeConfig {
+        ProbeConfig {
+            name: String::from("port"),
+            target: ProbeTarget::Tcp {
+                address: address.to_string(),
+            },
+            timeout: 5,
+            interval: 60,
+        }
+    }
+
+    fn ping(host: &str) -> ProbeConfig {
+        ProbeConfig {
+            name: String::from("host"),
+            target: ProbeTarget::Ping {
+                host: String::from(host),
+            },
+            timeout: 1,
+            interval: 60,
+        }
+    }
+
+    #[tokio::test]
+    async fn http_success() {
+        let address = http_server(
+            "HTTP/1.1 204 No Content\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+        )
+        .await;
+        let result = run_probe(&http(address, None, None)).await;
+
+        assert_eq!(result.kind, ProbeKind::Http);
+        assert!(result.success);
+        assert_eq!(result.status_code, Some(204));
+        assert!(result.latency.is_some_and(|x| (0.0..5.0).contains(&x)));
+        assert_eq!(result.error, None);
+    }
+
+    #[tokio::test]
+    async fn http_error_status() {
+        let address = http_server(
+            "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+        )
+        .await;
+
+        let result = run_probe(&http(address, None, None)).await;
+        assert!(!result.success);
+        assert_eq!(result.status_code, Some(503));
+
+        let result = run_probe(&http(address, Some(503), None)).await;
+        assert!(result.success);
+    }
+
+    #[tokio::test]
+    async fn http_body_match() {
+        let address =
+            http_server("HTTP/1.1 200 OK\r\ncontent-length: 6\r\nconnection: close\r\n\r\nstatus")
+                .await;
+
+        let result = run_probe(&http(address, None, Some("stat"))).await;
+        assert!(result.success);
+        assert_eq!(result.body_match, Some(true));
+
+        let result = run_probe(&http(address, None, Some("healthy"))).await;
+        assert!(!result.success);
+        assert_eq!(result.body_match, Some(false));
+    }
+
+    #[tokio::test]
+    async fn http_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = http(listener.local_addr().unwrap(), None, None);
+        config.timeout = 1;
+
+        let result = run_probe(&config).await;
+
+        assert!(!result.success);
+        assert!(result.error.is_some());
+        assert_eq!(result.status_code, None);
+    }
+
+    #[tokio::test]
+    async fn tcp_connect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let result = run_probe(&tcp(listener.local_addr().unwrap())).await;
+
+        assert_eq!(result.kind, ProbeKind::Tcp);
+        assert!(result.success);
+        assert!(result.latency.is_some());
+        assert_eq!(result.error, None);
+    }
+
+    #[tokio::test]
+    async fn tcp_connection_refused() {
+        let address = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let result = run_probe(&tcp(address)).await;
+
+        assert!(!result.success);
+        assert_eq!(result.latency, None);
+        assert!(result.error.is_some());
+    }
+
+    #[test]
+    fn ping_config() {
+        let config: ProbeConfig =
+            serde_json::from_str(r#"{"name":"gateway","type":"ping","host":"10.0.0.1"}"#).unwrap();
+
+        assert!(matches!(config.target, ProbeTarget::Ping { host } if host == "10.0.0.1"));
+        assert_eq!(config.timeout, 10);
+    }
+
+    #[test]
+    fn echo_request_checksum() {
+        let request = echo_request(8, 7);
+
+        assert_eq!(request.len(), 8);
+        assert_eq!(request[0], 8);
+        assert_eq!(&request[4..6], &ECHO_ID.to_be_bytes());
+        assert_eq!(&request[6..8], &[0, 7]);
+        // The checksum over a packet including its checksum is zero.
+        assert_eq!(internet_checksum(&request), 0);
+    }
+
+    #[test]
+    fn echo_reply_parsing() {
+        let reply = [0, 0, 0x12, 0x34, 0x6d, 0x70, 0, 7];
+        assert_eq!(parse_echo_reply(&reply, false, 0), Some((ECHO_ID, 7)));
+
+        // A minimal IPv4 header of 5 words in front of the reply.
+        let mut packet = vec![0x45];
+        packet.extend_from_slice(&[0; 19]);
+        packet.extend_from_slice(&reply);
+        assert_eq!(parse_echo_reply(&packet, true, 0), Some((ECHO_ID, 7)));
+
+        // The echo request of another ping, looped back to a raw socket.
+        assert_eq!(parse_echo_reply(&echo_request(8, 7), false, 0), None);
+        assert_eq!(parse_echo_reply(&reply[..6], false, 0), None);
+        assert_eq!(parse_echo_reply(&[0x45, 0, 0], true, 0), None);
+    }
+
+    #[tokio::test]
+    async fn ping_localhost() {
+        let result = run_probe(&ping("127.0.0.1")).await;
+
+        assert_eq!(result.kind, ProbeKind::Ping);
+        assert_eq!(result.target, "127.0.0.1");
+
+        // Without root or a matching `net.ipv4.ping_group_range` no ICMP socket is available.
+        match result.error {
+            Some(error) => assert!(error.starts_with("Failed to open an ICMP socket")),
+            None => {
+                assert!(result.success);
+                assert!(result.latency.is_some_and(|x| (0.0..1.0).contains(&x)));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn ping_timeout() {
+        // TEST-NET-2 is reserved for documentation and never answers.
+        let result = run_probe(&ping("198.51.100.1")).await;
+
+        assert!(!result.success);
+        assert_eq!(result.latency, None);
+        assert!(result.error.is_some());
+    }
+}
@@ -0,0 +1,40 @@
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use tokio::fs;
+
+use crate::error::CliError;
+
+static SEQUENCE_FILE: &str = "sequence";
+
+/// Sequence number of the collected snapshots, persisted in the data dir.
+/// The next number is at least the current unix time in milliseconds, so it keeps increasing
+/// even if the data dir is lost, as long as the clock doesn't go backwards.
+pub struct Sequence {
+    path: PathBuf,
+}
+
+impl Sequence {
+    pub async fn open(data_dir: &Path) -> Result<Self, CliError> {
+        fs::create_dir_all(data_dir).await.map_err(CliError::Io)?;
+
+        Ok(Self {
+            path: data_dir.join(SEQUENCE_FILE),
+        })
+    }
+
+    pub async fn next(&self) -> Result<u64, CliError> {
+        let last = match fs::read_to_string(&self.path).await {
+            Ok(contents) => contents.trim().parse::<u64>().unwrap_or_default(),
+            Err(why) if why.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(why) => return Err(CliError::Io(why)),
+        };
+        let next = (last + 1).max(Utc::now().timestamp_millis() as u64);
+
+        fs::write(&self.path, next.to_string())
+            .await
+            .map_err(CliError::Io)?;
+
+        Ok(next)
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    io::{self, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use log::warn;
+use monitoring_core::models::SystemInformation;
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+};
+
+use crate::error::CliError;
+
+static SPOOL_FILE: &str = "spool.jsonl";
+
+/// Append-only file of undelivered snapshots, one json entry per line, oldest first.
+/// When the file grows beyond `max_size` bytes the oldest entries are dropped.
+pub struct Spool {
+    path: PathBuf,
+    max_size: u64,
+}
+
+impl Spool {
+    pub async fn open(data_dir: &Path, max_size: u64) -> Result<Self, CliError> {
+        fs::create_dir_all(data_dir).await.map_err(CliError::Io)?;
+
+        Ok(Self {
+            path: data_dir.join(SPOOL_FILE),
+            max_size,
+        })
+    }
+
+    pub async fn push(&self, entry: &SystemInformation) -> Result<(), CliError> {
+        let mut line = serde_json::to_string(entry).map_err(CliError::Serde)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(CliError::Io)?;
+
+        // An interrupted write can leave a partial last line, the entry has to start on a new one.
+        if !ends_with_newline(&mut file).await.map_err(CliError::Io)? {
+            line.insert(0, '\n');
+        }
+
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(CliError::Io)?;
+        file.flush().await.map_err(CliError::Io)?;
+
+        let size = file.metadata().await.map_err(CliError::Io)?.len();
+
+        if size > self.max_size {
+            self.truncate().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns all entries in the order they were pushed.
+    /// Lines that can't be parsed, e.g. from an interrupted write, are skipped.
+    pub async fn load(&self) -> Result<Vec<SystemInformation>, CliError> {
+        let contents = match fs::read_to_string(&self.path).await {
+            Ok(contents) => contents,
+            Err(why) if why.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(why) => return Err(CliError::Io(why)),
+        };
+
+        let entries = contents
+            .lines()
+            .filter(|x| !x.trim().is_empty())
+            .filter_map(|x| match serde_json::from_str(x) {
+                Ok(entry) => Some(entry),
+                Err(why) => {
+                    warn!("Skipping invalid spool entry: {why}");
+                    None
+                }
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// Replaces the spool with `entries`, removing the file if there are none left.
+    pub async fn store(&self, entries: &[SystemInformation]) -> Result<(), CliError> {
+        if entries.is_empty() {
+            return match fs::remove_file(&self.path).await {
+                Err(why) if why.kind() != std::io::ErrorKind::NotFound => Err(CliError::Io(why)),
+                _ => Ok(()),
+            };
+        }
+
+        let mut contents = String::new();
+
+        for entry in entries {
+            contents.push_str(&serde_json::to_string(entry).map_err(CliError::Serde)?);
+            contents.push('\n');
+        }
+
+        // Written to a temporary file first so a crash can't leave a half written spool.
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, contents).await.map_err(CliError::Io)?;
+        fs::rename(&tmp_path, &self.path)
+            .await
+            .map_err(CliError::Io)
+    }
+
+    async fn truncate(&self) -> Result<(), CliError> {
+        let mut entries = self.load().await?;
+        let mut sizes = Vec::with_capacity(entries.len());
+
+        for entry in &entries {
+            sizes.push(serde_json::to_string(entry).map_err(CliError::Serde)?.len() as u64 + 1);
+        }
+
+        let mut size: u64 = sizes.iter().sum();
+        let mut dropped = 0;
+
+        while size > self.max_size && dropped < sizes.len() {
+            size -= sizes[dropped];
+            dropped += 1;
+        }
+
+        warn!(
+            "Spool exceeds {} bytes, dropping the {dropped} oldest entries",
+            self.max_size
+        );
+        entries.drain(..dropped);
+
+        self.store(&entries).await
+    }
+}
+
+/// True for an empty file or one whose last byte is a newline.
+async fn ends_with_newline(file: &mut File) -> io::Result<bool> {
+    let len = file.metadata().await?.len();
+
+    if len == 0 {
+        return Ok(true);
+    }
+
+    let mut last = [0; 1];
+    file.seek(SeekFrom::Start(len - 1)).await?;
+    file.read_exact(&mut last).await?;
+
+    Ok(last[0] == b'\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use chrono::NaiveDate;
+    use monitoring_core::models::SystemInformation;
+    use tempfile::TempDir;
+    use tokio::fs;
+
+    use super::{Spool, SPOOL_FILE};
+
+    fn entry(sequence: u64) -> SystemInformation {
+        SystemInformation {
+            hostname: "host".into(),
+            os_info: None,
+            cpu: None,
+            load_avg: None,
+            memory: None,
+            swap: None,
+            battery_life: None,
+            mounts: Vec::new(),
+            networks: HashMap::new(),
+            net_stats: HashMap::new(),
+            socket_stats: None,
+            processes: None,
+            disk_io: Vec::new(),
+            thermals: None,
+            checks: Vec::new(),
+            extra: Default::default(),
+            uptime: Duration::from_secs(60),
+            boot_time: NaiveDate::from_ymd_opt(2023, 6, 1)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+            collected_at: None,
+            sequence: Some(sequence),
+        }
+    }
+
+    /// Size of a spooled entry including its newline, the same for sequences of one digit.
+    fn entry_size() -> u64 {
+        serde_json::to_string(&entry(0)).unwrap().len() as u64 + 1
+    }
+
+    async fn sequences(spool: &Spool) -> Vec<u64> {
+        spool
+            .load()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter_map(|x| x.sequence)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn keeps_entries_in_order() {
+        let dir = TempDir::new().unwrap();
+        let spool = Spool::open(dir.path(), 10 * entry_size()).await.unwrap();
+
+        for sequence in 0..3 {
+            spool.push(&entry(sequence)).await.unwrap();
+        }
+
+        assert_eq!(sequences(&spool).await, [0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn drops_oldest_entries_over_max_size() {
+        let dir = TempDir::new().unwrap();
+        let spool = Spool::open(dir.path(), 3 * entry_size()).await.unwrap();
+
+        for sequence in 0..5 {
+            spool.push(&entry(sequence)).await.unwrap();
+        }
+
+        assert_eq!(sequences(&spool).await, [2, 3, 4]);
+
+        let size = fs::metadata(dir.path().join(SPOOL_FILE))
+            .await
+            .unwrap()
+            .len();
+        assert_eq!(size, 3 * entry_size());
+    }
+
+    #[tokio::test]
+    async fn drops_until_under_max_size() {
+        let dir = TempDir::new().unwrap();
+        let spool = Spool::open(dir.path(), 2 * entry_size() + entry_size() / 2)
+            .await
+            .unwrap();
+
+        for sequence in 0..3 {
+            spool.push(&entry(sequence)).await.unwrap();
+        }
+
+        assert_eq!(sequences(&spool).await, [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn skips_corrupt_trailing_line() {
+        let dir = TempDir::new().unwrap();
+        let spool = Spool::open(dir.path(), 10 * entry_size()).await.unwrap();
+
+        spool.push(&entry(0)).await.unwrap();
+        spool.push(&entry(1)).await.unwrap();
+
+        let path = dir.path().join(SPOOL_FILE);
+        let mut contents = fs::read_to_string(&path).await.unwrap();
+        contents.push_str("{\"hostname\":\"ho");
+        fs::write(&path, contents).await.unwrap();
+
+        assert_eq!(sequences(&spool).await, [0, 1]);
+
+        spool.push(&entry(2)).await.unwrap();
+
+        assert_eq!(sequences(&spool).await, [0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn truncate_removes_corrupt_lines() {
+        let dir = TempDir::new().unwrap();
+        let spool = Spool::open(dir.path(), 2 * entry_size()).await.unwrap();
+        let path = dir.path().join(SPOOL_FILE);
+
+        spool.push(&entry(0)).await.unwrap();
+        fs::write(&path, fs::read_to_string(&path).await.unwrap() + "not json")
+            .await
+            .unwrap();
+        spool.push(&entry(1)).await.unwrap();
+        spool.push(&entry(2)).await.unwrap();
+
+        assert_eq!(sequences(&spool).await, [1, 2]);
+        assert!(!fs::read_to_string(&path)
+            .await
+            .unwrap()
+            .contains("not json"));
+    }
+
+    #[tokio::test]
+    async fn store_without_entries_removes_the_file() {
+        let dir = TempDir::new().unwrap();
+        let spool = Spool::open(dir.path(), 10 * entry_size()).await.unwrap();
+
+        spool.push(&entry(0)).await.unwrap();
+        spool.store(&[]).await.unwrap();
+
+        assert!(!dir.path().join(SPOOL_FILE).exists());
+        assert!(spool.load().await.unwrap().is_empty());
+    }
+}
@@ -1,8 +1,517 @@
-use serde::{Serialize, Deserialize};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::Severity;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct InsertDeviceProfile {
     pub device_name: String,
+}
+
+/// A profile key generated by the service, it can't be retrieved again.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewProfileKey {
+    pub profile_id: i32,
+    pub id_profile_key: i32,
+    /// The key devices send, `<id_profile_key>.<secret>`.
     pub profile_key: String,
+    /// End of the grace period of the replaced keys, `None` for a new profile.
+    pub previous_expire_date: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RotateProfileKey {
+    /// Seconds the replaced keys stay valid, the service default if missing.
+    pub grace_period: Option<i64>,
+}
+
+impl RotateProfileKey {
+    /// Longest grace period in seconds the service accepts.
+    pub const MAX_GRACE_PERIOD: i64 = 30 * 24 * 60 * 60;
+}
+
+/// A profile key as returned by the service, without the key itself.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProfileKeyInfo {
+    pub id_profile_key: i32,
+    pub create_user: i64,
+    pub create_date: NaiveDateTime,
+    pub expire_date: Option<NaiveDateTime>,
+    pub revoke_date: Option<NaiveDateTime>,
+}
+
+/// A device profile as returned by the service, without its profile key.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeviceProfile {
+    pub id_device_profile: i32,
+    pub device_name: String,
+    /// Id of the user whose token created the profile.
+    pub create_user: i64,
+    pub create_date: NaiveDateTime,
+    /// Date of the latest snapshot, `None` if the device never sent one.
+    pub last_seen: Option<NaiveDateTime>,
+}
+
+/// The metrics which can be queried as a time series for a device profile.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MetricKind {
+    Cpu,
+    Memory,
+    Swap,
+    LoadAverage,
+    Filesystem,
+    Network,
+    /// Per second rates of the network counters, computed by the service.
+    NetworkRate,
+    DiskIo,
+}
+
+/// A time series for one metric of a device profile.
+/// Metrics with multiple instances (mounts, interfaces) have one series per instance.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MetricSeries {
+    pub profile_id: i32,
+    pub metric: MetricKind,
+    pub from: NaiveDateTime,
+    pub to: NaiveDateTime,
+    pub step: Option<u32>,
+    pub series: Vec<Series>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Series {
+    pub name: String,
+    pub points: Vec<MetricPoint>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MetricPoint {
+    pub timestamp: NaiveDateTime,
+    pub values: BTreeMap<String, f64>,
+}
+
+impl MetricKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MetricKind::Cpu => "cpu",
+            MetricKind::Memory => "memory",
+            MetricKind::Swap => "swap",
+            MetricKind::LoadAverage => "load-average",
+            MetricKind::Filesystem => "filesystem",
+            MetricKind::Network => "network",
+            MetricKind::NetworkRate => "network-rate",
+            MetricKind::DiskIo => "disk-io",
+        }
+    }
+}
+
+impl fmt::Display for MetricKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MetricKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cpu" => Ok(MetricKind::Cpu),
+            "memory" => Ok(MetricKind::Memory),
+            "swap" => Ok(MetricKind::Swap),
+            "load-average" => Ok(MetricKind::LoadAverage),
+            "filesystem" => Ok(MetricKind::Filesystem),
+            "network" => Ok(MetricKind::Network),
+            "network-rate" => Ok(MetricKind::NetworkRate),
+            "disk-io" => Ok(MetricKind::DiskIo),
+            _ => Err(format!("Unknown metric '{s}'.")),
+        }
+    }
+}
+
+impl MetricPoint {
+    pub fn new(timestamp: NaiveDateTime, values: &[(&str, f64)]) -> Self {
+        Self {
+            timestamp,
+            values: values
+                .iter()
+                .map(|(name, value)| (String::from(*name), *value))
+                .collect(),
+        }
+    }
+}
+
+/// Result of a probe of the agent against a service.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProbeResult {
+    pub name: String,
+    pub kind: ProbeKind,
+    /// Url of a http probe, address of a tcp probe.
+    pub target: String,
+    pub success: bool,
+    /// Seconds until the response or the connection, missing if the probe failed before.
+    pub latency: Option<f64>,
+    pub status_code: Option<u16>,
+    /// Whether the body contained the expected text, missing if there is none.
+    pub body_match: Option<bool>,
+    /// Days until the tls certificate expires, missing for plain connections.
+    pub cert_expiry_days: Option<i64>,
+    pub error: Option<String>,
+    /// Time in UTC the probe was started.
+    pub executed_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProbeKind {
+    Http,
+    Tcp,
+    Ping,
+}
+
+/// An error log of a device as returned by the service.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorLogEntry {
+    pub id_error_log: i32,
+    pub profile_id: i32,
+    pub severity: Severity,
+    pub source: String,
+    pub message: String,
+    pub context: BTreeMap<String, String>,
+    /// Time in UTC the error occurred on the device.
+    pub timestamp: NaiveDateTime,
+    pub receive_date: NaiveDateTime,
+    pub acknowledged_user: Option<i64>,
+    pub acknowledged_date: Option<NaiveDateTime>,
+}
+
+/// A page of the error logs of a device profile, newest first.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorLogPage {
+    pub page: u32,
+    pub per_page: u32,
+    /// Number of error logs matching the filters on all pages.
+    pub total: i64,
+    pub errors: Vec<ErrorLogEntry>,
+}
+
+/// Filters of the error log query, missing values aren't filtered.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ErrorLogQuery {
+    /// Page starting at 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+    /// The minimum severity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// Unix timestamp in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<i64>,
+    /// Unix timestamp in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub acknowledged: Option<bool>,
+}
+
+/// The values an alert rule can watch.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AlertMetric {
+    /// Aggregated cpu idle time as a fraction between 0 and 1.
+    CpuIdle,
+    CpuTemperature,
+    /// Temperature of the sensor `chip/label` in `target`, the hottest sensor without a target.
+    Temperature,
+    /// Rpm of the fan `chip/label` in `target`, the slowest fan without a target.
+    FanSpeed,
+    /// The one minute load average.
+    LoadAverage,
+    /// Free memory as a fraction of the total memory.
+    MemoryFree,
+    /// Available memory as a fraction of the total memory.
+    /// Page cache and other reclaimable memory count as available.
+    MemoryAvailable,
+    /// Free swap as a fraction of the total swap.
+    SwapFree,
+    /// Available space of the mount in `target` as a fraction of its size.
+    FilesystemAvail,
+    BatteryRemaining,
+    /// Status of the check in `target`, 0 ok, 1 warning, 2 critical and 3 unknown.
+    CheckStatus,
+    /// 1 if the probe in `target` succeeded, 0 if it failed.
+    ProbeSuccess,
+    /// Days until the certificate of the probe in `target` expires.
+    CertExpiry,
+    /// Seconds since the last snapshot of the profile.
+    NoSnapshot,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AlertComparison {
+    Below,
+    Above,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InsertAlertRule {
+    pub profile_id: i32,
+    pub metric: AlertMetric,
+    pub target: Option<String>,
+    pub comparison: AlertComparison,
+    pub threshold: f64,
+    /// Seconds the condition has to hold before the rule fires.
+    pub duration: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AlertRule {
+    pub id_alert_rule: i32,
+    pub profile_id: i32,
+    pub metric: AlertMetric,
+    pub target: Option<String>,
+    pub comparison: AlertComparison,
+    pub threshold: f64,
+    pub duration: i64,
     pub create_user: i64,
-}
\ No newline at end of file
+    pub create_date: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Alert {
+    pub id_alert: i32,
+    pub rule: AlertRule,
+    pub device_name: String,
+    pub value: f64,
+    pub fired_date: NaiveDateTime,
+    pub resolved_date: Option<NaiveDateTime>,
+    /// The value which resolved the alert.
+    #[serde(default)]
+    pub resolved_value: Option<f64>,
+}
+
+impl AlertMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertMetric::CpuIdle => "cpu-idle",
+            AlertMetric::CpuTemperature => "cpu-temperature",
+            AlertMetric::Temperature => "temperature",
+            AlertMetric::FanSpeed => "fan-speed",
+            AlertMetric::LoadAverage => "load-average",
+            AlertMetric::MemoryFree => "memory-free",
+            AlertMetric::MemoryAvailable => "memory-available",
+            AlertMetric::SwapFree => "swap-free",
+            AlertMetric::FilesystemAvail => "filesystem-avail",
+            AlertMetric::BatteryRemaining => "battery-remaining",
+            AlertMetric::CheckStatus => "check-status",
+            AlertMetric::ProbeSuccess => "probe-success",
+            AlertMetric::CertExpiry => "cert-expiry",
+            AlertMetric::NoSnapshot => "no-snapshot",
+        }
+    }
+}
+
+impl fmt::Display for AlertMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AlertMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cpu-idle" => Ok(AlertMetric::CpuIdle),
+            "cpu-temperature" => Ok(AlertMetric::CpuTemperature),
+            "temperature" => Ok(AlertMetric::Temperature),
+            "fan-speed" => Ok(AlertMetric::FanSpeed),
+            "load-average" => Ok(AlertMetric::LoadAverage),
+            "memory-free" => Ok(AlertMetric::MemoryFree),
+            "memory-available" => Ok(AlertMetric::MemoryAvailable),
+            "swap-free" => Ok(AlertMetric::SwapFree),
+            "filesystem-avail" => Ok(AlertMetric::FilesystemAvail),
+            "battery-remaining" => Ok(AlertMetric::BatteryRemaining),
+            "check-status" => Ok(AlertMetric::CheckStatus),
+            "probe-success" => Ok(AlertMetric::ProbeSuccess),
+            "cert-expiry" => Ok(AlertMetric::CertExpiry),
+            "no-snapshot" => Ok(AlertMetric::NoSnapshot),
+            _ => Err(format!("Unknown alert metric '{s}'.")),
+        }
+    }
+}
+
+impl ProbeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProbeKind::Http => "http",
+            ProbeKind::Tcp => "tcp",
+            ProbeKind::Ping => "ping",
+        }
+    }
+}
+
+impl fmt::Display for ProbeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ProbeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "http" => Ok(ProbeKind::Http),
+            "tcp" => Ok(ProbeKind::Tcp),
+            "ping" => Ok(ProbeKind::Ping),
+            _ => Err(format!("Unknown probe kind '{s}'.")),
+        }
+    }
+}
+
+impl AlertComparison {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertComparison::Below => "below",
+            AlertComparison::Above => "above",
+        }
+    }
+
+    /// Returns true if `value` violates the `threshold`.
+    pub fn is_violated(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            AlertComparison::Below => value < threshold,
+            AlertComparison::Above => value > threshold,
+        }
+    }
+}
+
+impl fmt::Display for AlertComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AlertComparison {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "below" => Ok(AlertComparison::Below),
+            "above" => Ok(AlertComparison::Above),
+            _ => Err(format!("Unknown alert comparison '{s}'.")),
+        }
+    }
+}
+
+/// The permissions an api token can be granted.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    #[serde(rename = "profiles:read")]
+    ProfilesRead,
+    /// Create profiles and manage their keys.
+    #[serde(rename = "profiles:write")]
+    ProfilesWrite,
+    /// Snapshots, metrics, error logs, alerts and alert rules.
+    #[serde(rename = "metrics:read")]
+    MetricsRead,
+    /// Create and delete alert rules.
+    #[serde(rename = "alerts:manage")]
+    AlertsManage,
+    /// Acknowledge error logs.
+    #[serde(rename = "errors:manage")]
+    ErrorsManage,
+    /// Every other scope on all profiles, also manages users and their tokens.
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InsertUser {
+    pub name: String,
+    pub discord_id: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct User {
+    pub id_user: i32,
+    pub name: String,
+    pub discord_id: Option<i64>,
+    pub create_date: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InsertApiToken {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    /// The profiles the token is restricted to, all profiles if missing.
+    pub profile_ids: Option<Vec<i32>>,
+    pub expire_date: Option<NaiveDateTime>,
+}
+
+/// An api token generated by the service, it can't be retrieved again.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewApiToken {
+    pub id_api_token: i32,
+    pub user_id: i32,
+    pub token: String,
+}
+
+/// An api token as returned by the service, without the token itself.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiTokenInfo {
+    pub id_api_token: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub profile_ids: Option<Vec<i32>>,
+    pub create_date: NaiveDateTime,
+    pub expire_date: Option<NaiveDateTime>,
+    pub revoke_date: Option<NaiveDateTime>,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 6] = [
+        Scope::ProfilesRead,
+        Scope::ProfilesWrite,
+        Scope::MetricsRead,
+        Scope::AlertsManage,
+        Scope::ErrorsManage,
+        Scope::Admin,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::ProfilesRead => "profiles:read",
+            Scope::ProfilesWrite => "profiles:write",
+            Scope::MetricsRead => "metrics:read",
+            Scope::AlertsManage => "alerts:manage",
+            Scope::ErrorsManage => "errors:manage",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scope::ALL
+            .into_iter()
+            .find(|x| x.as_str() == s)
+            .ok_or_else(|| format!("Unknown scope '{s}'."))
+    }
+}
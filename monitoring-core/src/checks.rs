@@ -0,0 +1,436 @@
+use std::{
+    collections::HashMap,
+    io::Read,
+    process::{Command, Stdio},
+    sync::Mutex,
+    thread::{self, sleep},
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use systemstat::System;
+
+use crate::{
+    collector::BuiltinCollector,
+    models::{Check, CheckStatus, PerfData, SystemInformation},
+};
+
+/// Time between polls of a running check command.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A Nagios plugin style command, run without a shell.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CheckConfig {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Seconds after which the command is killed and the check is unknown.
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+    /// Minimum seconds between two runs, snapshots in between carry the last result.
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+}
+
+fn default_timeout() -> u64 {
+    10
+}
+
+fn default_interval() -> u64 {
+    60
+}
+
+/// Runs the configured checks that are due on every collection, in parallel.
+pub struct CheckRunner {
+    checks: Vec<CheckConfig>,
+    results: Mutex<HashMap<String, (Instant, Check)>>,
+}
+
+impl CheckRunner {
+    pub fn new(checks: Vec<CheckConfig>) -> Self {
+        Self {
+            checks,
+            results: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Results of all checks, running the ones whose interval has passed.
+    pub fn run(&self) -> Vec<Check> {
+        let due: Vec<&CheckConfig> = {
+            let results = self.results.lock().unwrap_or_else(|x| x.into_inner());
+
+            self.checks
+                .iter()
+                .filter(|check| match results.get(&check.name) {
+                    Some((last_run, _)) => {
+                        last_run.elapsed() >= Duration::from_secs(check.interval)
+                    }
+                    None => true,
+                })
+                .collect()
+        };
+
+        let finished: Vec<(Instant, Check)> = thread::scope(|scope| {
+            let handles: Vec<_> = due
+                .into_iter()
+                .map(|check| scope.spawn(move || (Instant::now(), run_check(check))))
+                .collect();
+
+            handles.into_iter().filter_map(|x| x.join().ok()).collect()
+        });
+
+        let mut results = self.results.lock().unwrap_or_else(|x| x.into_inner());
+
+        for (started, check) in finished {
+            results.insert(check.name.clone(), (started, check));
+        }
+
+        self.checks
+            .iter()
+            .filter_map(|check| results.get(&check.name).map(|(_, x)| x.clone()))
+            .collect()
+    }
+}
+
+impl BuiltinCollector for CheckRunner {
+    fn name(&self) -> &str {
+        "checks"
+    }
+
+    fn collect_into(&self, _system: &System, info: &mut SystemInformation) -> anyhow::Result<()> {
+        info.checks = self.run();
+
+        Ok(())
+    }
+}
+
+fn run_check(config: &CheckConfig) -> Check {
+    let executed_at = Utc::now().naive_utc();
+    let started = Instant::now();
+
+    let (status, output) = match execute(config) {
+        Ok((code, output)) => (CheckStatus::from_exit_code(code), output),
+        Err(why) => (CheckStatus::Unknown, why),
+    };
+    let (text, perfdata) = parse_output(&output);
+
+    Check {
+        name: config.name.clone(),
+        status,
+        output: text,
+        perfdata,
+        executed_at,
+        duration: started.elapsed().as_secs_f64(),
+    }
+}
+
+/// Exit code and stdout of the command, an error message if it couldn't run or timed out.
+fn execute(config: &CheckConfig) -> Result<(Option<i32>, String), String> {
+    let mut child = Command::new(&config.command)
+        .args(&config.args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|why| format!("Failed to run '{}': {why}", config.command))?;
+
+    // Read in the background, a full pipe would block the command.
+    let mut stdout = child.stdout.take();
+    let reader = thread::spawn(move || {
+        let mut output = String::new();
+
+        if let Some(stdout) = stdout.as_mut() {
+            let _ = stdout.read_to_string(&mut output);
+        }
+
+        output
+    });
+
+    let timeout = Duration::from_secs(config.timeout);
+    let started = Instant::now();
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+
+                return Err(format!("Timed out after {} seconds", config.timeout));
+            }
+            Ok(None) => sleep(POLL_INTERVAL),
+            Err(why) => return Err(format!("Failed to wait for '{}': {why}", config.command)),
+        }
+    };
+
+    let output = reader.join().unwrap_or_default();
+
+    Ok((status.code(), output))
+}
+
+/// Splits plugin output into the first line of text and the performance data.
+/// Performance data follows a `|` on the first line and on the long output lines.
+pub fn parse_output(output: &str) -> (String, Vec<PerfData>) {
+    let mut lines = output.lines();
+    let first = lines.next().unwrap_or_default();
+
+    let (text, perfdata) = match first.split_once('|') {
+        Some((text, perfdata)) => (text, Some(perfdata)),
+        None => (first, None),
+    };
+
+    let mut values: Vec<PerfData> = perfdata.map(parse_perfdata).unwrap_or_default();
+    let mut in_perfdata = false;
+
+    for line in lines {
+        if in_perfdata {
+            values.extend(parse_perfdata(line));
+        } else if let Some((_, perfdata)) = line.split_once('|') {
+            in_perfdata = true;
+            values.extend(parse_perfdata(perfdata));
+        }
+    }
+
+    (text.trim().to_string(), values)
+}
+
+/// Parses space separated `label=value[unit];[warn];[crit];[min];[max]` entries.
+/// Labels may be quoted with `'`, entries without a numeric value are skipped.
+pub fn parse_perfdata(perfdata: &str) -> Vec<PerfData> {
+    let mut values = Vec::new();
+    let mut chars = perfdata.trim().chars().peekable();
+
+    loop {
+        while chars.next_if(|x| x.is_whitespace()).is_some() {}
+
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut label = String::new();
+
+        if chars.next_if_eq(&'\'').is_some() {
+            while let Some(c) = chars.next() {
+                if c == '\'' {
+                    // A doubled quote is a quote in the label.
+                    if chars.next_if_eq(&'\'').is_none() {
+                        break;
+                    }
+                }
+
+                label.push(c);
+            }
+        }
+
+        while let Some(c) = chars.next_if(|x| *x != '=' && !x.is_whitespace()) {
+            label.push(c);
+        }
+
+        if chars.next_if_eq(&'=').is_none() {
+            continue;
+        }
+
+        let mut value = String::new();
+
+        while let Some(c) = chars.next_if(|x| !x.is_whitespace()) {
+            value.push(c);
+        }
+
+        if let Some(value) = parse_value(label, &value) {
+            values.push(value);
+        }
+    }
+
+    values
+}
+
+fn parse_value(label: String, value: &str) -> Option<PerfData> {
+    let mut fields = value.split(';');
+    let value = fields.next()?;
+    let unit_start = value
+        .find(|x: char| !(x.is_ascii_digit() || x == '.' || x == '-' || x == '+'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(unit_start);
+
+    let mut field = || {
+        fields
+            .next()
+            .map(|x| x.trim().to_string())
+            .filter(|x| !x.is_empty())
+    };
+
+    Some(PerfData {
+        label,
+        value: number.parse().ok()?,
+        unit: unit.to_string(),
+        warn: field(),
+        crit: field(),
+        min: field().and_then(|x| x.parse().ok()),
+        max: field().and_then(|x| x.parse().ok()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_output, parse_perfdata, run_check, CheckConfig};
+    use crate::models::{CheckStatus, PerfData};
+
+    type Fields<'a> = (
+        &'a str,
+        f64,
+        &'a str,
+        Option<&'a str>,
+        Option<&'a str>,
+        Option<f64>,
+        Option<f64>,
+    );
+
+    fn fields(x: &PerfData) -> Fields<'_> {
+        (
+            &x.label,
+            x.value,
+            &x.unit,
+            x.warn.as_deref(),
+            x.crit.as_deref(),
+            x.min,
+            x.max,
+        )
+    }
+
+    fn check(command: &str, args: &[&str], timeout: u64) -> CheckConfig {
+        CheckConfig {
+            name: String::from("test"),
+            command: String::from(command),
+            args: args.iter().map(|x| x.to_string()).collect(),
+            timeout,
+            interval: 0,
+        }
+    }
+
+    #[test]
+    fn perfdata() {
+        let cases: &[(&str, &[Fields])] = &[
+            ("load=0.5", &[("load", 0.5, "", None, None, None, None)]),
+            (
+                "time=0.021s;1;2;0;10",
+                &[(
+                    "time",
+                    0.021,
+                    "s",
+                    Some("1"),
+                    Some("2"),
+                    Some(0.0),
+                    Some(10.0),
+                )],
+            ),
+            (
+                "used=80%;@~:90;95",
+                &[("used", 80.0, "%", Some("@~:90"), Some("95"), None, None)],
+            ),
+            (
+                "temp=-5.5C;;;-40",
+                &[("temp", -5.5, "C", None, None, Some(-40.0), None)],
+            ),
+            (
+                "'disk usage'=1024KB;;;0;2048",
+                &[(
+                    "disk usage",
+                    1024.0,
+                    "KB",
+                    None,
+                    None,
+                    Some(0.0),
+                    Some(2048.0),
+                )],
+            ),
+            ("'it''s'=1", &[("it's", 1.0, "", None, None, None, None)]),
+            (
+                "  a=1  b=2c ",
+                &[
+                    ("a", 1.0, "", None, None, None, None),
+                    ("b", 2.0, "c", None, None, None, None),
+                ],
+            ),
+            (
+                "missing=U;1;2 novalue ok=3",
+                &[("ok", 3.0, "", None, None, None, None)],
+            ),
+            ("", &[]),
+        ];
+
+        for (input, expected) in cases {
+            let values = parse_perfdata(input);
+            let values: Vec<Fields> = values.iter().map(fields).collect();
+
+            assert_eq!(&values, expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn output_with_long_perfdata() {
+        let (text, perfdata) = parse_output(
+            "DISK OK - free space | /=2643MB;5948;5958;0;5968\n/ 15272 MB (77%);\n/boot 68 MB (69%);\n/home 69357 MB (27%);| /boot=68MB;88;93;0;98\n/home=69357MB;253404;253409;0;253414",
+        );
+        let labels: Vec<&str> = perfdata.iter().map(|x| x.label.as_str()).collect();
+
+        assert_eq!(text, "DISK OK - free space");
+        assert_eq!(labels, ["/", "/boot", "/home"]);
+        assert_eq!(perfdata[2].value, 69357.0);
+    }
+
+    #[test]
+    fn output_without_perfdata() {
+        let (text, perfdata) = parse_output("PING OK\nsecond line\n");
+
+        assert_eq!(text, "PING OK");
+        assert!(perfdata.is_empty());
+        assert_eq!(parse_output("").0, "");
+    }
+
+    #[test]
+    fn status_of_exit_codes() {
+        let cases = [
+            ("exit 0", CheckStatus::Ok),
+            ("exit 1", CheckStatus::Warning),
+            ("exit 2", CheckStatus::Critical),
+            ("exit 3", CheckStatus::Unknown),
+            ("exit 42", CheckStatus::Unknown),
+            ("kill -9 $$", CheckStatus::Unknown),
+        ];
+
+        for (script, status) in cases {
+            assert_eq!(
+                run_check(&check("sh", &["-c", script], 10)).status,
+                status,
+                "{script}"
+            );
+        }
+    }
+
+    #[test]
+    fn output_of_the_command() {
+        let result = run_check(&check("sh", &["-c", "echo 'LOAD OK | load=0.5;1;2'"], 10));
+
+        assert_eq!(result.output, "LOAD OK");
+        assert_eq!(result.perfdata.len(), 1);
+    }
+
+    #[test]
+    fn missing_command_is_unknown() {
+        let result = run_check(&check("/nonexistent/check", &[], 10));
+
+        assert_eq!(result.status, CheckStatus::Unknown);
+        assert!(result.output.starts_with("Failed to run"));
+    }
+
+    #[test]
+    fn timeout_kills_the_command() {
+        let result = run_check(&check("sleep", &["10"], 1));
+
+        assert_eq!(result.status, CheckStatus::Unknown);
+        assert_eq!(result.output, "Timed out after 1 seconds");
+        assert!(result.duration < 5.0);
+    }
+}
@@ -1,7 +1,16 @@
+use chrono::NaiveDateTime;
 use reqwest::StatusCode;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::{api::models::InsertDeviceProfile, models::SystemInformation, ErrorLog};
+use crate::{
+    api::models::{
+        Alert, AlertRule, ApiTokenInfo, DeviceProfile, ErrorLogPage, ErrorLogQuery,
+        InsertAlertRule, InsertApiToken, InsertDeviceProfile, InsertUser, MetricKind, MetricSeries,
+        NewApiToken, NewProfileKey, ProbeResult, ProfileKeyInfo, RotateProfileKey, User,
+    },
+    models::SystemInformation,
+    ErrorLog,
+};
 
 pub struct SysInfoClient {
     config: ClientConfig,
@@ -14,6 +23,7 @@ pub enum ClientError {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ClientConfig {
+    /// Api token of a service user, devices only need their profile key.
     pub api_key: String,
     pub server_url: String,
 }
@@ -35,43 +45,398 @@ impl SysInfoClient {
     pub async fn get_version(&self) -> Result<(String, StatusCode), ClientError> {
         let resp = reqwest::Client::new()
             .get(format!("{}/", self.config.server_url))
-            .header("x-api-key", self.config.api_key.clone())
             .send()
             .await
-            .map_err(|x| ClientError::Reqwest(x))?;
+            .map_err(ClientError::Reqwest)?;
 
-        let status = resp.status().clone();
-        let version = resp.text().await.map_err(|x| ClientError::Reqwest(x))?;
+        let status = resp.status();
+        let version = resp.text().await.map_err(ClientError::Reqwest)?;
 
         Ok((version, status))
     }
 
-    pub async fn get_profiles(&self, read_key: &str) -> Result<(String, StatusCode), ClientError> {
+    pub async fn get_profiles(
+        &self,
+    ) -> Result<(Option<Vec<DeviceProfile>>, StatusCode), ClientError> {
         let resp = reqwest::Client::new()
-            .get(format!("{}/", self.config.server_url))
-            .header("x-api-key", self.config.api_key.clone())
-            .header("x-read-key", read_key)
+            .get(format!("{}/profiles", self.config.server_url))
+            .bearer_auth(&self.config.api_key)
             .send()
             .await
-            .map_err(|x| ClientError::Reqwest(x))?;
+            .map_err(ClientError::Reqwest)?;
 
-        let status = resp.status().clone();
-        let version = resp.text().await.map_err(|x| ClientError::Reqwest(x))?;
+        let status = resp.status();
 
-        Ok((version, status))
+        if !status.is_success() {
+            return Ok((None, status));
+        }
+
+        let profiles = resp
+            .json::<Vec<DeviceProfile>>()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Ok((Some(profiles), status))
+    }
+
+    pub async fn get_latest_sys_info(
+        &self,
+        profile_id: i32,
+    ) -> Result<(Option<SystemInformation>, StatusCode), ClientError> {
+        let resp = reqwest::Client::new()
+            .get(format!(
+                "{}/system-info/{}",
+                self.config.server_url, profile_id
+            ))
+            .bearer_auth(&self.config.api_key)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        let status = resp.status();
+
+        if !status.is_success() {
+            return Ok((None, status));
+        }
+
+        let info = resp
+            .json::<SystemInformation>()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Ok((Some(info), status))
     }
 
+    pub async fn get_metrics(
+        &self,
+        profile_id: i32,
+        metric: MetricKind,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        step: Option<u32>,
+    ) -> Result<(Option<MetricSeries>, StatusCode), ClientError> {
+        let mut query = vec![
+            ("from", from.timestamp().to_string()),
+            ("to", to.timestamp().to_string()),
+        ];
+
+        if let Some(step) = step {
+            query.push(("step", step.to_string()));
+        }
+
+        let resp = reqwest::Client::new()
+            .get(format!(
+                "{}/profiles/{}/metrics/{}",
+                self.config.server_url, profile_id, metric
+            ))
+            .bearer_auth(&self.config.api_key)
+            .query(&query)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        let status = resp.status();
+
+        if !status.is_success() {
+            return Ok((None, status));
+        }
+
+        let series = resp
+            .json::<MetricSeries>()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Ok((Some(series), status))
+    }
+
+    /// `since` keeps the alerts which fired or resolved since the unix timestamp.
+    pub async fn get_alerts(
+        &self,
+        profile_id: Option<i32>,
+        active_only: bool,
+        since: Option<i64>,
+    ) -> Result<(Option<Vec<Alert>>, StatusCode), ClientError> {
+        let mut query = vec![("active", active_only.to_string())];
+
+        if let Some(profile_id) = profile_id {
+            query.push(("profile_id", profile_id.to_string()));
+        }
+
+        if let Some(since) = since {
+            query.push(("since", since.to_string()));
+        }
+
+        let resp = reqwest::Client::new()
+            .get(format!("{}/alerts", self.config.server_url))
+            .bearer_auth(&self.config.api_key)
+            .query(&query)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        let status = resp.status();
+
+        if !status.is_success() {
+            return Ok((None, status));
+        }
+
+        let alerts = resp
+            .json::<Vec<Alert>>()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Ok((Some(alerts), status))
+    }
+
+    pub async fn get_alert_rules(
+        &self,
+        profile_id: Option<i32>,
+    ) -> Result<(Option<Vec<AlertRule>>, StatusCode), ClientError> {
+        let mut request = reqwest::Client::new()
+            .get(format!("{}/alerts/rules", self.config.server_url))
+            .bearer_auth(&self.config.api_key);
+
+        if let Some(profile_id) = profile_id {
+            request = request.query(&[("profile_id", profile_id)]);
+        }
+
+        let resp = request.send().await.map_err(ClientError::Reqwest)?;
+
+        let status = resp.status();
+
+        if !status.is_success() {
+            return Ok((None, status));
+        }
+
+        let rules = resp
+            .json::<Vec<AlertRule>>()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Ok((Some(rules), status))
+    }
+
+    pub async fn get_error_logs(
+        &self,
+        profile_id: i32,
+        query: &ErrorLogQuery,
+    ) -> Result<(Option<ErrorLogPage>, StatusCode), ClientError> {
+        let resp = reqwest::Client::new()
+            .get(format!(
+                "{}/profiles/{}/errors",
+                self.config.server_url, profile_id
+            ))
+            .bearer_auth(&self.config.api_key)
+            .query(query)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        let status = resp.status();
+
+        if !status.is_success() {
+            return Ok((None, status));
+        }
+
+        let page = resp
+            .json::<ErrorLogPage>()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Ok((Some(page), status))
+    }
+
+    pub async fn acknowledge_error_log(&self, error_id: i32) -> Result<StatusCode, ClientError> {
+        let resp = reqwest::Client::new()
+            .post(format!(
+                "{}/errors/{}/ack",
+                self.config.server_url, error_id
+            ))
+            .bearer_auth(&self.config.api_key)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Ok(resp.status())
+    }
+
+    pub async fn post_alert_rule(&self, data: InsertAlertRule) -> Result<StatusCode, ClientError> {
+        let resp = reqwest::Client::new()
+            .post(format!("{}/alerts/rules", self.config.server_url))
+            .bearer_auth(&self.config.api_key)
+            .json(&data)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Ok(resp.status())
+    }
+
+    pub async fn delete_alert_rule(&self, rule_id: i32) -> Result<StatusCode, ClientError> {
+        let resp = reqwest::Client::new()
+            .delete(format!(
+                "{}/alerts/rules/{}",
+                self.config.server_url, rule_id
+            ))
+            .bearer_auth(&self.config.api_key)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Ok(resp.status())
+    }
+
+    /// Creates a profile, the response contains its generated key.
     pub async fn post_profile(
         &self,
         data: InsertDeviceProfile,
-    ) -> Result<StatusCode, ClientError> {
+    ) -> Result<(Option<NewProfileKey>, StatusCode), ClientError> {
         let resp = reqwest::Client::new()
             .post(format!("{}/profiles", self.config.server_url))
-            .header("x-api-key", self.config.api_key.clone())
+            .bearer_auth(&self.config.api_key)
+            .json(&data)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Self::new_profile_key(resp).await
+    }
+
+    pub async fn rotate_profile_key(
+        &self,
+        profile_id: i32,
+        data: RotateProfileKey,
+    ) -> Result<(Option<NewProfileKey>, StatusCode), ClientError> {
+        let resp = reqwest::Client::new()
+            .post(format!(
+                "{}/profiles/{}/rotate-key",
+                self.config.server_url, profile_id
+            ))
+            .bearer_auth(&self.config.api_key)
+            .json(&data)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Self::new_profile_key(resp).await
+    }
+
+    pub async fn get_profile_keys(
+        &self,
+        profile_id: i32,
+    ) -> Result<(Option<Vec<ProfileKeyInfo>>, StatusCode), ClientError> {
+        let resp = reqwest::Client::new()
+            .get(format!(
+                "{}/profiles/{}/keys",
+                self.config.server_url, profile_id
+            ))
+            .bearer_auth(&self.config.api_key)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        let status = resp.status();
+
+        if !status.is_success() {
+            return Ok((None, status));
+        }
+
+        let keys = resp
+            .json::<Vec<ProfileKeyInfo>>()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Ok((Some(keys), status))
+    }
+
+    pub async fn revoke_profile_key(
+        &self,
+        profile_id: i32,
+        key_id: i32,
+    ) -> Result<StatusCode, ClientError> {
+        let resp = reqwest::Client::new()
+            .delete(format!(
+                "{}/profiles/{}/keys/{}",
+                self.config.server_url, profile_id, key_id
+            ))
+            .bearer_auth(&self.config.api_key)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Ok(resp.status())
+    }
+
+    pub async fn post_user(
+        &self,
+        data: InsertUser,
+    ) -> Result<(Option<User>, StatusCode), ClientError> {
+        let resp = reqwest::Client::new()
+            .post(format!("{}/users", self.config.server_url))
+            .bearer_auth(&self.config.api_key)
             .json(&data)
             .send()
             .await
-            .map_err(|x| ClientError::Reqwest(x))?;
+            .map_err(ClientError::Reqwest)?;
+
+        Self::json_response(resp).await
+    }
+
+    pub async fn get_users(&self) -> Result<(Option<Vec<User>>, StatusCode), ClientError> {
+        let resp = reqwest::Client::new()
+            .get(format!("{}/users", self.config.server_url))
+            .bearer_auth(&self.config.api_key)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Self::json_response(resp).await
+    }
+
+    /// Creates a token for the user, the response contains the token itself.
+    pub async fn post_api_token(
+        &self,
+        user_id: i32,
+        data: InsertApiToken,
+    ) -> Result<(Option<NewApiToken>, StatusCode), ClientError> {
+        let resp = reqwest::Client::new()
+            .post(format!(
+                "{}/users/{}/tokens",
+                self.config.server_url, user_id
+            ))
+            .bearer_auth(&self.config.api_key)
+            .json(&data)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Self::json_response(resp).await
+    }
+
+    pub async fn get_api_tokens(
+        &self,
+        user_id: i32,
+    ) -> Result<(Option<Vec<ApiTokenInfo>>, StatusCode), ClientError> {
+        let resp = reqwest::Client::new()
+            .get(format!(
+                "{}/users/{}/tokens",
+                self.config.server_url, user_id
+            ))
+            .bearer_auth(&self.config.api_key)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Self::json_response(resp).await
+    }
+
+    pub async fn revoke_api_token(&self, token_id: i32) -> Result<StatusCode, ClientError> {
+        let resp = reqwest::Client::new()
+            .delete(format!("{}/tokens/{}", self.config.server_url, token_id))
+            .bearer_auth(&self.config.api_key)
+            .send()
+            .await
+            .map_err(ClientError::Reqwest)?;
 
         Ok(resp.status())
     }
@@ -80,22 +445,63 @@ impl SysInfoClient {
         &self,
         profile_id: i32,
         profile_key: &str,
-        data: SystemInformation,
+        data: &SystemInformation,
     ) -> Result<StatusCode, ClientError> {
         self.post_with_profile_key(profile_id, profile_key, "system-info", data)
             .await
     }
 
+    pub async fn post_probes(
+        &self,
+        profile_id: i32,
+        profile_key: &str,
+        data: &[ProbeResult],
+    ) -> Result<StatusCode, ClientError> {
+        self.post_with_profile_key(profile_id, profile_key, "probes", data)
+            .await
+    }
+
     pub async fn post_error_log(
         &self,
         profile_id: i32,
         profile_key: &str,
-        data: ErrorLog,
+        data: &ErrorLog,
     ) -> Result<StatusCode, ClientError> {
         self.post_with_profile_key(profile_id, profile_key, "error", data)
             .await
     }
 
+    async fn new_profile_key(
+        resp: reqwest::Response,
+    ) -> Result<(Option<NewProfileKey>, StatusCode), ClientError> {
+        let status = resp.status();
+
+        if !status.is_success() {
+            return Ok((None, status));
+        }
+
+        let key = resp
+            .json::<NewProfileKey>()
+            .await
+            .map_err(ClientError::Reqwest)?;
+
+        Ok((Some(key), status))
+    }
+
+    async fn json_response<T: DeserializeOwned>(
+        resp: reqwest::Response,
+    ) -> Result<(Option<T>, StatusCode), ClientError> {
+        let status = resp.status();
+
+        if !status.is_success() {
+            return Ok((None, status));
+        }
+
+        let data = resp.json::<T>().await.map_err(ClientError::Reqwest)?;
+
+        Ok((Some(data), status))
+    }
+
     async fn post_with_profile_key<T: Serialize>(
         &self,
         profile_id: i32,
@@ -108,12 +514,11 @@ impl SysInfoClient {
                 "{}/{}/{}",
                 self.config.server_url, sub_path, profile_id
             ))
-            .header("x-api-key", self.config.api_key.clone())
             .header("x-profile-key", profile_key)
             .json(&data)
             .send()
             .await
-            .map_err(|x| ClientError::Reqwest(x))?;
+            .map_err(ClientError::Reqwest)?;
 
         Ok(resp.status())
     }
@@ -0,0 +1,182 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use systemstat::{Platform, System};
+
+use crate::models::{
+    BatteryLife, CpuInformation, DiskIo, Filesystem, LoadAverage, Memory, Network,
+    NetworkStatistics, Processes, SocketStatistics, Swap, SystemInformation, Thermals,
+};
+
+/// A custom section of the system information.
+///
+/// Collectors registered with [`CollectorOptions::register`](crate::options::CollectorOptions::register)
+/// are sent in `extra` of [`SystemInformation`].
+pub trait Collector: Send + Sync {
+    /// Name of the section, the key of the collector in `extra`.
+    fn name(&self) -> &str;
+
+    /// Collects the section, serialized as json.
+    fn collect(&self, system: &System) -> anyhow::Result<serde_json::Value>;
+}
+
+/// A built-in section, it fills the field of [`SystemInformation`] with the same name.
+pub(crate) trait BuiltinCollector: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn collect_into(&self, system: &System, info: &mut SystemInformation) -> anyhow::Result<()>;
+}
+
+/// Context of the error of a failed built-in collector.
+/// The name of the collector can be recovered with `anyhow::Error::downcast_ref`.
+#[derive(Debug)]
+pub struct CollectorFailed {
+    pub collector: String,
+}
+
+impl fmt::Display for CollectorFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed to collect '{}'", self.collector)
+    }
+}
+
+/// A built-in section collected by a function and stored into its field by another.
+struct Section<T> {
+    name: &'static str,
+    collect: fn(&System) -> anyhow::Result<T>,
+    store: fn(&mut SystemInformation, T),
+}
+
+impl<T> BuiltinCollector for Section<T> {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn collect_into(&self, system: &System, info: &mut SystemInformation) -> anyhow::Result<()> {
+        (self.store)(info, (self.collect)(system)?);
+
+        Ok(())
+    }
+}
+
+fn section<T: 'static>(
+    name: &'static str,
+    collect: fn(&System) -> anyhow::Result<T>,
+    store: fn(&mut SystemInformation, T),
+) -> Arc<dyn BuiltinCollector> {
+    Arc::new(Section {
+        name,
+        collect,
+        store,
+    })
+}
+
+pub(crate) fn os() -> Arc<dyn BuiltinCollector> {
+    section(
+        "os_info",
+        |_| Ok(os_info::get()),
+        |info, x| info.os_info = Some(x),
+    )
+}
+
+/// The cpu load and the load average.
+pub(crate) fn cpu() -> Vec<Arc<dyn BuiltinCollector>> {
+    vec![
+        section("cpu", CpuInformation::collect, |info, x| info.cpu = Some(x)),
+        section(
+            "load_avg",
+            |system| Ok(LoadAverage::from(system.load_average()?)),
+            |info, x| info.load_avg = Some(x),
+        ),
+    ]
+}
+
+pub(crate) fn memory() -> Arc<dyn BuiltinCollector> {
+    section("memory", Memory::collect, |info, x| info.memory = Some(x))
+}
+
+pub(crate) fn swap() -> Arc<dyn BuiltinCollector> {
+    section(
+        "swap",
+        |system| Ok(Swap::from(system.swap()?)),
+        |info, x| info.swap = Some(x),
+    )
+}
+
+/// Batteries are optional, a missing battery isn't an error.
+pub(crate) fn battery_life() -> Arc<dyn BuiltinCollector> {
+    section(
+        "battery_life",
+        |system| Ok(system.battery_life().ok().map(BatteryLife::from)),
+        |info, x| info.battery_life = x,
+    )
+}
+
+pub(crate) fn filesystem() -> Arc<dyn BuiltinCollector> {
+    section(
+        "mounts",
+        |system| {
+            Ok(system
+                .mounts()?
+                .into_iter()
+                .map(Filesystem::from)
+                .collect::<Vec<Filesystem>>())
+        },
+        |info, x| info.mounts = x,
+    )
+}
+
+/// The interfaces with their addresses and statistics and the socket statistics.
+pub(crate) fn network() -> Vec<Arc<dyn BuiltinCollector>> {
+    vec![
+        section(
+            "networks",
+            |system| {
+                Ok(system
+                    .networks()?
+                    .into_iter()
+                    .map(|(name, network)| (name, Network::from(network)))
+                    .collect::<HashMap<String, Network>>())
+            },
+            |info, x| info.networks = x,
+        ),
+        section(
+            "net_stats",
+            |system| {
+                let mut net_stats = HashMap::new();
+
+                for (name, _) in system.networks()? {
+                    let stats = NetworkStatistics::from(system.network_stats(&name)?);
+                    net_stats.insert(name, stats);
+                }
+
+                Ok(net_stats)
+            },
+            |info, x| info.net_stats = x,
+        ),
+        section(
+            "socket_stats",
+            |system| Ok(SocketStatistics::from(system.socket_stats()?)),
+            |info, x| info.socket_stats = Some(x),
+        ),
+    ]
+}
+
+pub(crate) fn processes() -> Arc<dyn BuiltinCollector> {
+    section(
+        "processes",
+        |_| Processes::collect(),
+        |info, x| info.processes = Some(x),
+    )
+}
+
+pub(crate) fn disk_io() -> Arc<dyn BuiltinCollector> {
+    section("disk_io", |_| DiskIo::collect(), |info, x| info.disk_io = x)
+}
+
+pub(crate) fn thermals() -> Arc<dyn BuiltinCollector> {
+    section(
+        "thermals",
+        |_| Ok(Thermals::collect()),
+        |info, x| info.thermals = Some(x),
+    )
+}
@@ -1,12 +1,76 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
 pub mod models;
 pub mod options;
 pub mod client;
 pub mod api;
+pub mod checks;
+pub mod collector;
+pub mod prometheus;
+mod procfs;
+mod sysfs;
 
-#[derive(Serialize, Deserialize)]
+/// An error of a device, reported to the service.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ErrorLog {
     pub profile_id: u32,
+    pub severity: Severity,
+    /// The part of the agent the error occurred in, the name of the collector for collection failures.
+    pub source: String,
     pub message: String,
+    /// Time in UTC the error occurred.
+    pub timestamp: NaiveDateTime,
+    /// Additional details, like the version of the agent.
+    #[serde(default)]
+    pub context: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 4] = [
+        Severity::Info,
+        Severity::Warning,
+        Severity::Error,
+        Severity::Critical,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            "critical" => Ok(Severity::Critical),
+            _ => Err(format!("Unknown severity '{s}'.")),
+        }
+    }
 }
@@ -1,17 +1,24 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     ffi::OsString,
+    fmt,
     net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
     thread::sleep,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use chrono::{NaiveDate, NaiveDateTime};
+use anyhow::Context;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use log::warn;
 use os_info::Info;
 use serde::{Deserialize, Serialize};
 use systemstat::{CPULoad, Platform, System};
 
-use crate::options::CollectorOptions;
+use crate::{collector::CollectorFailed, options::CollectorOptions, procfs, sysfs};
+
+/// Number of processes kept in each ranking of [`Processes`].
+const PROCESS_COUNT: usize = 10;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SystemInformation {
@@ -26,13 +33,30 @@ pub struct SystemInformation {
     pub networks: HashMap<String, Network>,
     pub net_stats: HashMap<String, NetworkStatistics>,
     pub socket_stats: Option<SocketStatistics>,
+    pub processes: Option<Processes>,
+    #[serde(default)]
+    pub disk_io: Vec<DiskIo>,
+    pub thermals: Option<Thermals>,
+    #[serde(default)]
+    pub checks: Vec<Check>,
+    /// Sections of the collectors registered by the agent, by collector name.
+    #[serde(default)]
+    pub extra: BTreeMap<String, serde_json::Value>,
     pub uptime: Duration,
     pub boot_time: NaiveDateTime,
+    /// Time in UTC the snapshot was collected on the device.
+    /// Missing for agents that don't send it, the service uses its receive time instead.
+    #[serde(default)]
+    pub collected_at: Option<NaiveDateTime>,
+    /// Increases with every snapshot of a device, used by the service to drop retried uploads.
+    #[serde(default)]
+    pub sequence: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CpuInformation {
-    pub temperature: f32,
+    /// Missing if the platform has no cpu temperature sensor, e.g. in most VMs.
+    pub temperature: Option<f32>,
     pub loads: Vec<CpuLoad>,
     pub aggregate_load: CpuLoad,
 }
@@ -57,6 +81,28 @@ pub struct LoadAverage {
 pub struct Memory {
     pub total: u64,
     pub free: u64,
+    /// Memory available for new allocations without swapping, including reclaimable caches.
+    /// Missing from older agents, the service uses `free` instead.
+    #[serde(default)]
+    pub available: u64,
+    #[serde(default)]
+    pub buffers: u64,
+    /// Page cache, without the buffers.
+    #[serde(default)]
+    pub cached: u64,
+    #[serde(default)]
+    pub shared: u64,
+    #[serde(default)]
+    pub slab: u64,
+    #[serde(default)]
+    pub dirty: u64,
+    #[serde(default)]
+    pub writeback: u64,
+    /// Size of the hugepage pool.
+    #[serde(default)]
+    pub hugepages_total: u64,
+    #[serde(default)]
+    pub hugepages_free: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -124,45 +170,118 @@ pub struct SocketStatistics {
     pub udp6_sockets_in_use: usize,
 }
 
-impl SystemInformation {
-    pub fn collect(
-        options: &CollectorOptions,
-        system: System,
-        hostname: OsString,
-        os_info: Option<Info>,
-    ) -> anyhow::Result<Self> {
-        let mut mounts = Vec::new();
+/// The processes with the highest cpu load and memory usage, read from /proc on Linux.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Processes {
+    pub by_cpu: Vec<Process>,
+    pub by_memory: Vec<Process>,
+}
 
-        if options.filesystem() {
-            for fs in system.mounts()? {
-                mounts.push(Filesystem::from(fs));
-            }
-        }
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Process {
+    pub pid: u32,
+    pub name: String,
+    pub cmdline: String,
+    pub user: String,
+    pub state: String,
+    pub threads: u64,
+    /// Missing if the process belongs to another user and the agent isn't running as root.
+    pub open_fds: Option<u64>,
+    /// Share of the total cpu time of all cores, between 0 and 1.
+    pub cpu_usage: f32,
+    /// Resident set size in bytes.
+    pub rss: u64,
+}
 
-        let mut networks = HashMap::new();
+/// I/O of a block device, as rates over the sampling window.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DiskIo {
+    pub name: String,
+    /// Bytes read per second.
+    pub read_bytes: f64,
+    /// Bytes written per second.
+    pub write_bytes: f64,
+    /// Completed reads per second.
+    pub read_ops: f64,
+    /// Completed writes per second.
+    pub write_ops: f64,
+    /// Requests in progress at the end of the window.
+    pub in_flight: u64,
+    /// Share of the window the device was busy, between 0 and 1.
+    pub io_time: f64,
+}
 
-        if options.network() {
-            for (key, value) in system.networks()? {
-                networks.insert(key, Network::from(value));
-            }
-        }
+/// All temperature sensors and fans of the hwmon chips and thermal zones, Linux only.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Thermals {
+    pub temperatures: Vec<TemperatureSensor>,
+    pub fans: Vec<Fan>,
+}
 
-        let mut net_stats = HashMap::new();
+/// A temperature sensor in degrees Celsius, values the sensor doesn't provide are missing.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TemperatureSensor {
+    pub chip: String,
+    pub label: String,
+    pub current: Option<f32>,
+    pub high: Option<f32>,
+    pub critical: Option<f32>,
+}
 
-        if options.network() {
-            for (network, _) in system.networks()? {
-                net_stats.insert(
-                    network.clone(),
-                    NetworkStatistics::from(system.network_stats(&network)?),
-                );
-            }
-        }
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Fan {
+    pub chip: String,
+    pub label: String,
+    pub rpm: Option<u32>,
+}
 
-        let socket_stats = if options.network() {
-            Some(SocketStatistics::from(system.socket_stats()?))
-        } else {
-            None
-        };
+/// Result of a Nagios style check command.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    /// First line of the output without the performance data.
+    pub output: String,
+    pub perfdata: Vec<PerfData>,
+    /// Time in UTC the command was started.
+    pub executed_at: NaiveDateTime,
+    /// Run time of the command in seconds.
+    pub duration: f64,
+}
+
+/// Status of a check by the exit code of the command, 0 to 3. Any other exit code is unknown.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Critical,
+    Unknown,
+}
+
+/// A value of the performance data of a check, `label=value[unit];[warn];[crit];[min];[max]`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PerfData {
+    pub label: String,
+    pub value: f64,
+    pub unit: String,
+    /// Warning range as written by the plugin, e.g. `10`, `10:20` or `@~:5`.
+    pub warn: Option<String>,
+    /// Critical range as written by the plugin.
+    pub crit: Option<String>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl SystemInformation {
+    /// Collects the sections enabled in the options, the registered collectors are stored in `extra`.
+    /// A failing built-in collector fails the snapshot, a failing registered one is skipped.
+    pub fn collect(
+        options: &CollectorOptions,
+        system: System,
+        hostname: OsString,
+    ) -> anyhow::Result<Self> {
+        let collected_at = Utc::now().naive_utc();
 
         let boot_time = {
             let boot_time = system.boot_time()?;
@@ -180,53 +299,53 @@ impl SystemInformation {
             .unwrap()
         };
 
-        let battery_life = match system.battery_life() {
-            Ok(b) => Some(BatteryLife::from(b)),
-            Err(_) => None,
-        };
-
-        let (cpu, load_avg) = if options.cpu() {
-            (
-                Some(CpuInformation::collect(&system)?),
-                Some(LoadAverage::from(system.load_average()?)),
-            )
-        } else {
-            (None, None)
+        let mut info = Self {
+            hostname,
+            os_info: None,
+            cpu: None,
+            load_avg: None,
+            memory: None,
+            swap: None,
+            battery_life: None,
+            mounts: Vec::new(),
+            networks: HashMap::new(),
+            net_stats: HashMap::new(),
+            socket_stats: None,
+            processes: None,
+            disk_io: Vec::new(),
+            thermals: None,
+            checks: Vec::new(),
+            extra: BTreeMap::new(),
+            uptime: system.uptime()?,
+            boot_time,
+            collected_at: Some(collected_at),
+            sequence: None,
         };
 
-        let swap = if options.swap() {
-            Some(Swap::from(system.swap()?))
-        } else {
-            None
-        };
+        for collector in options.builtin() {
+            collector
+                .collect_into(&system, &mut info)
+                .with_context(|| CollectorFailed {
+                    collector: collector.name().to_string(),
+                })?;
+        }
 
-        let memory = if options.memory() {
-            Some(Memory::from(system.memory()?))
-        } else {
-            None
-        };
+        for collector in options.registered() {
+            match collector.collect(&system) {
+                Ok(value) => {
+                    info.extra.insert(collector.name().to_string(), value);
+                }
+                Err(why) => warn!("Failed to collect '{}', error: {:?}", collector.name(), why),
+            }
+        }
 
-        Ok(Self {
-            hostname,
-            os_info,
-            cpu,
-            load_avg,
-            memory,
-            swap,
-            battery_life: battery_life,
-            mounts,
-            networks,
-            net_stats,
-            socket_stats: socket_stats,
-            uptime: system.uptime()?,
-            boot_time: boot_time,
-        })
+        Ok(info)
     }
 }
 
 impl CpuInformation {
     pub fn collect(system: &System) -> anyhow::Result<Self> {
-        let temp = system.cpu_temp()?;
+        let temp = system.cpu_temp().ok();
         let (cpu_loads, load_aggregate) = {
             let load = system.cpu_load()?;
             let aggregate = system.cpu_load_aggregate()?;
@@ -244,12 +363,172 @@ impl CpuInformation {
 
         Ok(Self {
             temperature: temp,
-            loads: loads,
+            loads,
             aggregate_load: CpuLoad::from(load_aggregate),
         })
     }
 }
 
+impl Processes {
+    /// Samples the cpu time of all processes over one second.
+    pub fn collect() -> anyhow::Result<Self> {
+        let total_before = procfs::total_cpu_time()?;
+        let before: HashMap<u32, u64> = procfs::process_stats()?
+            .into_iter()
+            .map(|x| (x.pid, x.cpu_time))
+            .collect();
+
+        sleep(Duration::from_secs(1));
+
+        let total = procfs::total_cpu_time()?
+            .saturating_sub(total_before)
+            .max(1);
+        let users = procfs::user_names();
+        let mut processes = Vec::new();
+
+        for stat in procfs::process_stats()? {
+            let status = match procfs::process_status(stat.pid) {
+                Ok(status) => status,
+                Err(_) => continue,
+            };
+            let cpu_time = stat
+                .cpu_time
+                .saturating_sub(before.get(&stat.pid).copied().unwrap_or(stat.cpu_time));
+
+            processes.push(Process {
+                pid: stat.pid,
+                name: stat.name,
+                cmdline: String::new(),
+                user: users
+                    .get(&status.uid)
+                    .cloned()
+                    .unwrap_or_else(|| status.uid.to_string()),
+                state: stat.state,
+                threads: status.threads,
+                open_fds: None,
+                cpu_usage: cpu_time as f32 / total as f32,
+                rss: status.rss,
+            });
+        }
+
+        processes.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage));
+        let by_cpu = top_processes(&processes);
+
+        processes.sort_by_key(|x| std::cmp::Reverse(x.rss));
+        let by_memory = top_processes(&processes);
+
+        Ok(Self { by_cpu, by_memory })
+    }
+}
+
+impl CheckStatus {
+    pub fn from_exit_code(code: Option<i32>) -> Self {
+        match code {
+            Some(0) => CheckStatus::Ok,
+            Some(1) => CheckStatus::Warning,
+            Some(2) => CheckStatus::Critical,
+            _ => CheckStatus::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "ok",
+            CheckStatus::Warning => "warning",
+            CheckStatus::Critical => "critical",
+            CheckStatus::Unknown => "unknown",
+        }
+    }
+
+    /// The exit code of the status.
+    pub fn code(&self) -> u8 {
+        match self {
+            CheckStatus::Ok => 0,
+            CheckStatus::Warning => 1,
+            CheckStatus::Critical => 2,
+            CheckStatus::Unknown => 3,
+        }
+    }
+}
+
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CheckStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ok" => Ok(CheckStatus::Ok),
+            "warning" => Ok(CheckStatus::Warning),
+            "critical" => Ok(CheckStatus::Critical),
+            "unknown" => Ok(CheckStatus::Unknown),
+            _ => Err(format!("Unknown check status '{s}'.")),
+        }
+    }
+}
+
+impl Thermals {
+    pub fn collect() -> Self {
+        let (mut temperatures, fans) = sysfs::hwmon_sensors();
+        temperatures.extend(sysfs::thermal_zones());
+
+        Self { temperatures, fans }
+    }
+}
+
+impl DiskIo {
+    /// Samples the counters of all disks over one second.
+    pub fn collect() -> anyhow::Result<Vec<Self>> {
+        let before: HashMap<String, procfs::DiskStat> = procfs::disk_stats()?
+            .into_iter()
+            .map(|x| (x.name.clone(), x))
+            .collect();
+        let started = Instant::now();
+
+        sleep(Duration::from_secs(1));
+
+        let secs = started.elapsed().as_secs_f64();
+        let mut disks = Vec::new();
+
+        for after in procfs::disk_stats()? {
+            let before = match before.get(&after.name) {
+                Some(before) => before,
+                None => continue,
+            };
+            let rate = |before: u64, after: u64| after.saturating_sub(before) as f64 / secs;
+
+            disks.push(Self {
+                read_bytes: rate(before.read_bytes, after.read_bytes),
+                write_bytes: rate(before.write_bytes, after.write_bytes),
+                read_ops: rate(before.reads, after.reads),
+                write_ops: rate(before.writes, after.writes),
+                in_flight: after.in_flight,
+                io_time: (rate(before.io_time, after.io_time) / 1000.0).min(1.0),
+                name: after.name,
+            });
+        }
+
+        Ok(disks)
+    }
+}
+
+/// Takes the first processes and reads the details that are too expensive for all of them.
+fn top_processes(sorted: &[Process]) -> Vec<Process> {
+    sorted
+        .iter()
+        .take(PROCESS_COUNT)
+        .map(|x| Process {
+            cmdline: procfs::process_cmdline(x.pid).unwrap_or_default(),
+            open_fds: procfs::process_open_fds(x.pid).ok(),
+            ..x.clone()
+        })
+        .collect()
+}
+
 impl From<CPULoad> for CpuLoad {
     fn from(value: CPULoad) -> Self {
         Self {
@@ -272,12 +551,30 @@ impl From<systemstat::LoadAverage> for LoadAverage {
     }
 }
 
-impl From<systemstat::Memory> for Memory {
-    fn from(value: systemstat::Memory) -> Self {
-        Self {
-            total: value.total.0,
-            free: value.free.0,
-        }
+impl Memory {
+    /// Total and free memory of the platform, with the breakdown of /proc/meminfo where available.
+    pub fn collect(system: &System) -> anyhow::Result<Self> {
+        let memory = system.memory()?;
+        let meminfo = procfs::meminfo().unwrap_or_default();
+        let field = |key: &str| meminfo.get(key).copied().unwrap_or_default();
+        let hugepage_size = field("Hugepagesize");
+
+        Ok(Self {
+            total: memory.total.0,
+            free: memory.free.0,
+            available: meminfo
+                .get("MemAvailable")
+                .copied()
+                .unwrap_or(memory.free.0),
+            buffers: field("Buffers"),
+            cached: field("Cached"),
+            shared: field("Shmem"),
+            slab: field("Slab"),
+            dirty: field("Dirty"),
+            writeback: field("Writeback"),
+            hugepages_total: field("HugePages_Total") * hugepage_size,
+            hugepages_free: field("HugePages_Free") * hugepage_size,
+        })
     }
 }
 
@@ -1,6 +1,13 @@
+use std::sync::Arc;
+
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+use crate::{
+    checks::{CheckConfig, CheckRunner},
+    collector::{self, BuiltinCollector, Collector},
+};
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct CollectorOptions {
     cpu: bool,
     memory: bool,
@@ -8,9 +15,17 @@ pub struct CollectorOptions {
     network: bool,
     filesystem: bool,
     swap: bool,
+    processes: bool,
+    disk_io: bool,
+    thermals: bool,
+    #[serde(skip)]
+    checks: Option<Arc<CheckRunner>>,
+    #[serde(skip)]
+    registered: Vec<Arc<dyn Collector>>,
 }
 
 impl CollectorOptions {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         cpu: bool,
         memory: bool,
@@ -18,6 +33,9 @@ impl CollectorOptions {
         network: bool,
         filesystem: bool,
         swap: bool,
+        processes: bool,
+        disk_io: bool,
+        thermals: bool,
     ) -> Self {
         Self {
             cpu,
@@ -26,7 +44,79 @@ impl CollectorOptions {
             network,
             filesystem,
             swap,
+            processes,
+            disk_io,
+            thermals,
+            checks: None,
+            registered: Vec::new(),
+        }
+    }
+
+    /// Runs the check commands on collection, their results are kept between runs.
+    pub fn set_checks(&mut self, checks: Vec<CheckConfig>) {
+        self.checks = if checks.is_empty() {
+            None
+        } else {
+            Some(Arc::new(CheckRunner::new(checks)))
+        };
+    }
+
+    /// Adds a custom section, collected after the built-in ones.
+    pub fn register(&mut self, collector: impl Collector + 'static) {
+        self.registered.push(Arc::new(collector));
+    }
+
+    /// The built-in collectors enabled by the options, in collection order.
+    pub(crate) fn builtin(&self) -> Vec<Arc<dyn BuiltinCollector>> {
+        let mut collectors = Vec::new();
+
+        if self.os {
+            collectors.push(collector::os());
+        }
+
+        if self.filesystem {
+            collectors.push(collector::filesystem());
+        }
+
+        if self.network {
+            collectors.extend(collector::network());
+        }
+
+        collectors.push(collector::battery_life());
+
+        if self.cpu {
+            collectors.extend(collector::cpu());
+        }
+
+        if self.swap {
+            collectors.push(collector::swap());
         }
+
+        if self.memory {
+            collectors.push(collector::memory());
+        }
+
+        if self.processes {
+            collectors.push(collector::processes());
+        }
+
+        if self.disk_io {
+            collectors.push(collector::disk_io());
+        }
+
+        if self.thermals {
+            collectors.push(collector::thermals());
+        }
+
+        if let Some(checks) = &self.checks {
+            collectors.push(checks.clone());
+        }
+
+        collectors
+    }
+
+    pub fn registered(&self) -> &[Arc<dyn Collector>] {
+        &self.registered
     }
 
     pub fn cpu(&self) -> bool {
@@ -52,4 +142,16 @@ impl CollectorOptions {
     pub fn network(&self) -> bool {
         self.network
     }
+
+    pub fn processes(&self) -> bool {
+        self.processes
+    }
+
+    pub fn disk_io(&self) -> bool {
+        self.disk_io
+    }
+
+    pub fn thermals(&self) -> bool {
+        self.thermals
+    }
 }
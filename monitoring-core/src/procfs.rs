@@ -0,0 +1,408 @@
+//! Readers for the Linux /proc files that systemstat doesn't cover.
+
+use std::{collections::HashMap, fs, io, path::Path};
+
+/// Size of a sector in /proc/diskstats, independent of the actual sector size of the device.
+const SECTOR_SIZE: u64 = 512;
+
+/// Fields of `/proc/<pid>/stat` used by the process collector.
+pub(crate) struct ProcessStat {
+    pub pid: u32,
+    pub name: String,
+    pub state: String,
+    /// User and system time in clock ticks.
+    pub cpu_time: u64,
+}
+
+/// Fields of `/proc/<pid>/status` used by the process collector.
+pub(crate) struct ProcessStatus {
+    pub uid: u32,
+    pub threads: u64,
+    /// Resident set size in bytes, 0 for kernel threads.
+    pub rss: u64,
+}
+
+/// Counters of a block device from `/proc/diskstats`.
+pub(crate) struct DiskStat {
+    pub name: String,
+    pub reads: u64,
+    pub read_bytes: u64,
+    pub writes: u64,
+    pub write_bytes: u64,
+    pub in_flight: u64,
+    /// Time spent doing io in milliseconds.
+    pub io_time: u64,
+}
+
+/// Sum of all cpu times in `/proc/stat`, in clock ticks.
+pub(crate) fn total_cpu_time() -> io::Result<u64> {
+    let contents = fs::read_to_string("/proc/stat")?;
+    let line = contents
+        .lines()
+        .find(|x| x.starts_with("cpu "))
+        .ok_or_else(|| invalid_data("Missing cpu line in /proc/stat"))?;
+
+    Ok(line
+        .split_whitespace()
+        .skip(1)
+        .filter_map(|x| x.parse::<u64>().ok())
+        .sum())
+}
+
+/// Stats of all running processes.
+/// Processes that exit while reading are skipped.
+pub(crate) fn process_stats() -> io::Result<Vec<ProcessStat>> {
+    let mut stats = Vec::new();
+
+    for entry in fs::read_dir("/proc")? {
+        let pid = match entry?
+            .file_name()
+            .to_str()
+            .and_then(|x| x.parse::<u32>().ok())
+        {
+            Some(pid) => pid,
+            None => continue,
+        };
+
+        if let Ok(stat) = process_stat(pid) {
+            stats.push(stat);
+        }
+    }
+
+    Ok(stats)
+}
+
+fn process_stat(pid: u32) -> io::Result<ProcessStat> {
+    parse_process_stat(pid, &fs::read_to_string(format!("/proc/{pid}/stat"))?)
+}
+
+fn parse_process_stat(pid: u32, contents: &str) -> io::Result<ProcessStat> {
+    // The name is in parentheses and may contain spaces or parentheses itself.
+    let (start, end) = match (contents.find('('), contents.rfind(')')) {
+        (Some(start), Some(end)) if start < end => (start, end),
+        _ => return Err(invalid_data("Invalid /proc/<pid>/stat")),
+    };
+    let fields: Vec<&str> = contents[end + 1..].split_whitespace().collect();
+
+    // Field 3 of the man page is the first one after the name.
+    let field = |number: usize| -> io::Result<u64> {
+        fields
+            .get(number - 3)
+            .and_then(|x| x.parse().ok())
+            .ok_or_else(|| invalid_data("Invalid /proc/<pid>/stat"))
+    };
+
+    Ok(ProcessStat {
+        pid,
+        name: String::from(&contents[start + 1..end]),
+        state: fields.first().map(|x| x.to_string()).unwrap_or_default(),
+        cpu_time: field(14)? + field(15)?,
+    })
+}
+
+pub(crate) fn process_status(pid: u32) -> io::Result<ProcessStatus> {
+    Ok(parse_process_status(&fs::read_to_string(format!(
+        "/proc/{pid}/status"
+    ))?))
+}
+
+fn parse_process_status(contents: &str) -> ProcessStatus {
+    let mut status = ProcessStatus {
+        uid: 0,
+        threads: 0,
+        rss: 0,
+    };
+
+    for line in contents.lines() {
+        let (key, value) = match line.split_once(':') {
+            Some(x) => x,
+            None => continue,
+        };
+        let value = value.split_whitespace().next().unwrap_or_default();
+
+        match key {
+            "Uid" => status.uid = value.parse().unwrap_or_default(),
+            "Threads" => status.threads = value.parse().unwrap_or_default(),
+            "VmRSS" => status.rss = value.parse::<u64>().unwrap_or_default() * 1024,
+            _ => {}
+        }
+    }
+
+    status
+}
+
+/// Command line with the arguments separated by spaces, empty for kernel threads.
+pub(crate) fn process_cmdline(pid: u32) -> io::Result<String> {
+    let contents = fs::read(format!("/proc/{pid}/cmdline"))?;
+
+    Ok(String::from_utf8_lossy(&contents)
+        .split('\0')
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" "))
+}
+
+/// Number of open file descriptors, only readable for own processes unless running as root.
+pub(crate) fn process_open_fds(pid: u32) -> io::Result<u64> {
+    Ok(fs::read_dir(format!("/proc/{pid}/fd"))?.count() as u64)
+}
+
+/// User names by uid from `/etc/passwd`.
+pub(crate) fn user_names() -> HashMap<u32, String> {
+    let contents = fs::read_to_string("/etc/passwd").unwrap_or_default();
+
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+
+            Some((uid, String::from(name)))
+        })
+        .collect()
+}
+
+/// Fields of `/proc/meminfo` in bytes, hugepage counts are kept as they are.
+pub(crate) fn meminfo() -> io::Result<HashMap<String, u64>> {
+    Ok(parse_meminfo(&fs::read_to_string("/proc/meminfo")?))
+}
+
+fn parse_meminfo(contents: &str) -> HashMap<String, u64> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let mut fields = value.split_whitespace();
+            let value: u64 = fields.next()?.parse().ok()?;
+            let value = match fields.next() {
+                Some("kB") => value * 1024,
+                _ => value,
+            };
+
+            Some((String::from(key), value))
+        })
+        .collect()
+}
+
+/// Counters of all whole disks, partitions and loop or ram devices are skipped.
+pub(crate) fn disk_stats() -> io::Result<Vec<DiskStat>> {
+    let contents = fs::read_to_string("/proc/diskstats")?;
+
+    // Partitions have no entry of their own in /sys/block.
+    parse_disk_stats(&contents, |name| {
+        Path::new("/sys/block").join(name).exists()
+    })
+}
+
+/// `is_disk` tells whole disks apart from partitions.
+fn parse_disk_stats(contents: &str, is_disk: impl Fn(&str) -> bool) -> io::Result<Vec<DiskStat>> {
+    let mut stats = Vec::new();
+
+    for line in contents.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        let name = match fields.get(2) {
+            Some(name) => *name,
+            None => continue,
+        };
+
+        if name.starts_with("loop") || name.starts_with("ram") || !is_disk(name) {
+            continue;
+        }
+
+        // Field 1 of the kernel documentation is the first one after the name.
+        let field = |number: usize| -> io::Result<u64> {
+            fields
+                .get(number + 2)
+                .and_then(|x| x.parse().ok())
+                .ok_or_else(|| invalid_data("Invalid /proc/diskstats"))
+        };
+
+        stats.push(DiskStat {
+            name: String::from(name),
+            reads: field(1)?,
+            read_bytes: field(3)? * SECTOR_SIZE,
+            writes: field(5)?,
+            write_bytes: field(7)? * SECTOR_SIZE,
+            in_flight: field(9)?,
+            io_time: field(10)?,
+        });
+    }
+
+    Ok(stats)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_disk_stats, parse_meminfo, parse_process_stat, parse_process_status};
+
+    const MEMINFO: &str = "MemTotal:        8048836 kB
+MemFree:          512304 kB
+MemAvailable:    4194304 kB
+Buffers:          102400 kB
+Cached:          3145728 kB
+Shmem:             65536 kB
+Slab:             262144 kB
+Dirty:               128 kB
+Writeback:             0 kB
+HugePages_Total:       4
+HugePages_Free:        2
+Hugepagesize:       2048 kB
+";
+
+    const DISKSTATS: &str = "   7       0 loop0 61 0 2290 12 0 0 0 0 0 24 12 0 0 0 0 0 0
+   1       0 ram0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+   8       0 sda 8000 120 1000000 4200 2000 300 400000 9000 3 7000 13200 0 0 0 0 0 0
+   8       1 sda1 7000 100 900000 4000 1900 300 390000 8800 0 6800 12800 0 0 0 0 0 0
+ 259       0 nvme0n1 500 0 64000 100 250 0 32000 50 0 120 150
+ 259       1 nvme0n1p1 400 0 60000 90 200 0 30000 40 0 100 130
+ 253       0 dm-0 300 0 20000 80 100 0 8000 20 0 60 100 0 0 0 0 0 0
+";
+
+    const PROCESS_STAT: &str = "4242 (Web Content) S 1 4242 4242 0 -1 4194560 90000 0 12 0 1500 300 0 0 20 0 27 0 5000 2900000000 60000 18446744073709551615 0 0 0 0 0 0 0 4096 17663 0 0 0 17 3 0 0 0 0 0\n";
+
+    const PROCESS_STATUS: &str = "Name:\tpostgres
+Umask:\t0077
+State:\tS (sleeping)
+Tgid:\t812
+Pid:\t812
+PPid:\t1
+Uid:\t114\t114\t114\t114
+Gid:\t121\t121\t121\t121
+VmPeak:\t  219420 kB
+VmSize:\t  219372 kB
+VmRSS:\t   28944 kB
+RssAnon:\t    4100 kB
+Threads:\t1
+";
+
+    const KERNEL_THREAD_STATUS: &str = "Name:\tkworker/0:1-events
+Umask:\t0000
+State:\tI (idle)
+Tgid:\t37
+Pid:\t37
+PPid:\t2
+Uid:\t0\t0\t0\t0
+Gid:\t0\t0\t0\t0
+Threads:\t1
+";
+
+    fn is_disk(name: &str) -> bool {
+        ["loop0", "ram0", "sda", "nvme0n1", "dm-0"].contains(&name)
+    }
+
+    #[test]
+    fn meminfo_in_bytes() {
+        let meminfo = parse_meminfo(MEMINFO);
+
+        assert_eq!(meminfo["MemTotal"], 8048836 * 1024);
+        assert_eq!(meminfo["MemFree"], 512304 * 1024);
+        assert_eq!(meminfo["MemAvailable"], 4194304 * 1024);
+        assert_eq!(meminfo["Writeback"], 0);
+        assert_eq!(meminfo["Hugepagesize"], 2048 * 1024);
+    }
+
+    #[test]
+    fn meminfo_keeps_counts() {
+        let meminfo = parse_meminfo(MEMINFO);
+
+        assert_eq!(meminfo["HugePages_Total"], 4);
+        assert_eq!(meminfo["HugePages_Free"], 2);
+    }
+
+    #[test]
+    fn meminfo_skips_invalid_lines() {
+        let meminfo = parse_meminfo("MemTotal: 1024 kB\nbroken line\nEmpty:\nText: abc kB\n");
+
+        assert_eq!(meminfo.len(), 1);
+        assert_eq!(meminfo["MemTotal"], 1024 * 1024);
+    }
+
+    #[test]
+    fn diskstats_of_whole_disks() {
+        let stats = parse_disk_stats(DISKSTATS, is_disk).unwrap();
+        let names: Vec<&str> = stats.iter().map(|x| x.name.as_str()).collect();
+
+        assert_eq!(names, ["sda", "nvme0n1", "dm-0"]);
+    }
+
+    #[test]
+    fn diskstats_counters() {
+        let stats = parse_disk_stats(DISKSTATS, is_disk).unwrap();
+        let sda = &stats[0];
+
+        assert_eq!(sda.reads, 8000);
+        assert_eq!(sda.read_bytes, 1000000 * 512);
+        assert_eq!(sda.writes, 2000);
+        assert_eq!(sda.write_bytes, 400000 * 512);
+        assert_eq!(sda.in_flight, 3);
+        assert_eq!(sda.io_time, 7000);
+
+        // Older kernels have no discard and flush fields.
+        let nvme = &stats[1];
+
+        assert_eq!(nvme.read_bytes, 64000 * 512);
+        assert_eq!(nvme.io_time, 120);
+    }
+
+    #[test]
+    fn diskstats_skip_empty_lines() {
+        assert!(parse_disk_stats("\n  \n", is_disk).unwrap().is_empty());
+    }
+
+    #[test]
+    fn diskstats_fail_on_truncated_disks() {
+        assert!(parse_disk_stats("8 0 sda 8000 120 1000000", is_disk).is_err());
+        assert!(parse_disk_stats("8 1 sda1 8000 120 1000000", is_disk).is_ok());
+    }
+
+    #[test]
+    fn process_stat_fields() {
+        let stat = parse_process_stat(4242, PROCESS_STAT).unwrap();
+
+        assert_eq!(stat.pid, 4242);
+        assert_eq!(stat.name, "Web Content");
+        assert_eq!(stat.state, "S");
+        assert_eq!(stat.cpu_time, 1500 + 300);
+    }
+
+    #[test]
+    fn process_stat_name_with_parentheses() {
+        let contents = PROCESS_STAT.replace("(Web Content)", "(a) b (c))");
+        let stat = parse_process_stat(4242, &contents).unwrap();
+
+        assert_eq!(stat.name, "a) b (c)");
+        assert_eq!(stat.state, "S");
+        assert_eq!(stat.cpu_time, 1800);
+    }
+
+    #[test]
+    fn process_stat_fails_on_invalid_contents() {
+        assert!(parse_process_stat(1, "").is_err());
+        assert!(parse_process_stat(1, "1 )init( S 0").is_err());
+        assert!(parse_process_stat(1, "1 (init) S 0 1 1").is_err());
+    }
+
+    #[test]
+    fn process_status_fields() {
+        let status = parse_process_status(PROCESS_STATUS);
+
+        assert_eq!(status.uid, 114);
+        assert_eq!(status.threads, 1);
+        assert_eq!(status.rss, 28944 * 1024);
+    }
+
+    #[test]
+    fn process_status_of_kernel_threads() {
+        let status = parse_process_status(KERNEL_THREAD_STATUS);
+
+        assert_eq!(status.uid, 0);
+        assert_eq!(status.threads, 1);
+        assert_eq!(status.rss, 0);
+    }
+}
@@ -0,0 +1,470 @@
+use std::fmt::Write as _;
+
+use chrono::NaiveDateTime;
+
+use crate::models::{CpuLoad, DiskIo, Filesystem, Memory, NetworkStatistics, SystemInformation};
+
+/// Name, help and value of a metric family with one sample per instance of `T`.
+type Family<T, V = u64> = (&'static str, &'static str, fn(&T) -> V);
+
+/// A snapshot of a device with the labels added to all of its samples.
+pub struct Device {
+    pub labels: Vec<(&'static str, String)>,
+    /// Collection time, exported as `monitoring_last_snapshot_timestamp_seconds`.
+    pub timestamp: NaiveDateTime,
+    pub info: SystemInformation,
+}
+
+/// Writes the system information of all devices, grouped by metric family.
+pub fn write_devices(exp: &mut Exposition, devices: &[Device]) {
+    exp.family(
+        "monitoring_last_snapshot_timestamp_seconds",
+        "Unix time of the latest snapshot.",
+        "gauge",
+    );
+    for device in devices {
+        exp.sample(&device.labels, &[], device.timestamp.timestamp() as f64);
+    }
+
+    exp.family(
+        "monitoring_uptime_seconds",
+        "Uptime of the device.",
+        "gauge",
+    );
+    for device in devices {
+        exp.sample(&device.labels, &[], device.info.uptime.as_secs() as f64);
+    }
+
+    exp.family(
+        "monitoring_cpu_load_ratio",
+        "Aggregated cpu load per mode.",
+        "gauge",
+    );
+    for device in devices {
+        if let Some(cpu) = device.info.cpu.as_ref() {
+            for (mode, value) in cpu_modes(&cpu.aggregate_load) {
+                exp.sample(&device.labels, &[("mode", mode)], value);
+            }
+        }
+    }
+
+    exp.family(
+        "monitoring_cpu_core_load_ratio",
+        "Cpu load per core and mode.",
+        "gauge",
+    );
+    for device in devices {
+        if let Some(cpu) = device.info.cpu.as_ref() {
+            for (core, load) in cpu.loads.iter().enumerate() {
+                let core = core.to_string();
+
+                for (mode, value) in cpu_modes(load) {
+                    exp.sample(&device.labels, &[("core", &core), ("mode", mode)], value);
+                }
+            }
+        }
+    }
+
+    exp.family(
+        "monitoring_cpu_temperature_celsius",
+        "Cpu temperature.",
+        "gauge",
+    );
+    for device in devices {
+        if let Some(temperature) = device.info.cpu.as_ref().and_then(|x| x.temperature) {
+            exp.sample(&device.labels, &[], temperature as f64);
+        }
+    }
+
+    exp.family(
+        "monitoring_temperature_celsius",
+        "Temperature per sensor.",
+        "gauge",
+    );
+    for device in devices {
+        if let Some(thermals) = device.info.thermals.as_ref() {
+            for sensor in &thermals.temperatures {
+                if let Some(current) = sensor.current {
+                    exp.sample(
+                        &device.labels,
+                        &[("chip", &sensor.chip), ("sensor", &sensor.label)],
+                        current as f64,
+                    );
+                }
+            }
+        }
+    }
+
+    exp.family("monitoring_fan_rpm", "Fan speed per fan.", "gauge");
+    for device in devices {
+        if let Some(thermals) = device.info.thermals.as_ref() {
+            for fan in &thermals.fans {
+                if let Some(rpm) = fan.rpm {
+                    exp.sample(
+                        &device.labels,
+                        &[("chip", &fan.chip), ("fan", &fan.label)],
+                        rpm as f64,
+                    );
+                }
+            }
+        }
+    }
+
+    exp.family("monitoring_load_average", "System load average.", "gauge");
+    for device in devices {
+        if let Some(l_avg) = device.info.load_avg.as_ref() {
+            exp.sample(&device.labels, &[("period", "1m")], l_avg.one as f64);
+            exp.sample(&device.labels, &[("period", "5m")], l_avg.five as f64);
+            exp.sample(&device.labels, &[("period", "15m")], l_avg.fifteen as f64);
+        }
+    }
+
+    exp.family("monitoring_memory_total_bytes", "Total memory.", "gauge");
+    for device in devices {
+        if let Some(memory) = device.info.memory.as_ref() {
+            exp.sample(&device.labels, &[], memory.total as f64);
+        }
+    }
+
+    exp.family("monitoring_memory_free_bytes", "Free memory.", "gauge");
+    for device in devices {
+        if let Some(memory) = device.info.memory.as_ref() {
+            exp.sample(&device.labels, &[], memory.free as f64);
+        }
+    }
+
+    let memory_families: [Family<Memory>; 9] = [
+        (
+            "monitoring_memory_available_bytes",
+            "Memory available without swapping.",
+            |x| x.available,
+        ),
+        (
+            "monitoring_memory_buffers_bytes",
+            "Memory used by buffers.",
+            |x| x.buffers,
+        ),
+        (
+            "monitoring_memory_cached_bytes",
+            "Memory used by the page cache.",
+            |x| x.cached,
+        ),
+        ("monitoring_memory_shared_bytes", "Shared memory.", |x| {
+            x.shared
+        }),
+        (
+            "monitoring_memory_slab_bytes",
+            "Memory used by the kernel slab allocator.",
+            |x| x.slab,
+        ),
+        (
+            "monitoring_memory_dirty_bytes",
+            "Memory waiting to be written back.",
+            |x| x.dirty,
+        ),
+        (
+            "monitoring_memory_writeback_bytes",
+            "Memory being written back.",
+            |x| x.writeback,
+        ),
+        (
+            "monitoring_memory_hugepages_total_bytes",
+            "Size of the hugepage pool.",
+            |x| x.hugepages_total,
+        ),
+        (
+            "monitoring_memory_hugepages_free_bytes",
+            "Free hugepages.",
+            |x| x.hugepages_free,
+        ),
+    ];
+
+    for (name, help, value) in memory_families {
+        exp.family(name, help, "gauge");
+        for device in devices {
+            if let Some(memory) = device.info.memory.as_ref() {
+                exp.sample(&device.labels, &[], value(memory) as f64);
+            }
+        }
+    }
+
+    exp.family("monitoring_swap_total_bytes", "Total swap.", "gauge");
+    for device in devices {
+        if let Some(swap) = device.info.swap.as_ref() {
+            exp.sample(&device.labels, &[], swap.total as f64);
+        }
+    }
+
+    exp.family("monitoring_swap_free_bytes", "Free swap.", "gauge");
+    for device in devices {
+        if let Some(swap) = device.info.swap.as_ref() {
+            exp.sample(&device.labels, &[], swap.free as f64);
+        }
+    }
+
+    exp.family(
+        "monitoring_battery_remaining_ratio",
+        "Remaining battery capacity.",
+        "gauge",
+    );
+    for device in devices {
+        if let Some(battery) = device.info.battery_life.as_ref() {
+            exp.sample(&device.labels, &[], battery.remaining_capacity as f64);
+        }
+    }
+
+    let filesystem_families: [Family<Filesystem>; 3] = [
+        (
+            "monitoring_filesystem_size_bytes",
+            "Size of the filesystem.",
+            |x| x.total,
+        ),
+        (
+            "monitoring_filesystem_free_bytes",
+            "Free space of the filesystem.",
+            |x| x.free,
+        ),
+        (
+            "monitoring_filesystem_avail_bytes",
+            "Space of the filesystem available to unprivileged users.",
+            |x| x.avail,
+        ),
+    ];
+
+    for (name, help, value) in filesystem_families {
+        exp.family(name, help, "gauge");
+
+        for device in devices {
+            for mount in &device.info.mounts {
+                exp.sample(
+                    &device.labels,
+                    &[
+                        ("mountpoint", &mount.fs_mounted_on),
+                        ("device", &mount.fs_mounted_from),
+                        ("fstype", &mount.fs_type),
+                    ],
+                    value(mount) as f64,
+                );
+            }
+        }
+    }
+
+    let network_families: [Family<NetworkStatistics>; 6] = [
+        ("monitoring_network_receive_bytes", "Received bytes.", |x| {
+            x.rx_bytes
+        }),
+        (
+            "monitoring_network_transmit_bytes",
+            "Transmitted bytes.",
+            |x| x.tx_bytes,
+        ),
+        (
+            "monitoring_network_receive_packets",
+            "Received packets.",
+            |x| x.rx_packets,
+        ),
+        (
+            "monitoring_network_transmit_packets",
+            "Transmitted packets.",
+            |x| x.tx_packets,
+        ),
+        (
+            "monitoring_network_receive_errors",
+            "Receive errors.",
+            |x| x.rx_errors,
+        ),
+        (
+            "monitoring_network_transmit_errors",
+            "Transmit errors.",
+            |x| x.tx_errors,
+        ),
+    ];
+
+    for (name, help, value) in network_families {
+        exp.family(name, help, "gauge");
+
+        for device in devices {
+            let mut interfaces: Vec<_> = device.info.net_stats.iter().collect();
+            interfaces.sort_by(|a, b| a.0.cmp(b.0));
+
+            for (interface, stats) in interfaces {
+                exp.sample(
+                    &device.labels,
+                    &[("interface", interface)],
+                    value(stats) as f64,
+                );
+            }
+        }
+    }
+
+    let disk_families: [Family<DiskIo, f64>; 6] = [
+        (
+            "monitoring_disk_read_bytes_per_second",
+            "Bytes read per second.",
+            |x| x.read_bytes,
+        ),
+        (
+            "monitoring_disk_write_bytes_per_second",
+            "Bytes written per second.",
+            |x| x.write_bytes,
+        ),
+        (
+            "monitoring_disk_reads_per_second",
+            "Completed reads per second.",
+            |x| x.read_ops,
+        ),
+        (
+            "monitoring_disk_writes_per_second",
+            "Completed writes per second.",
+            |x| x.write_ops,
+        ),
+        (
+            "monitoring_disk_io_in_flight",
+            "Io requests in progress.",
+            |x| x.in_flight as f64,
+        ),
+        (
+            "monitoring_disk_io_time_ratio",
+            "Share of time the disk was busy.",
+            |x| x.io_time,
+        ),
+    ];
+
+    for (name, help, value) in disk_families {
+        exp.family(name, help, "gauge");
+
+        for device in devices {
+            for disk in &device.info.disk_io {
+                exp.sample(&device.labels, &[("disk", &disk.name)], value(disk));
+            }
+        }
+    }
+
+    exp.family(
+        "monitoring_sockets_in_use",
+        "Sockets in use per protocol.",
+        "gauge",
+    );
+    for device in devices {
+        if let Some(sockets) = device.info.socket_stats.as_ref() {
+            for (protocol, value) in [
+                ("tcp", sockets.tcp_sockets_in_use),
+                ("udp", sockets.udp_sockets_in_use),
+                ("tcp6", sockets.tcp6_sockets_in_use),
+                ("udp6", sockets.udp6_sockets_in_use),
+            ] {
+                exp.sample(&device.labels, &[("protocol", protocol)], value as f64);
+            }
+        }
+    }
+
+    exp.family(
+        "monitoring_tcp_sockets_orphaned",
+        "Orphaned tcp sockets.",
+        "gauge",
+    );
+    for device in devices {
+        if let Some(sockets) = device.info.socket_stats.as_ref() {
+            exp.sample(&device.labels, &[], sockets.tcp_sockets_orphaned as f64);
+        }
+    }
+
+    exp.family(
+        "monitoring_check_status",
+        "Status of a check, 0 ok, 1 warning, 2 critical and 3 unknown.",
+        "gauge",
+    );
+    for device in devices {
+        for check in &device.info.checks {
+            exp.sample(
+                &device.labels,
+                &[("check", &check.name)],
+                check.status.code() as f64,
+            );
+        }
+    }
+
+    exp.family(
+        "monitoring_check_perfdata",
+        "Performance data of a check in the unit of the plugin.",
+        "gauge",
+    );
+    for device in devices {
+        for check in &device.info.checks {
+            for perfdata in &check.perfdata {
+                exp.sample(
+                    &device.labels,
+                    &[
+                        ("check", &check.name),
+                        ("label", &perfdata.label),
+                        ("unit", &perfdata.unit),
+                    ],
+                    perfdata.value,
+                );
+            }
+        }
+    }
+}
+
+fn cpu_modes(load: &CpuLoad) -> [(&'static str, f64); 5] {
+    [
+        ("user", load.user as f64),
+        ("nice", load.nice as f64),
+        ("system", load.system as f64),
+        ("interrupt", load.interrupt as f64),
+        ("idle", load.idle as f64),
+    ]
+}
+
+/// Writes metrics in the Prometheus text format.
+/// All samples of a metric family have to follow its `family` call.
+#[derive(Default)]
+pub struct Exposition {
+    out: String,
+    name: String,
+}
+
+impl Exposition {
+    pub fn family(&mut self, name: &str, help: &str, kind: &str) {
+        let _ = writeln!(self.out, "# HELP {name} {help}");
+        let _ = writeln!(self.out, "# TYPE {name} {kind}");
+        self.name = String::from(name);
+    }
+
+    pub fn sample(&mut self, labels: &[(&str, String)], extra: &[(&str, &str)], value: f64) {
+        let name = self.name.clone();
+        self.named_sample(&name, labels, extra, value);
+    }
+
+    pub fn named_sample(
+        &mut self,
+        name: &str,
+        labels: &[(&str, String)],
+        extra: &[(&str, &str)],
+        value: f64,
+    ) {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(key, value)| (*key, value.as_str()))
+            .chain(extra.iter().copied())
+            .map(|(key, value)| format!("{key}=\"{}\"", escape(value)))
+            .collect();
+
+        if labels.is_empty() {
+            let _ = writeln!(self.out, "{name} {value}");
+        } else {
+            let _ = writeln!(self.out, "{name}{{{}}} {value}", labels.join(","));
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        self.out
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
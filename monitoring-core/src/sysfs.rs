@@ -0,0 +1,304 @@
+//! Readers for the Linux /sys files of the temperature and fan sensors.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::models::{Fan, TemperatureSensor};
+
+/// Temperature and fan sensors of all hwmon chips.
+/// Sensors that can't be read are reported without a value instead of being skipped.
+pub(crate) fn hwmon_sensors() -> (Vec<TemperatureSensor>, Vec<Fan>) {
+    read_hwmon(Path::new("/sys/class/hwmon"))
+}
+
+fn read_hwmon(class_dir: &Path) -> (Vec<TemperatureSensor>, Vec<Fan>) {
+    let mut temperatures = Vec::new();
+    let mut fans = Vec::new();
+
+    for dir in sorted_dirs(class_dir, "hwmon") {
+        // Older drivers keep the attributes in the device directory.
+        let dir = if dir.join("name").exists() {
+            dir
+        } else {
+            dir.join("device")
+        };
+        let chip = match read_string(&dir.join("name")) {
+            Some(chip) => chip,
+            None => continue,
+        };
+
+        for index in sensor_indices(&dir, "temp") {
+            temperatures.push(TemperatureSensor {
+                chip: chip.clone(),
+                label: read_string(&dir.join(format!("temp{index}_label")))
+                    .unwrap_or_else(|| format!("temp{index}")),
+                current: read_millis(&dir.join(format!("temp{index}_input"))),
+                high: read_millis(&dir.join(format!("temp{index}_max"))),
+                critical: read_millis(&dir.join(format!("temp{index}_crit"))),
+            });
+        }
+
+        for index in sensor_indices(&dir, "fan") {
+            fans.push(Fan {
+                chip: chip.clone(),
+                label: read_string(&dir.join(format!("fan{index}_label")))
+                    .unwrap_or_else(|| format!("fan{index}")),
+                rpm: read_string(&dir.join(format!("fan{index}_input")))
+                    .and_then(|x| x.parse().ok()),
+            });
+        }
+    }
+
+    (temperatures, fans)
+}
+
+/// Temperatures of the thermal zones, with the `hot` and `critical` trip points as limits.
+pub(crate) fn thermal_zones() -> Vec<TemperatureSensor> {
+    read_thermal_zones(Path::new("/sys/class/thermal"))
+}
+
+fn read_thermal_zones(class_dir: &Path) -> Vec<TemperatureSensor> {
+    let mut sensors = Vec::new();
+
+    for dir in sorted_dirs(class_dir, "thermal_zone") {
+        let label = match read_string(&dir.join("type")) {
+            Some(label) => label,
+            None => continue,
+        };
+        let mut sensor = TemperatureSensor {
+            chip: dir
+                .file_name()
+                .map(|x| x.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            label,
+            current: read_millis(&dir.join("temp")),
+            high: None,
+            critical: None,
+        };
+
+        for index in 0.. {
+            let kind = match read_string(&dir.join(format!("trip_point_{index}_type"))) {
+                Some(kind) => kind,
+                None => break,
+            };
+            let temp = read_millis(&dir.join(format!("trip_point_{index}_temp")));
+
+            match kind.as_str() {
+                "hot" => sensor.high = temp,
+                "critical" => sensor.critical = temp,
+                _ => {}
+            }
+        }
+
+        sensors.push(sensor);
+    }
+
+    sensors
+}
+
+fn sorted_dirs(path: &Path, prefix: &str) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = match fs::read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(|x| x.ok())
+            .filter(|x| x.file_name().to_string_lossy().starts_with(prefix))
+            .map(|x| x.path())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    dirs.sort();
+
+    dirs
+}
+
+/// Indices of the sensors of a kind, e.g. `1` and `2` for `temp1_input` and `temp2_label`.
+fn sensor_indices(dir: &Path, kind: &str) -> BTreeSet<u32> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return BTreeSet::new(),
+    };
+
+    entries
+        .filter_map(|x| x.ok())
+        .filter_map(|x| {
+            let name = x.file_name().to_string_lossy().into_owned();
+            let (sensor, _) = name.strip_prefix(kind)?.split_once('_')?;
+
+            sensor.parse().ok()
+        })
+        .collect()
+}
+
+fn read_string(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|x| x.trim().to_string())
+        .filter(|x| !x.is_empty())
+}
+
+/// Reads a value in thousandths, e.g. millidegrees Celsius.
+fn read_millis(path: &Path) -> Option<f32> {
+    read_string(path)?
+        .parse::<f64>()
+        .ok()
+        .map(|x| (x / 1000.0) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use tempfile::TempDir;
+
+    use super::{read_hwmon, read_thermal_zones};
+
+    fn write(dir: &Path, files: &[(&str, &str)]) {
+        fs::create_dir_all(dir).unwrap();
+
+        for (name, contents) in files {
+            fs::write(dir.join(name), contents).unwrap();
+        }
+    }
+
+    #[test]
+    fn hwmon_in_degrees() {
+        let sysfs = TempDir::new().unwrap();
+        write(
+            &sysfs.path().join("hwmon0"),
+            &[
+                ("name", "coretemp\n"),
+                ("temp1_label", "Package id 0\n"),
+                ("temp1_input", "45500\n"),
+                ("temp1_max", "80000\n"),
+                ("temp1_crit", "100000\n"),
+                ("fan1_label", "CPU Fan\n"),
+                ("fan1_input", "1200\n"),
+            ],
+        );
+
+        let (temperatures, fans) = read_hwmon(sysfs.path());
+
+        assert_eq!(temperatures.len(), 1);
+        assert_eq!(temperatures[0].chip, "coretemp");
+        assert_eq!(temperatures[0].label, "Package id 0");
+        assert_eq!(temperatures[0].current, Some(45.5));
+        assert_eq!(temperatures[0].high, Some(80.0));
+        assert_eq!(temperatures[0].critical, Some(100.0));
+
+        assert_eq!(fans.len(), 1);
+        assert_eq!(fans[0].label, "CPU Fan");
+        assert_eq!(fans[0].rpm, Some(1200));
+    }
+
+    #[test]
+    fn hwmon_without_labels() {
+        let sysfs = TempDir::new().unwrap();
+        write(
+            &sysfs.path().join("hwmon0"),
+            &[
+                ("name", "nvme\n"),
+                ("temp1_input", "38850\n"),
+                ("temp2_input", "41850\n"),
+                ("fan1_input", "800\n"),
+            ],
+        );
+
+        let (temperatures, fans) = read_hwmon(sysfs.path());
+        let labels: Vec<&str> = temperatures.iter().map(|x| x.label.as_str()).collect();
+
+        assert_eq!(labels, ["temp1", "temp2"]);
+        assert_eq!(temperatures[1].current, Some(41.85));
+        assert_eq!(temperatures[1].high, None);
+        assert_eq!(fans[0].label, "fan1");
+    }
+
+    #[test]
+    fn hwmon_attributes_in_device_directory() {
+        let sysfs = TempDir::new().unwrap();
+        write(
+            &sysfs.path().join("hwmon1/device"),
+            &[("name", "it8728\n"), ("temp1_input", "30000\n")],
+        );
+        // Directories that aren't chips are skipped.
+        write(&sysfs.path().join("power"), &[("name", "power\n")]);
+
+        let (temperatures, _) = read_hwmon(sysfs.path());
+
+        assert_eq!(temperatures.len(), 1);
+        assert_eq!(temperatures[0].chip, "it8728");
+        assert_eq!(temperatures[0].current, Some(30.0));
+    }
+
+    #[test]
+    fn hwmon_unreadable_sensors() {
+        let sysfs = TempDir::new().unwrap();
+        let chip = sysfs.path().join("hwmon0");
+        write(
+            &chip,
+            &[
+                ("name", "acpitz\n"),
+                ("temp1_input", "not a number\n"),
+                ("temp2_max", "90000\n"),
+                ("fan1_input", "\n"),
+            ],
+        );
+        // Reading fails like for a sensor whose driver returns an error, even as root.
+        fs::create_dir(chip.join("temp2_input")).unwrap();
+
+        let (temperatures, fans) = read_hwmon(sysfs.path());
+
+        assert_eq!(temperatures.len(), 2);
+        assert_eq!(temperatures[0].current, None);
+        assert_eq!(temperatures[1].current, None);
+        assert_eq!(temperatures[1].high, Some(90.0));
+        assert_eq!(fans.len(), 1);
+        assert_eq!(fans[0].rpm, None);
+    }
+
+    #[test]
+    fn hwmon_without_class_directory() {
+        let sysfs = TempDir::new().unwrap();
+        let (temperatures, fans) = read_hwmon(&sysfs.path().join("hwmon"));
+
+        assert!(temperatures.is_empty());
+        assert!(fans.is_empty());
+    }
+
+    #[test]
+    fn thermal_zones_with_trip_points() {
+        let sysfs = TempDir::new().unwrap();
+        write(
+            &sysfs.path().join("thermal_zone0"),
+            &[
+                ("type", "x86_pkg_temp\n"),
+                ("temp", "52000\n"),
+                ("trip_point_0_type", "passive\n"),
+                ("trip_point_0_temp", "70000\n"),
+                ("trip_point_1_type", "hot\n"),
+                ("trip_point_1_temp", "85000\n"),
+                ("trip_point_2_type", "critical\n"),
+                ("trip_point_2_temp", "105000\n"),
+            ],
+        );
+        write(
+            &sysfs.path().join("thermal_zone1"),
+            &[("type", "acpitz\n"), ("temp", "\n")],
+        );
+        write(&sysfs.path().join("cooling_device0"), &[("type", "Fan\n")]);
+
+        let zones = read_thermal_zones(sysfs.path());
+
+        assert_eq!(zones.len(), 2);
+        assert_eq!(zones[0].chip, "thermal_zone0");
+        assert_eq!(zones[0].label, "x86_pkg_temp");
+        assert_eq!(zones[0].current, Some(52.0));
+        assert_eq!(zones[0].high, Some(85.0));
+        assert_eq!(zones[0].critical, Some(105.0));
+
+        assert_eq!(zones[1].label, "acpitz");
+        assert_eq!(zones[1].current, None);
+        assert_eq!(zones[1].high, None);
+    }
+}
@@ -0,0 +1,196 @@
+use chrono::{NaiveDateTime, Utc};
+use monitoring_core::api::models::{self as api, AlertMetric, InsertAlertRule};
+use rocket::{delete, error, get, http::Status, post, serde::json::Json};
+use rocket_db_pools::Connection;
+use service_lib::{
+    auth::{AlertsManage, Auth, MetricsRead},
+    database::MonitoringDb,
+    models::{alert_rules::AlertRule, alerts::Alert, device_profiles::DeviceProfile},
+};
+
+/// `since` is a unix timestamp in seconds, only alerts which fired or resolved since then are returned.
+#[get("/alerts?<profile_id>&<active>&<since>")]
+pub async fn get_alerts(
+    auth: Auth<MetricsRead>,
+    mut db: Connection<MonitoringDb>,
+    profile_id: Option<u32>,
+    active: Option<bool>,
+    since: Option<i64>,
+) -> Result<Json<Vec<api::Alert>>, Status> {
+    if profile_id.is_some_and(|id| !auth.allows_profile(id as i32)) {
+        return Err(Status::Forbidden);
+    }
+
+    let since = match since {
+        Some(since) => Some(NaiveDateTime::from_timestamp_opt(since, 0).ok_or(Status::BadRequest)?),
+        None => None,
+    };
+
+    let alerts = Alert::get_all_detailed(
+        &mut db,
+        profile_id.map(|id| id as i32),
+        active.unwrap_or(false),
+        since,
+    )
+    .await;
+
+    match alerts {
+        Ok(alerts) => Ok(Json(
+            alerts
+                .into_iter()
+                .filter(|(_, rule, _)| auth.allows_profile(rule.device_profile_id))
+                .filter_map(|(alert, rule, device_name)| {
+                    Some(api::Alert {
+                        id_alert: alert.id_alert,
+                        rule: to_api_rule(rule)?,
+                        device_name,
+                        value: alert.value,
+                        fired_date: alert.fired_date,
+                        resolved_date: alert.resolved_date,
+                        resolved_value: alert.resolved_value,
+                    })
+                })
+                .collect(),
+        )),
+        Err(why) => {
+            error!("Failed to get alerts from db: {why}");
+
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+#[get("/alerts/rules?<profile_id>")]
+pub async fn get_alert_rules(
+    auth: Auth<MetricsRead>,
+    mut db: Connection<MonitoringDb>,
+    profile_id: Option<u32>,
+) -> Result<Json<Vec<api::AlertRule>>, Status> {
+    if profile_id.is_some_and(|id| !auth.allows_profile(id as i32)) {
+        return Err(Status::Forbidden);
+    }
+
+    let rules = match profile_id {
+        Some(profile_id) => AlertRule::get_all_by_profile(&mut db, profile_id as i32).await,
+        None => AlertRule::get_all(&mut db).await,
+    };
+
+    match rules {
+        Ok(rules) => Ok(Json(
+            rules
+                .into_iter()
+                .filter(|rule| auth.allows_profile(rule.device_profile_id))
+                .filter_map(to_api_rule)
+                .collect(),
+        )),
+        Err(why) => {
+            error!("Failed to get alert rules from db: {why}");
+
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+#[post("/alerts/rules", data = "<rule>")]
+pub async fn save_alert_rule(
+    auth: Auth<AlertsManage>,
+    mut db: Connection<MonitoringDb>,
+    rule: Json<InsertAlertRule>,
+) -> Status {
+    if !auth.allows_profile(rule.profile_id) {
+        return Status::Forbidden;
+    }
+
+    let target_valid = match rule.metric {
+        AlertMetric::FilesystemAvail
+        | AlertMetric::CheckStatus
+        | AlertMetric::ProbeSuccess
+        | AlertMetric::CertExpiry => rule.target.is_some(),
+        AlertMetric::Temperature | AlertMetric::FanSpeed => true,
+        _ => rule.target.is_none(),
+    };
+
+    if rule.duration < 0 || !target_valid {
+        return Status::BadRequest;
+    }
+
+    match DeviceProfile::get(&mut db, rule.profile_id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Status::NotFound,
+        Err(why) => {
+            error!("Failed to get profile '{}': {why}", rule.profile_id);
+
+            return Status::InternalServerError;
+        }
+    }
+
+    let alert_rule = AlertRule::new(
+        rule.profile_id,
+        rule.metric.as_str(),
+        rule.target.clone(),
+        rule.comparison.as_str(),
+        rule.threshold,
+        rule.duration,
+        auth.user_id(),
+        Utc::now().naive_utc(),
+    );
+
+    if let Err(why) = alert_rule.insert(&mut db).await {
+        error!("Failed to create new alert rule: {why}");
+
+        return Status::InternalServerError;
+    }
+
+    Status::Ok
+}
+
+#[delete("/alerts/rules/<rule_id>")]
+pub async fn delete_alert_rule(
+    auth: Auth<AlertsManage>,
+    mut db: Connection<MonitoringDb>,
+    rule_id: u32,
+) -> Status {
+    match AlertRule::get(&mut db, rule_id as i32).await {
+        Ok(Some(rule)) if auth.allows_profile(rule.device_profile_id) => {}
+        Ok(Some(_)) => return Status::Forbidden,
+        Ok(None) => return Status::NotFound,
+        Err(why) => {
+            error!("Failed to get alert rule '{rule_id}': {why}");
+
+            return Status::InternalServerError;
+        }
+    }
+
+    match AlertRule::delete(&mut db, rule_id as i32).await {
+        Ok(true) => Status::Ok,
+        Ok(false) => Status::NotFound,
+        Err(why) => {
+            error!("Failed to delete alert rule '{rule_id}': {why}");
+
+            Status::InternalServerError
+        }
+    }
+}
+
+fn to_api_rule(rule: AlertRule) -> Option<api::AlertRule> {
+    let (metric, comparison) = match (rule.metric.parse(), rule.comparison.parse()) {
+        (Ok(metric), Ok(comparison)) => (metric, comparison),
+        _ => {
+            error!("Alert rule '{}' is invalid.", rule.id_alert_rule);
+
+            return None;
+        }
+    };
+
+    Some(api::AlertRule {
+        id_alert_rule: rule.id_alert_rule,
+        profile_id: rule.device_profile_id,
+        metric,
+        target: rule.target,
+        comparison,
+        threshold: rule.threshold,
+        duration: rule.duration,
+        create_user: rule.create_user,
+        create_date: rule.create_date,
+    })
+}
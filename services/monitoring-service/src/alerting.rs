@@ -0,0 +1,454 @@
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use monitoring_core::api::models::{AlertComparison, AlertMetric};
+use monitoring_core::models::CheckStatus;
+use rocket::fairing::AdHoc;
+use rocket::{error, info, warn};
+use rocket_db_pools::Database;
+use service_lib::database::MonitoringDb;
+use service_lib::models::alert_rules::AlertRule;
+use service_lib::models::alerts::Alert;
+use service_lib::models::battery_lifes::BatteryLife;
+use service_lib::models::check_results::CheckResult;
+use service_lib::models::cpu_informations::CpuInformation;
+use service_lib::models::cpu_loads::CpuLoad;
+use service_lib::models::fan_infos::FanInfo;
+use service_lib::models::filesystem_infos::FilesystemInfo;
+use service_lib::models::load_averages::LoadAverage;
+use service_lib::models::memory_infos::MemoryInfo;
+use service_lib::models::probe_results::ProbeResult;
+use service_lib::models::swap_infos::SwapInfo;
+use service_lib::models::system_informations::SystemInformation;
+use service_lib::models::temperature_infos::TemperatureInfo;
+use sqlx::pool::PoolConnection;
+use sqlx::{PgPool, Postgres};
+
+/// Evaluates the alert rules of all profiles every `interval` seconds.
+/// This catches rules like `no-snapshot` which can't fire on ingest.
+pub fn stage(interval: u64) -> AdHoc {
+    AdHoc::on_liftoff("Alert evaluation", move |rocket| {
+        Box::pin(async move {
+            let pool = match MonitoringDb::fetch(rocket) {
+                Some(db) => PgPool::clone(db),
+                None => {
+                    error!("Failed to get database for alert evaluation!");
+                    return;
+                }
+            };
+
+            rocket::tokio::spawn(async move {
+                let mut timer = rocket::tokio::time::interval(StdDuration::from_secs(interval));
+
+                loop {
+                    timer.tick().await;
+
+                    match pool.acquire().await {
+                        Ok(mut db) => {
+                            if let Err(why) = evaluate_all(&mut db).await {
+                                error!("Failed to evaluate alert rules: {why}");
+                            }
+                        }
+                        Err(why) => error!("Failed to get database connection: {why}"),
+                    }
+                }
+            });
+        })
+    })
+}
+
+pub async fn evaluate_all(db: &mut PoolConnection<Postgres>) -> Result<(), sqlx::Error> {
+    let now = Utc::now().naive_utc();
+
+    for rule in AlertRule::get_all(db).await? {
+        evaluate_rule(db, rule, now).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn evaluate_profile(
+    db: &mut PoolConnection<Postgres>,
+    profile_id: i32,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now().naive_utc();
+
+    for rule in AlertRule::get_all_by_profile(db, profile_id).await? {
+        evaluate_rule(db, rule, now).await?;
+    }
+
+    Ok(())
+}
+
+async fn evaluate_rule(
+    db: &mut PoolConnection<Postgres>,
+    rule: AlertRule,
+    now: NaiveDateTime,
+) -> Result<(), sqlx::Error> {
+    let (metric, comparison) = match (
+        rule.metric.parse::<AlertMetric>(),
+        rule.comparison.parse::<AlertComparison>(),
+    ) {
+        (Ok(metric), Ok(comparison)) => (metric, comparison),
+        _ => {
+            warn!("Skipping invalid alert rule '{}'.", rule.id_alert_rule);
+            return Ok(());
+        }
+    };
+
+    let value = match current_value(db, &rule, metric, comparison, now).await? {
+        Some(value) => value,
+        None => return Ok(()),
+    };
+
+    let violated = comparison.is_violated(value, rule.threshold);
+
+    match (
+        violated,
+        Alert::get_active_by_rule(db, rule.id_alert_rule).await?,
+    ) {
+        (true, None) => {
+            let alert = Alert::new(rule.id_alert_rule, value, now)
+                .insert(db)
+                .await?;
+
+            if alert.is_some() {
+                info!(
+                    "Alert rule '{}' fired for profile '{}' with value {value}.",
+                    rule.id_alert_rule, rule.device_profile_id
+                );
+            }
+        }
+        (false, Some(alert)) => {
+            alert.resolve(db, now, value).await?;
+            info!(
+                "Alert rule '{}' resolved for profile '{}' with value {value}.",
+                rule.id_alert_rule, rule.device_profile_id
+            );
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Returns the value of the metric which is closest to the threshold within the rule duration.
+/// The rule is violated for the whole duration exactly if this value violates the threshold.
+async fn current_value(
+    db: &mut PoolConnection<Postgres>,
+    rule: &AlertRule,
+    metric: AlertMetric,
+    comparison: AlertComparison,
+    now: NaiveDateTime,
+) -> Result<Option<f64>, sqlx::Error> {
+    let profile_id = rule.device_profile_id;
+
+    if let AlertMetric::ProbeSuccess | AlertMetric::CertExpiry = metric {
+        return probe_value(db, rule, metric, comparison, now).await;
+    }
+
+    // Windows follow the receive time, the clock of the device may be off.
+    let latest = match SystemInformation::get_latest_received(db, profile_id, now).await? {
+        Some(latest) => latest,
+        None => return Ok(None),
+    };
+
+    if metric == AlertMetric::NoSnapshot {
+        return Ok(Some((now - latest.receive_date).num_seconds() as f64));
+    }
+
+    let from = match window_start(rule.duration, latest.receive_date, now) {
+        Some(from) => from,
+        None => return Ok(None),
+    };
+    // The snapshot at or before the window start holds the values at its start,
+    // without one the profile hasn't reported for the whole duration yet.
+    let start = match SystemInformation::get_latest_received(db, profile_id, from).await? {
+        Some(start) => start.receive_date,
+        None => return Ok(None),
+    };
+
+    // The sections are stored by collection time, their snapshots are identified by it.
+    let snapshots: HashSet<NaiveDateTime> =
+        SystemInformation::get_received_range(db, profile_id, start, now)
+            .await?
+            .into_iter()
+            .map(|x| x.create_date)
+            .collect();
+    let (first, last) = match (snapshots.iter().min(), snapshots.iter().max()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return Ok(None),
+    };
+
+    let values: Vec<f64> = match metric {
+        AlertMetric::CpuIdle => of_snapshots(
+            CpuLoad::get_aggregate_range(db, profile_id, first, last).await?,
+            &snapshots,
+        )
+        .map(|(_, load)| load.idle as f64)
+        .collect(),
+        AlertMetric::CpuTemperature => of_snapshots(
+            CpuInformation::get_range(db, profile_id, first, last).await?,
+            &snapshots,
+        )
+        .filter_map(|(_, cpu)| cpu.temperature.map(|x| x as f64))
+        .collect(),
+        AlertMetric::Temperature => {
+            let sensors = of_snapshots(
+                TemperatureInfo::get_range(db, profile_id, first, last).await?,
+                &snapshots,
+            )
+            .filter(|(_, sensor)| {
+                rule.target.is_none() || Some(&sensor.sensor_name()) == rule.target.as_ref()
+            })
+            .filter_map(|(date, sensor)| Some((date, sensor.current? as f64)));
+
+            per_snapshot(sensors, f64::max)
+        }
+        AlertMetric::FanSpeed => {
+            let fans = of_snapshots(
+                FanInfo::get_range(db, profile_id, first, last).await?,
+                &snapshots,
+            )
+            .filter(|(_, fan)| {
+                rule.target.is_none() || Some(&fan.fan_name()) == rule.target.as_ref()
+            })
+            .filter_map(|(date, fan)| Some((date, fan.rpm? as f64)));
+
+            per_snapshot(fans, f64::min)
+        }
+        AlertMetric::LoadAverage => of_snapshots(
+            LoadAverage::get_range(db, profile_id, first, last).await?,
+            &snapshots,
+        )
+        .map(|(_, l_avg)| l_avg.one as f64)
+        .collect(),
+        AlertMetric::MemoryFree => of_snapshots(
+            MemoryInfo::get_range(db, profile_id, first, last).await?,
+            &snapshots,
+        )
+        .filter_map(|(_, mem)| ratio(mem.free, mem.total))
+        .collect(),
+        AlertMetric::MemoryAvailable => of_snapshots(
+            MemoryInfo::get_range(db, profile_id, first, last).await?,
+            &snapshots,
+        )
+        .filter_map(|(_, mem)| ratio(mem.available, mem.total))
+        .collect(),
+        AlertMetric::SwapFree => of_snapshots(
+            SwapInfo::get_range(db, profile_id, first, last).await?,
+            &snapshots,
+        )
+        .filter_map(|(_, swap)| ratio(swap.free, swap.total))
+        .collect(),
+        AlertMetric::FilesystemAvail => of_snapshots(
+            FilesystemInfo::get_range(db, profile_id, first, last).await?,
+            &snapshots,
+        )
+        .filter(|(_, mount)| Some(&mount.fs_mounted_on) == rule.target.as_ref())
+        .filter_map(|(_, mount)| ratio(mount.avail, mount.total))
+        .collect(),
+        AlertMetric::BatteryRemaining => of_snapshots(
+            BatteryLife::get_range(db, profile_id, first, last).await?,
+            &snapshots,
+        )
+        .map(|(_, battery)| battery.remaining_capacity as f64)
+        .collect(),
+        AlertMetric::CheckStatus => of_snapshots(
+            CheckResult::get_range(db, profile_id, first, last).await?,
+            &snapshots,
+        )
+        .filter(|(_, check)| Some(&check.name) == rule.target.as_ref())
+        .filter_map(|(_, check)| check.status.parse::<CheckStatus>().ok())
+        .map(|status| status.code() as f64)
+        .collect(),
+        AlertMetric::NoSnapshot | AlertMetric::ProbeSuccess | AlertMetric::CertExpiry => {
+            return Ok(None)
+        }
+    };
+
+    Ok(closest_to_threshold(values, comparison))
+}
+
+/// Like [`current_value`] for the probe in the rule target, probes are sent apart from the snapshots.
+async fn probe_value(
+    db: &mut PoolConnection<Postgres>,
+    rule: &AlertRule,
+    metric: AlertMetric,
+    comparison: AlertComparison,
+    now: NaiveDateTime,
+) -> Result<Option<f64>, sqlx::Error> {
+    let name = match &rule.target {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+    let latest = match ProbeResult::get_latest(db, rule.device_profile_id, name, now).await? {
+        Some(latest) => latest,
+        None => return Ok(None),
+    };
+
+    let from = match window_start(rule.duration, latest.receive_date, now) {
+        Some(from) => from,
+        None => return Ok(None),
+    };
+    let start = match ProbeResult::get_latest(db, rule.device_profile_id, name, from).await? {
+        Some(start) => start.receive_date,
+        None => return Ok(None),
+    };
+    let probes = ProbeResult::get_range(db, rule.device_profile_id, name, start, now).await?;
+
+    let values = match metric {
+        AlertMetric::ProbeSuccess => probes
+            .iter()
+            .map(|probe| if probe.success { 1.0 } else { 0.0 })
+            .collect(),
+        _ => probes
+            .iter()
+            .filter_map(|probe| probe.cert_expiry_days.map(|x| x as f64))
+            .collect(),
+    };
+
+    Ok(closest_to_threshold(values, comparison))
+}
+
+/// Returns the start of the rule duration ending at `now`, `None` if nothing arrived within it.
+/// Rules without a duration only look at the `latest` value.
+fn window_start(duration: i64, latest: NaiveDateTime, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let from = now - Duration::seconds(duration);
+
+    if latest >= from {
+        Some(from)
+    } else if duration == 0 {
+        Some(latest)
+    } else {
+        None
+    }
+}
+
+/// Keeps the values of the `snapshots`, the ranges of the sections follow the device clock.
+fn of_snapshots<'a, T: 'a>(
+    values: Vec<(NaiveDateTime, T)>,
+    snapshots: &'a HashSet<NaiveDateTime>,
+) -> impl Iterator<Item = (NaiveDateTime, T)> + 'a {
+    values
+        .into_iter()
+        .filter(|(date, _)| snapshots.contains(date))
+}
+
+fn closest_to_threshold(values: Vec<f64>, comparison: AlertComparison) -> Option<f64> {
+    match comparison {
+        AlertComparison::Below => values.into_iter().reduce(f64::max),
+        AlertComparison::Above => values.into_iter().reduce(f64::min),
+    }
+}
+
+/// Combines the values of all instances of a snapshot into one value.
+fn per_snapshot(
+    values: impl Iterator<Item = (NaiveDateTime, f64)>,
+    combine: fn(f64, f64) -> f64,
+) -> Vec<f64> {
+    let mut snapshots: BTreeMap<NaiveDateTime, f64> = BTreeMap::new();
+
+    for (date, value) in values {
+        snapshots
+            .entry(date)
+            .and_modify(|x| *x = combine(*x, value))
+            .or_insert(value);
+    }
+
+    snapshots.into_values().collect()
+}
+
+fn ratio(part: i64, total: i64) -> Option<f64> {
+    if total <= 0 {
+        return None;
+    }
+
+    Some(part as f64 / total as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate, NaiveDateTime};
+    use monitoring_core::api::models::AlertComparison;
+
+    use super::{closest_to_threshold, per_snapshot, ratio, window_start};
+
+    fn date(seconds: i64) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 6, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            + Duration::seconds(seconds)
+    }
+
+    #[test]
+    fn is_violated_excludes_the_threshold() {
+        assert!(AlertComparison::Below.is_violated(9.9, 10.0));
+        assert!(!AlertComparison::Below.is_violated(10.0, 10.0));
+        assert!(AlertComparison::Above.is_violated(10.1, 10.0));
+        assert!(!AlertComparison::Above.is_violated(10.0, 10.0));
+    }
+
+    #[test]
+    fn closest_to_threshold_fires_only_if_every_value_violates() {
+        let below = closest_to_threshold(vec![5.0, 8.0, 3.0], AlertComparison::Below);
+        assert_eq!(below, Some(8.0));
+        assert!(AlertComparison::Below.is_violated(below.unwrap(), 10.0));
+
+        let above = closest_to_threshold(vec![95.0, 92.0, 99.0], AlertComparison::Above);
+        assert_eq!(above, Some(92.0));
+        assert!(AlertComparison::Above.is_violated(above.unwrap(), 90.0));
+    }
+
+    #[test]
+    fn closest_to_threshold_resolves_on_one_good_value() {
+        let below = closest_to_threshold(vec![5.0, 12.0, 3.0], AlertComparison::Below);
+        assert!(!AlertComparison::Below.is_violated(below.unwrap(), 10.0));
+
+        let above = closest_to_threshold(vec![95.0, 90.0], AlertComparison::Above);
+        assert!(!AlertComparison::Above.is_violated(above.unwrap(), 90.0));
+    }
+
+    #[test]
+    fn closest_to_threshold_without_values() {
+        assert_eq!(closest_to_threshold(vec![], AlertComparison::Below), None);
+        assert_eq!(closest_to_threshold(vec![], AlertComparison::Above), None);
+    }
+
+    #[test]
+    fn per_snapshot_combines_values_of_the_same_date() {
+        let values = vec![
+            (date(60), 40.0),
+            (date(0), 50.0),
+            (date(60), 70.0),
+            (date(0), 30.0),
+        ];
+
+        assert_eq!(
+            per_snapshot(values.clone().into_iter(), f64::max),
+            vec![50.0, 70.0]
+        );
+        assert_eq!(per_snapshot(values.into_iter(), f64::min), vec![30.0, 40.0]);
+        assert!(per_snapshot(std::iter::empty(), f64::max).is_empty());
+    }
+
+    #[test]
+    fn ratio_needs_a_total() {
+        assert_eq!(ratio(25, 100), Some(0.25));
+        assert_eq!(ratio(0, 100), Some(0.0));
+        assert_eq!(ratio(10, 0), None);
+        assert_eq!(ratio(10, -1), None);
+    }
+
+    #[test]
+    fn window_start_covers_the_duration() {
+        let now = date(600);
+
+        assert_eq!(window_start(300, date(590), now), Some(date(300)));
+        assert_eq!(window_start(300, date(300), now), Some(date(300)));
+        assert_eq!(window_start(300, date(299), now), None);
+        assert_eq!(window_start(0, date(100), now), Some(date(100)));
+    }
+}
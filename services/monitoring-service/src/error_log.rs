@@ -1,25 +1,195 @@
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
+use monitoring_core::{
+    api::models::{ErrorLogEntry, ErrorLogPage},
+    ErrorLog, Severity,
+};
 use rocket::http::Status;
-use rocket::post;
+use rocket::serde::json::{serde_json, Json};
+use rocket::{error, get, post};
 use rocket_db_pools::Connection;
-use service_lib::api_key::ApiKey;
+use service_lib::auth::{Auth, ErrorsManage, MetricsRead};
 use service_lib::database::MonitoringDb;
-use service_lib::models::error_logs::ErrorLog;
+use service_lib::models::device_profiles::DeviceProfile;
+use service_lib::models::error_logs::{self, ErrorLogFilter};
 use service_lib::profile_key::ProfileKey;
 
-#[post("/error/<profile_id>")]
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+
+#[post("/error/<profile_id>", data = "<error_log>")]
 pub async fn save_error(
-    _a_key: ApiKey<'_>,
     _p_key: ProfileKey<'_>,
     mut db: Connection<MonitoringDb>,
     profile_id: u32,
+    error_log: Json<ErrorLog>,
 ) -> Status {
-    let error_log = ErrorLog::new(profile_id, "Test", Utc::now().naive_utc());
+    if error_log.profile_id != profile_id {
+        return Status::BadRequest;
+    }
+
+    let model = error_logs::ErrorLog::new(
+        profile_id,
+        error_log.severity.as_str(),
+        &error_log.source,
+        &error_log.message,
+        serde_json::to_value(&error_log.context).unwrap_or_default(),
+        error_log.timestamp,
+        Utc::now().naive_utc(),
+    );
 
-    if let Err(why) = error_log.insert(&mut *db).await {
-        rocket::error!("Failed to insert error log: {why}");
+    if let Err(why) = model.insert(&mut db).await {
+        error!("Failed to insert error log: {why}");
         return Status::InternalServerError;
     };
 
-    return Status::Ok;
+    Status::Ok
+}
+
+/// Returns a page of the error logs of a profile, newest first.
+/// `severity` is the minimum severity, `from` and `to` are unix timestamps in seconds.
+#[allow(clippy::too_many_arguments)]
+#[get("/profiles/<profile_id>/errors?<page>&<per_page>&<severity>&<from>&<to>&<acknowledged>")]
+pub async fn get_errors(
+    auth: Auth<MetricsRead>,
+    mut db: Connection<MonitoringDb>,
+    profile_id: u32,
+    page: Option<u32>,
+    per_page: Option<u32>,
+    severity: Option<&str>,
+    from: Option<i64>,
+    to: Option<i64>,
+    acknowledged: Option<bool>,
+) -> Result<Json<ErrorLogPage>, Status> {
+    if !auth.allows_profile(profile_id as i32) {
+        return Err(Status::Forbidden);
+    }
+
+    let page = page.unwrap_or(1);
+    let per_page = per_page.unwrap_or(DEFAULT_PAGE_SIZE);
+
+    if page == 0 || per_page == 0 || per_page > MAX_PAGE_SIZE {
+        return Err(Status::BadRequest);
+    }
+
+    let severity = match severity {
+        Some(severity) => severity
+            .parse::<Severity>()
+            .map_err(|_| Status::BadRequest)?,
+        None => Severity::Info,
+    };
+    let filter = ErrorLogFilter {
+        severities: Severity::ALL
+            .iter()
+            .filter(|x| **x >= severity)
+            .map(|x| String::from(x.as_str()))
+            .collect(),
+        from: parse_timestamp(from)?,
+        to: parse_timestamp(to)?,
+        acknowledged,
+    };
+
+    match DeviceProfile::get(&mut db, profile_id as i32).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(Status::NotFound),
+        Err(why) => {
+            error!("Failed to get profile '{profile_id}': {why}");
+
+            return Err(Status::InternalServerError);
+        }
+    }
+
+    let offset = (page - 1) as i64 * per_page as i64;
+    let logs = error_logs::ErrorLog::get_page(
+        &mut db,
+        profile_id as i32,
+        &filter,
+        per_page as i64,
+        offset,
+    )
+    .await;
+    let total = error_logs::ErrorLog::count(&mut db, profile_id as i32, &filter).await;
+
+    match (logs, total) {
+        (Ok(logs), Ok(total)) => Ok(Json(ErrorLogPage {
+            page,
+            per_page,
+            total,
+            errors: logs.into_iter().filter_map(to_api_error).collect(),
+        })),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Failed to get error logs of profile '{profile_id}': {why}");
+
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+/// Acknowledges an error log, conflicts if it was already acknowledged.
+#[post("/errors/<error_id>/ack")]
+pub async fn acknowledge_error(
+    auth: Auth<ErrorsManage>,
+    mut db: Connection<MonitoringDb>,
+    error_id: u32,
+) -> Status {
+    match error_logs::ErrorLog::get(&mut db, error_id as i32).await {
+        Ok(Some(error_log)) if auth.allows_profile(error_log.device_profile_id) => {}
+        Ok(Some(_)) => return Status::Forbidden,
+        Ok(None) => return Status::NotFound,
+        Err(why) => {
+            error!("Failed to get error log '{error_id}': {why}");
+
+            return Status::InternalServerError;
+        }
+    }
+
+    let acknowledged = error_logs::ErrorLog::acknowledge(
+        &mut db,
+        error_id as i32,
+        auth.user_id(),
+        Utc::now().naive_utc(),
+    )
+    .await;
+
+    match acknowledged {
+        Ok(true) => Status::Ok,
+        Ok(false) => Status::Conflict,
+        Err(why) => {
+            error!("Failed to acknowledge error log '{error_id}': {why}");
+
+            Status::InternalServerError
+        }
+    }
+}
+
+fn parse_timestamp(timestamp: Option<i64>) -> Result<Option<NaiveDateTime>, Status> {
+    timestamp
+        .map(|x| NaiveDateTime::from_timestamp_opt(x, 0).ok_or(Status::BadRequest))
+        .transpose()
+}
+
+fn to_api_error(error_log: error_logs::ErrorLog) -> Option<ErrorLogEntry> {
+    let (severity, context) = match (
+        error_log.severity.parse(),
+        serde_json::from_value(error_log.context),
+    ) {
+        (Ok(severity), Ok(context)) => (severity, context),
+        _ => {
+            error!("Error log '{}' is invalid.", error_log.id_error_log);
+
+            return None;
+        }
+    };
+
+    Some(ErrorLogEntry {
+        id_error_log: error_log.id_error_log,
+        profile_id: error_log.device_profile_id,
+        severity,
+        source: error_log.source,
+        message: error_log.message,
+        context,
+        timestamp: error_log.occurred_at,
+        receive_date: error_log.create_date,
+        acknowledged_user: error_log.acknowledged_user,
+        acknowledged_date: error_log.acknowledged_date,
+    })
 }
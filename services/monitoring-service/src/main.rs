@@ -1,16 +1,37 @@
+// The route attributes of rocket 0.5.0-rc.3 emit `pub use` re-exports of their uri macros,
+// which newer compilers report as unused in the modules declaring routes.
+#[allow(unused_imports)]
+mod alert;
+mod alerting;
+#[allow(unused_imports)]
 mod error_log;
+#[allow(unused_imports)]
+mod metrics;
+#[allow(unused_imports)]
 mod service_info;
+#[allow(unused_imports)]
+mod probe;
+#[allow(unused_imports)]
 mod profile;
+#[allow(unused_imports)]
+mod prometheus;
+mod rates;
+#[allow(unused_imports)]
+mod user;
 
 use std::env;
+use std::path::PathBuf;
 
-use profile::{get_profiles, save_profile};
+use alert::{delete_alert_rule, get_alert_rules, get_alerts, save_alert_rule};
+use metrics::get_metrics;
+use probe::save_probes;
+use profile::{get_profile_keys, get_profiles, revoke_profile_key, rotate_profile_key, save_profile};
+use prometheus::{get_prometheus_metrics, IngestStats};
 use rocket::{get, launch, routes};
 use rocket_db_pools::Database;
 use service_info::{get_latest_entry, save_system_info};
-use service_lib::api_key::ApiKeyVault;
+use user::{get_api_tokens, get_users, revoke_api_token, save_api_token, save_user};
 use service_lib::database::MonitoringDb;
-use service_lib::read_key::ReadKeyVault;
 
 #[get("/")]
 fn get_version() -> String {
@@ -19,22 +40,48 @@ fn get_version() -> String {
 
 #[launch]
 fn rocket() -> _ {
-    let api_key = env::var("MONITORING_API_KEY").expect("Expected api key in environment!");
-    let read_key = env::var("MONITORING_READ_KEY").expect("Expected api key in environment!");
+    let admin_token = env::var("MONITORING_ADMIN_TOKEN").ok();
+    let admin_token_file = env::var("MONITORING_ADMIN_TOKEN_FILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("admin-token"));
+    let alert_interval = env::var("MONITORING_ALERT_INTERVAL")
+        .ok()
+        .and_then(|interval| interval.parse().ok())
+        .filter(|interval| *interval > 0)
+        .unwrap_or(60);
 
     rocket::build()
-        .manage(ApiKeyVault::new(&api_key))
-        .manage(ReadKeyVault::new(&read_key))
+        .manage(IngestStats::default())
         .attach(MonitoringDb::init())
+        .attach(profile::stage())
+        .attach(user::stage(admin_token, admin_token_file))
+        .attach(alerting::stage(alert_interval))
         .mount(
             "/",
             routes![
                 get_version,
                 self::error_log::save_error,
+                self::error_log::get_errors,
+                self::error_log::acknowledge_error,
                 save_system_info,
+                save_probes,
                 get_latest_entry,
                 get_profiles,
                 save_profile,
+                rotate_profile_key,
+                get_profile_keys,
+                revoke_profile_key,
+                get_metrics,
+                get_alerts,
+                get_alert_rules,
+                save_alert_rule,
+                delete_alert_rule,
+                get_prometheus_metrics,
+                get_users,
+                save_user,
+                get_api_tokens,
+                save_api_token,
+                revoke_api_token,
             ],
         )
 }
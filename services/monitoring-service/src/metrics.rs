@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use monitoring_core::api::models::{MetricKind, MetricPoint, MetricSeries, Series};
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::{error, get};
+use rocket_db_pools::Connection;
+use service_lib::auth::{Auth, MetricsRead};
+use service_lib::database::MonitoringDb;
+use service_lib::models::cpu_loads::CpuLoad;
+use service_lib::models::disk_io_infos::DiskIoInfo;
+use service_lib::models::filesystem_infos::FilesystemInfo;
+use service_lib::models::load_averages::LoadAverage;
+use service_lib::models::memory_infos::MemoryInfo;
+use service_lib::models::network_rates::NetworkRate;
+use service_lib::models::network_statistics::NetworkStatistic;
+use service_lib::models::swap_infos::SwapInfo;
+use sqlx::pool::PoolConnection;
+use sqlx::Postgres;
+
+/// Default time range in hours if no `from` is given.
+const DEFAULT_RANGE_HOURS: i64 = 24;
+
+/// Returns the time series of a metric for a profile.
+/// `from` and `to` are unix timestamps in seconds, `step` is the bucket size in seconds
+/// used to average the points. Without `step` the raw snapshots are returned.
+#[allow(clippy::too_many_arguments)]
+#[get("/profiles/<profile_id>/metrics/<metric>?<from>&<to>&<step>")]
+pub async fn get_metrics(
+    auth: Auth<MetricsRead>,
+    mut db: Connection<MonitoringDb>,
+    profile_id: u32,
+    metric: &str,
+    from: Option<i64>,
+    to: Option<i64>,
+    step: Option<u32>,
+) -> Result<Json<MetricSeries>, Status> {
+    if !auth.allows_profile(profile_id as i32) {
+        return Err(Status::Forbidden);
+    }
+
+    let metric = metric
+        .parse::<MetricKind>()
+        .map_err(|_| Status::BadRequest)?;
+
+    let to = match to {
+        Some(to) => NaiveDateTime::from_timestamp_opt(to, 0).ok_or(Status::BadRequest)?,
+        None => Utc::now().naive_utc(),
+    };
+    let from = match from {
+        Some(from) => NaiveDateTime::from_timestamp_opt(from, 0).ok_or(Status::BadRequest)?,
+        None => to
+            .checked_sub_signed(Duration::hours(DEFAULT_RANGE_HOURS))
+            .ok_or(Status::BadRequest)?,
+    };
+
+    if from > to || step == Some(0) {
+        return Err(Status::BadRequest);
+    }
+
+    match load_series(&mut db, profile_id as i32, metric, from, to).await {
+        Ok(series) => Ok(Json(MetricSeries {
+            profile_id: profile_id as i32,
+            metric,
+            from,
+            to,
+            step,
+            series: series
+                .into_iter()
+                .map(|series| downsample(series, step))
+                .collect(),
+        })),
+        Err(why) => {
+            error!("Failed to load {metric} metrics for profile '{profile_id}': {why}");
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+async fn load_series(
+    db: &mut PoolConnection<Postgres>,
+    profile_id: i32,
+    metric: MetricKind,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> Result<Vec<Series>, sqlx::Error> {
+    let series = match metric {
+        MetricKind::Cpu => {
+            let points = CpuLoad::get_aggregate_range(db, profile_id, from, to)
+                .await?
+                .into_iter()
+                .map(|(date, load)| {
+                    MetricPoint::new(
+                        date,
+                        &[
+                            ("user", load.user as f64),
+                            ("nice", load.nice as f64),
+                            ("system", load.system as f64),
+                            ("interrupt", load.interrupt as f64),
+                            ("idle", load.idle as f64),
+                        ],
+                    )
+                })
+                .collect();
+
+            vec![single_series("aggregate", points)]
+        }
+        MetricKind::Memory => {
+            let points = MemoryInfo::get_range(db, profile_id, from, to)
+                .await?
+                .into_iter()
+                .map(|(date, mem)| {
+                    MetricPoint::new(
+                        date,
+                        &[
+                            ("free", mem.free as f64),
+                            ("total", mem.total as f64),
+                            ("available", mem.available as f64),
+                            ("buffers", mem.buffers as f64),
+                            ("cached", mem.cached as f64),
+                            ("shared", mem.shared as f64),
+                            ("slab", mem.slab as f64),
+                            ("dirty", mem.dirty as f64),
+                            ("writeback", mem.writeback as f64),
+                            ("hugepages_total", mem.hugepages_total as f64),
+                            ("hugepages_free", mem.hugepages_free as f64),
+                        ],
+                    )
+                })
+                .collect();
+
+            vec![single_series("memory", points)]
+        }
+        MetricKind::Swap => {
+            let points = SwapInfo::get_range(db, profile_id, from, to)
+                .await?
+                .into_iter()
+                .map(|(date, swap)| {
+                    MetricPoint::new(
+                        date,
+                        &[("free", swap.free as f64), ("total", swap.total as f64)],
+                    )
+                })
+                .collect();
+
+            vec![single_series("swap", points)]
+        }
+        MetricKind::LoadAverage => {
+            let points = LoadAverage::get_range(db, profile_id, from, to)
+                .await?
+                .into_iter()
+                .map(|(date, l_avg)| {
+                    MetricPoint::new(
+                        date,
+                        &[
+                            ("one", l_avg.one as f64),
+                            ("five", l_avg.five as f64),
+                            ("fifteen", l_avg.fifteen as f64),
+                        ],
+                    )
+                })
+                .collect();
+
+            vec![single_series("load-average", points)]
+        }
+        MetricKind::Filesystem => {
+            let mut series = BTreeMap::new();
+
+            for (date, mount) in FilesystemInfo::get_range(db, profile_id, from, to).await? {
+                series
+                    .entry(mount.fs_mounted_on.clone())
+                    .or_insert_with(Vec::new)
+                    .push(MetricPoint::new(
+                        date,
+                        &[
+                            ("free", mount.free as f64),
+                            ("avail", mount.avail as f64),
+                            ("total", mount.total as f64),
+                        ],
+                    ));
+            }
+
+            into_series(series)
+        }
+        MetricKind::Network => {
+            let mut series = BTreeMap::new();
+
+            for (date, name, netstat) in
+                NetworkStatistic::get_range(db, profile_id, from, to).await?
+            {
+                series
+                    .entry(name)
+                    .or_insert_with(Vec::new)
+                    .push(MetricPoint::new(
+                        date,
+                        &[
+                            ("rx_bytes", netstat.rx_bytes as f64),
+                            ("tx_bytes", netstat.tx_bytes as f64),
+                        ],
+                    ));
+            }
+
+            into_series(series)
+        }
+        MetricKind::NetworkRate => {
+            let mut series = BTreeMap::new();
+
+            for (date, name, rate) in NetworkRate::get_range(db, profile_id, from, to).await? {
+                series
+                    .entry(name)
+                    .or_insert_with(Vec::new)
+                    .push(MetricPoint::new(
+                        date,
+                        &[
+                            ("rx_bytes", rate.rx_bytes),
+                            ("tx_bytes", rate.tx_bytes),
+                            ("rx_packets", rate.rx_packets),
+                            ("tx_packets", rate.tx_packets),
+                            ("rx_errors", rate.rx_errors),
+                            ("tx_errors", rate.tx_errors),
+                        ],
+                    ));
+            }
+
+            into_series(series)
+        }
+        MetricKind::DiskIo => {
+            let mut series = BTreeMap::new();
+
+            for (date, disk) in DiskIoInfo::get_range(db, profile_id, from, to).await? {
+                series
+                    .entry(disk.name.clone())
+                    .or_insert_with(Vec::new)
+                    .push(MetricPoint::new(
+                        date,
+                        &[
+                            ("read_bytes", disk.read_bytes),
+                            ("write_bytes", disk.write_bytes),
+                            ("read_ops", disk.read_ops),
+                            ("write_ops", disk.write_ops),
+                            ("in_flight", disk.in_flight as f64),
+                            ("io_time", disk.io_time),
+                        ],
+                    ));
+            }
+
+            into_series(series)
+        }
+    };
+
+    Ok(series)
+}
+
+fn single_series(name: &str, points: Vec<MetricPoint>) -> Series {
+    Series {
+        name: String::from(name),
+        points,
+    }
+}
+
+fn into_series(series: BTreeMap<String, Vec<MetricPoint>>) -> Vec<Series> {
+    series
+        .into_iter()
+        .map(|(name, points)| Series { name, points })
+        .collect()
+}
+
+/// Averages the points of a series into buckets of `step` seconds.
+/// Each bucket is stamped with its start time.
+fn downsample(series: Series, step: Option<u32>) -> Series {
+    let step = match step {
+        Some(step) => step as i64,
+        None => return series,
+    };
+
+    let mut buckets: BTreeMap<i64, (usize, BTreeMap<String, f64>)> = BTreeMap::new();
+
+    for point in series.points {
+        let timestamp = point.timestamp.timestamp();
+        let bucket = timestamp - timestamp.rem_euclid(step);
+        let (count, sums) = buckets.entry(bucket).or_default();
+
+        *count += 1;
+
+        for (name, value) in point.values {
+            *sums.entry(name).or_insert(0.0) += value;
+        }
+    }
+
+    let points = buckets
+        .into_iter()
+        .filter_map(|(bucket, (count, sums))| {
+            Some(MetricPoint {
+                timestamp: NaiveDateTime::from_timestamp_opt(bucket, 0)?,
+                values: sums
+                    .into_iter()
+                    .map(|(name, sum)| (name, sum / count as f64))
+                    .collect(),
+            })
+        })
+        .collect();
+
+    Series {
+        name: series.name,
+        points,
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::Utc;
+use monitoring_core::api::models::ProbeResult;
+use rocket::http::Status;
+use rocket::post;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use service_lib::database::MonitoringDb;
+use service_lib::models::probe_results;
+use service_lib::profile_key::ProfileKey;
+
+use crate::alerting;
+
+#[post("/probes/<profile_id>", data = "<probes>")]
+pub async fn save_probes(
+    _p_key: ProfileKey<'_>,
+    mut db: Connection<MonitoringDb>,
+    profile_id: u32,
+    probes: Json<Vec<ProbeResult>>,
+) -> Status {
+    let receive_date = Utc::now().naive_utc();
+    let models = probes
+        .iter()
+        .map(|probe| {
+            probe_results::ProbeResult::new(
+                profile_id as i32,
+                &probe.name,
+                probe.kind.as_str(),
+                &probe.target,
+                probe.success,
+                probe.latency,
+                probe.status_code.map(i32::from),
+                probe.body_match,
+                probe.cert_expiry_days,
+                probe.error.clone(),
+                probe.executed_at,
+                receive_date,
+            )
+        })
+        .collect();
+
+    if let Err(why) = probe_results::ProbeResult::insert_many(&mut db, models).await {
+        rocket::error!("Failed to insert probe results for profile '{profile_id}': {why}");
+        return Status::InternalServerError;
+    }
+
+    if let Err(why) = alerting::evaluate_profile(&mut db, profile_id as i32).await {
+        rocket::error!("Failed to evaluate alert rules for profile '{profile_id}': {why}");
+    }
+
+    Status::Ok
+}
@@ -1,20 +1,41 @@
-use chrono::Utc;
-use monitoring_core::api::models::InsertDeviceProfile;
-use rocket::{error, get, http::Status, post, serde::json::Json};
-use rocket_db_pools::Connection;
+use chrono::{Duration, NaiveDateTime, Utc};
+use monitoring_core::api::models::{
+    self as api, InsertDeviceProfile, NewProfileKey, ProfileKeyInfo, RotateProfileKey,
+};
+use rocket::{
+    delete, error, fairing::AdHoc, get, http::Status, info, post, serde::json::Json, tokio::task,
+};
+use rocket_db_pools::{Connection, Database};
 use service_lib::{
-    api_key::ApiKey, database::MonitoringDb, models::device_profiles::DeviceProfile,
-    read_key::ReadKey,
+    auth::{Auth, ProfilesRead, ProfilesWrite},
+    credentials::{generate_key, hash_key},
+    database::MonitoringDb,
+    models::{device_profiles::DeviceProfile, profile_keys::ProfileKey},
 };
+use sqlx::{Connection as _, PgConnection, PgPool};
+
+/// Seconds the replaced keys stay valid after a rotation if no grace period is given.
+const DEFAULT_GRACE_PERIOD: i64 = 24 * 60 * 60;
 
 #[get("/profiles")]
 pub async fn get_profiles(
-    _a_key: ApiKey<'_>,
-    _r_key: ReadKey<'_>,
+    auth: Auth<ProfilesRead>,
     mut db: Connection<MonitoringDb>,
-) -> Result<Json<Vec<DeviceProfile>>, Status> {
-    match DeviceProfile::get_all(&mut *db).await {
-        Ok(profiles) => Ok(Json(profiles)),
+) -> Result<Json<Vec<api::DeviceProfile>>, Status> {
+    match DeviceProfile::get_all_with_last_seen(&mut db).await {
+        Ok(profiles) => Ok(Json(
+            profiles
+                .into_iter()
+                .filter(|(profile, _)| auth.allows_profile(profile.id_device_profile))
+                .map(|(profile, last_seen)| api::DeviceProfile {
+                    id_device_profile: profile.id_device_profile,
+                    device_name: profile.device_name,
+                    create_user: profile.create_user,
+                    create_date: profile.create_date,
+                    last_seen,
+                })
+                .collect(),
+        )),
         Err(why) => {
             error!("Failed to get devices profiles from db: {why}");
 
@@ -24,19 +45,276 @@ pub async fn get_profiles(
 }
 
 #[post("/profiles", data = "<profile>")]
-pub async fn save_profile(_a_key: ApiKey<'_>, mut db: Connection<MonitoringDb>, profile: Json<InsertDeviceProfile>) -> Status {
-    let device_profile = DeviceProfile::new(
+pub async fn save_profile(
+    auth: Auth<ProfilesWrite>,
+    mut db: Connection<MonitoringDb>,
+    profile: Json<InsertDeviceProfile>,
+) -> Result<Json<NewProfileKey>, Status> {
+    // A restricted token couldn't access the new profile.
+    if auth.is_restricted() {
+        return Err(Status::Forbidden);
+    }
+
+    let (key, key_hash) = generate_hashed_key().await?;
+    let create_date = Utc::now().naive_utc();
+
+    let created = insert_profile(
+        &mut db,
         &profile.device_name,
-        &profile.profile_key,
-        profile.create_user,
+        auth.user_id(),
+        &key_hash,
+        create_date,
+    )
+    .await;
+
+    match created {
+        Ok((id_device_profile, id_profile_key)) => Ok(Json(NewProfileKey {
+            profile_id: id_device_profile,
+            id_profile_key,
+            profile_key: device_key(id_profile_key, &key),
+            previous_expire_date: None,
+        })),
+        Err(why) => {
+            error!("Failed to create new profile: {why}");
+
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+/// Generates a new key for the profile.
+/// The previous keys stay valid for the grace period, so devices can be updated without downtime.
+#[post("/profiles/<profile_id>/rotate-key", data = "<rotation>")]
+pub async fn rotate_profile_key(
+    auth: Auth<ProfilesWrite>,
+    mut db: Connection<MonitoringDb>,
+    profile_id: u32,
+    rotation: Json<RotateProfileKey>,
+) -> Result<Json<NewProfileKey>, Status> {
+    if !auth.allows_profile(profile_id as i32) {
+        return Err(Status::Forbidden);
+    }
+
+    let grace_period = rotation.grace_period.unwrap_or(DEFAULT_GRACE_PERIOD);
+
+    if !(0..=RotateProfileKey::MAX_GRACE_PERIOD).contains(&grace_period) {
+        return Err(Status::BadRequest);
+    }
+
+    match DeviceProfile::get(&mut db, profile_id as i32).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(Status::NotFound),
+        Err(why) => {
+            error!("Failed to get profile '{profile_id}': {why}");
+
+            return Err(Status::InternalServerError);
+        }
+    }
+
+    let (key, key_hash) = generate_hashed_key().await?;
+    let create_date = Utc::now().naive_utc();
+    let expire_date = create_date
+        .checked_add_signed(Duration::seconds(grace_period))
+        .ok_or(Status::BadRequest)?;
+
+    let rotated = replace_keys(
+        &mut db,
+        ProfileKey::new(profile_id as i32, &key_hash, auth.user_id(), create_date),
+        expire_date,
+    )
+    .await;
+
+    match rotated {
+        Ok(id_profile_key) => {
+            info!("Rotated the key of profile '{profile_id}'.");
+
+            Ok(Json(NewProfileKey {
+                profile_id: profile_id as i32,
+                id_profile_key,
+                profile_key: device_key(id_profile_key, &key),
+                previous_expire_date: Some(expire_date),
+            }))
+        }
+        Err(why) => {
+            error!("Failed to rotate the key of profile '{profile_id}': {why}");
+
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+#[get("/profiles/<profile_id>/keys")]
+pub async fn get_profile_keys(
+    auth: Auth<ProfilesRead>,
+    mut db: Connection<MonitoringDb>,
+    profile_id: u32,
+) -> Result<Json<Vec<ProfileKeyInfo>>, Status> {
+    if !auth.allows_profile(profile_id as i32) {
+        return Err(Status::Forbidden);
+    }
+
+    match ProfileKey::get_all_by_profile(&mut db, profile_id as i32).await {
+        Ok(keys) => Ok(Json(
+            keys.into_iter()
+                .map(|key| ProfileKeyInfo {
+                    id_profile_key: key.id_profile_key,
+                    create_user: key.create_user,
+                    create_date: key.create_date,
+                    expire_date: key.expire_date,
+                    revoke_date: key.revoke_date,
+                })
+                .collect(),
+        )),
+        Err(why) => {
+            error!("Failed to get keys of profile '{profile_id}': {why}");
+
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+/// Revokes a key immediately, also during its grace period.
+#[delete("/profiles/<profile_id>/keys/<key_id>")]
+pub async fn revoke_profile_key(
+    auth: Auth<ProfilesWrite>,
+    mut db: Connection<MonitoringDb>,
+    profile_id: u32,
+    key_id: u32,
+) -> Status {
+    if !auth.allows_profile(profile_id as i32) {
+        return Status::Forbidden;
+    }
+
+    let revoked = ProfileKey::revoke(
+        &mut db,
+        profile_id as i32,
+        key_id as i32,
         Utc::now().naive_utc(),
-    );
+    )
+    .await;
+
+    match revoked {
+        Ok(true) => Status::Ok,
+        Ok(false) => Status::NotFound,
+        Err(why) => {
+            error!("Failed to revoke key '{key_id}' of profile '{profile_id}': {why}");
+
+            Status::InternalServerError
+        }
+    }
+}
+
+/// Hashes the plaintext keys of profiles created before the keys were hashed.
+pub fn stage() -> AdHoc {
+    AdHoc::try_on_ignite("Profile key hashing", |rocket| async {
+        let pool = match MonitoringDb::fetch(&rocket) {
+            Some(db) => PgPool::clone(db),
+            None => {
+                error!("Failed to get database for profile key hashing!");
+                return Err(rocket);
+            }
+        };
+
+        match hash_plain_keys(&pool).await {
+            Ok(0) => Ok(rocket),
+            Ok(count) => {
+                info!("Hashed the plaintext keys of {count} profiles.");
+                Ok(rocket)
+            }
+            Err(why) => {
+                error!("Failed to hash plaintext profile keys: {why}");
+                Err(rocket)
+            }
+        }
+    })
+}
+
+async fn hash_plain_keys(pool: &PgPool) -> anyhow::Result<usize> {
+    let mut db = pool.acquire().await?;
+    let profiles = DeviceProfile::get_all_with_plain_key(&mut db).await?;
+
+    for profile in &profiles {
+        let key = profile.profile_key.clone().unwrap_or_default();
+        let key_hash = task::spawn_blocking(move || hash_key(&key)).await??;
+
+        let mut tx = db.begin().await?;
 
-    if let Err(why) = device_profile.insert(&mut *db).await {
-        error!("Failed to create new profile: {why}");
+        ProfileKey::new(
+            profile.id_device_profile,
+            &key_hash,
+            profile.create_user,
+            profile.create_date,
+        )
+        .insert(&mut tx)
+        .await?;
+        DeviceProfile::clear_plain_key(&mut tx, profile.id_device_profile).await?;
 
-        return Status::InternalServerError;
+        tx.commit().await?;
     }
 
-    Status::Ok
+    Ok(profiles.len())
+}
+
+/// The key sent by devices, the id lets the service verify only the hash of that key.
+fn device_key(id_profile_key: i32, key: &str) -> String {
+    format!("{id_profile_key}.{key}")
+}
+
+/// A new random key and its hash.
+async fn generate_hashed_key() -> Result<(String, String), Status> {
+    let key = generate_key();
+    let plain = key.clone();
+
+    match task::spawn_blocking(move || hash_key(&plain)).await {
+        Ok(Ok(key_hash)) => Ok((key, key_hash)),
+        Ok(Err(why)) => {
+            error!("Failed to hash profile key: {why}");
+            Err(Status::InternalServerError)
+        }
+        Err(why) => {
+            error!("Failed to hash profile key: {why}");
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+async fn insert_profile(
+    db: &mut PgConnection,
+    device_name: &str,
+    create_user: i64,
+    key_hash: &str,
+    create_date: NaiveDateTime,
+) -> Result<(i32, i32), sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let device_profile = DeviceProfile::new(device_name, create_user, create_date)
+        .insert(&mut tx)
+        .await?;
+    let profile_key = ProfileKey::new(
+        device_profile.id_device_profile,
+        key_hash,
+        create_user,
+        create_date,
+    )
+    .insert(&mut tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((device_profile.id_device_profile, profile_key.id_profile_key))
+}
+
+async fn replace_keys(
+    db: &mut PgConnection,
+    key: ProfileKey,
+    expire_date: NaiveDateTime,
+) -> Result<i32, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    ProfileKey::expire_valid(&mut tx, key.device_profile_id, expire_date).await?;
+    let key = key.insert(&mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(key.id_profile_key)
 }
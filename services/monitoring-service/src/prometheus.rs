@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use monitoring_core::prometheus::{write_devices, Device, Exposition};
+use rocket::http::{ContentType, Status};
+use rocket::{error, get, State};
+use rocket_db_pools::Connection;
+use service_lib::auth::{Auth, MetricsRead};
+use service_lib::database::MonitoringDb;
+use service_lib::models::device_profiles::DeviceProfile;
+use service_lib::models::system_informations::SystemInformation;
+use sqlx::pool::PoolConnection;
+use sqlx::Postgres;
+
+use crate::service_info::load_metric_system_infos;
+
+/// Upper bounds in seconds of the ingest latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+/// Outcome of a `POST /system-info/<profile_id>` request.
+#[derive(Debug, Clone, Copy)]
+pub enum IngestResult {
+    Ok,
+    BadRequest,
+    Error,
+}
+
+/// Counters and latencies of the system info ingestion, exported by `/metrics`.
+#[derive(Default)]
+pub struct IngestStats {
+    ok: AtomicU64,
+    bad_request: AtomicU64,
+    error: AtomicU64,
+    latency_buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    latency_count: AtomicU64,
+    latency_sum_micros: AtomicU64,
+}
+
+impl IngestStats {
+    pub fn record(&self, result: IngestResult, latency: Duration) {
+        let counter = match result {
+            IngestResult::Ok => &self.ok,
+            IngestResult::BadRequest => &self.bad_request,
+            IngestResult::Error => &self.error,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        let secs = latency.as_secs_f64();
+
+        for (bucket, bound) in self.latency_buckets.iter().zip(LATENCY_BUCKETS) {
+            if secs <= bound {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        self.latency_count.fetch_add(1, Ordering::Relaxed);
+        self.latency_sum_micros
+            .fetch_add(latency.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Exports the latest snapshot of every device profile and the ingest stats
+/// in the Prometheus text format.
+#[get("/metrics")]
+pub async fn get_prometheus_metrics(
+    auth: Auth<MetricsRead>,
+    mut db: Connection<MonitoringDb>,
+    stats: &State<IngestStats>,
+) -> Result<(ContentType, String), Status> {
+    let devices = match load_devices(&mut db, &auth).await {
+        Ok(devices) => devices,
+        Err(why) => {
+            error!("Failed to load snapshots for metrics: {why}");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    let mut exposition = Exposition::default();
+    write_devices(&mut exposition, &devices);
+    write_ingest_stats(&mut exposition, stats);
+
+    Ok((ContentType::Plain, exposition.into_string()))
+}
+
+async fn load_devices(
+    db: &mut PoolConnection<Postgres>,
+    auth: &Auth<MetricsRead>,
+) -> Result<Vec<Device>, sqlx::Error> {
+    let mut device_names: HashMap<i32, String> = DeviceProfile::get_all(db)
+        .await?
+        .into_iter()
+        .filter(|profile| auth.allows_profile(profile.id_device_profile))
+        .map(|profile| (profile.id_device_profile, profile.device_name))
+        .collect();
+    let mut profile_ids: Vec<i32> = device_names.keys().copied().collect();
+    profile_ids.sort_unstable();
+
+    let latest = SystemInformation::get_latest_by_profiles(db, &profile_ids).await?;
+    let labels: Vec<_> = latest
+        .iter()
+        .map(|latest| {
+            let profile_id = latest.device_profile_id;
+
+            (
+                vec![
+                    ("profile_id", profile_id.to_string()),
+                    (
+                        "device_name",
+                        device_names.remove(&profile_id).unwrap_or_default(),
+                    ),
+                    ("hostname", latest.hostname.clone()),
+                ],
+                latest.create_date,
+            )
+        })
+        .collect();
+
+    let infos = load_metric_system_infos(latest, db).await?;
+
+    Ok(labels
+        .into_iter()
+        .zip(infos)
+        .map(|((labels, timestamp), info)| Device {
+            labels,
+            timestamp,
+            info,
+        })
+        .collect())
+}
+
+fn write_ingest_stats(exp: &mut Exposition, stats: &IngestStats) {
+    exp.family(
+        "monitoring_ingest_requests_total",
+        "System info snapshots received by the service.",
+        "counter",
+    );
+    for (result, counter) in [
+        ("ok", &stats.ok),
+        ("bad_request", &stats.bad_request),
+        ("error", &stats.error),
+    ] {
+        exp.sample(
+            &[],
+            &[("result", result)],
+            counter.load(Ordering::Relaxed) as f64,
+        );
+    }
+
+    let name = "monitoring_ingest_duration_seconds";
+    exp.family(name, "Time to store a system info snapshot.", "histogram");
+
+    for (bucket, bound) in stats.latency_buckets.iter().zip(LATENCY_BUCKETS) {
+        exp.named_sample(
+            &format!("{name}_bucket"),
+            &[],
+            &[("le", &bound.to_string())],
+            bucket.load(Ordering::Relaxed) as f64,
+        );
+    }
+
+    let count = stats.latency_count.load(Ordering::Relaxed) as f64;
+    exp.named_sample(&format!("{name}_bucket"), &[], &[("le", "+Inf")], count);
+    exp.named_sample(
+        &format!("{name}_sum"),
+        &[],
+        &[],
+        stats.latency_sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+    );
+    exp.named_sample(&format!("{name}_count"), &[], &[], count);
+}
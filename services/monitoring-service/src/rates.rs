@@ -0,0 +1,166 @@
+use chrono::NaiveDateTime;
+
+/// Boot times further apart than this in seconds are a reboot.
+/// The boot time is derived from the uptime and can jitter by a second.
+const BOOT_TIME_TOLERANCE: i64 = 5;
+
+/// A 32 bit counter within this of `u32::MAX` that went backwards has wrapped around.
+/// Further away it was more likely reset, e.g. by reloading the driver.
+const WRAP_MARGIN: u64 = 1 << 30;
+
+/// Cumulative counters of a snapshot together with the time they were read.
+pub struct CounterSample<const N: usize> {
+    pub date: NaiveDateTime,
+    pub boot_time: NaiveDateTime,
+    pub counters: [u64; N],
+}
+
+/// Per second rates of the counters between two snapshots.
+/// After a reboot the counters started at 0 on boot, so the rates are computed since the boot time.
+/// Returns `None` for the first sample, if the snapshots aren't in order and if a counter was reset
+/// without a reboot, since the time of the reset is unknown.
+pub fn counter_rates<const N: usize>(
+    previous: Option<&CounterSample<N>>,
+    current: &CounterSample<N>,
+) -> Option<[f64; N]> {
+    let previous = previous?;
+    let rebooted =
+        (current.boot_time - previous.boot_time).num_seconds().abs() > BOOT_TIME_TOLERANCE;
+    let start = if rebooted {
+        current.boot_time
+    } else {
+        previous.date
+    };
+    let secs = (current.date - start).num_milliseconds() as f64 / 1000.0;
+
+    if secs <= 0.0 {
+        return None;
+    }
+
+    let mut rates = [0.0; N];
+
+    for (i, rate) in rates.iter_mut().enumerate() {
+        let delta = if rebooted {
+            current.counters[i]
+        } else {
+            counter_delta(previous.counters[i], current.counters[i])?
+        };
+
+        *rate = delta as f64 / secs;
+    }
+
+    Some(rates)
+}
+
+/// Difference of a counter that may have wrapped around, `None` if it was reset.
+/// Some drivers still use 32 bit counters, a counter close below `u32::MAX` that went backwards
+/// is taken as wrapped around, any other counter going backwards as reset.
+fn counter_delta(previous: u64, current: u64) -> Option<u64> {
+    let max = u32::MAX as u64;
+
+    if current >= previous {
+        Some(current - previous)
+    } else if previous <= max && max - previous < WRAP_MARGIN {
+        Some(current + (max + 1 - previous))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+    use super::{counter_rates, CounterSample};
+
+    fn date(seconds: i64) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 6, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            + Duration::seconds(seconds)
+    }
+
+    fn sample<const N: usize>(date: i64, boot_time: i64, counters: [u64; N]) -> CounterSample<N> {
+        CounterSample {
+            date: self::date(date),
+            boot_time: self::date(boot_time),
+            counters,
+        }
+    }
+
+    #[test]
+    fn first_sample_has_no_rates() {
+        assert_eq!(counter_rates(None, &sample(10, 0, [100])), None);
+    }
+
+    #[test]
+    fn rates_of_the_delta() {
+        let previous = sample(100, 0, [1_000, 50]);
+        let current = sample(110, 0, [2_000, 50]);
+
+        assert_eq!(counter_rates(Some(&previous), &current), Some([100.0, 0.0]));
+    }
+
+    #[test]
+    fn boot_time_jitter_is_no_reboot() {
+        let previous = sample(100, 0, [1_000]);
+        let current = sample(110, 1, [1_500]);
+
+        assert_eq!(counter_rates(Some(&previous), &current), Some([50.0]));
+    }
+
+    #[test]
+    fn rates_since_boot_after_reboot() {
+        let previous = sample(100, 0, [1_000_000]);
+        let current = sample(200, 150, [500]);
+
+        assert_eq!(counter_rates(Some(&previous), &current), Some([10.0]));
+    }
+
+    #[test]
+    fn wrapped_32_bit_counter() {
+        let previous = sample(100, 0, [u32::MAX as u64 - 99]);
+        let current = sample(110, 0, [100]);
+
+        assert_eq!(counter_rates(Some(&previous), &current), Some([20.0]));
+    }
+
+    #[test]
+    fn reset_64_bit_counter() {
+        let previous = sample(100, 0, [u32::MAX as u64 + 1_000]);
+        let current = sample(110, 0, [300]);
+
+        assert_eq!(counter_rates(Some(&previous), &current), None);
+    }
+
+    #[test]
+    fn reset_small_counter() {
+        let previous = sample(100, 0, [5_000, 10]);
+        let current = sample(110, 0, [300, 20]);
+
+        assert_eq!(counter_rates(Some(&previous), &current), None);
+    }
+
+    #[test]
+    fn reset_32_bit_counter_far_from_wrapping() {
+        let previous = sample(100, 0, [u32::MAX as u64 / 2]);
+        let current = sample(110, 0, [300]);
+
+        assert_eq!(counter_rates(Some(&previous), &current), None);
+    }
+
+    #[test]
+    fn samples_out_of_order_have_no_rates() {
+        let previous = sample(110, 0, [1_000]);
+
+        assert_eq!(
+            counter_rates(Some(&previous), &sample(110, 0, [2_000])),
+            None
+        );
+        assert_eq!(
+            counter_rates(Some(&previous), &sample(100, 0, [2_000])),
+            None
+        );
+    }
+}
@@ -1,153 +1,265 @@
-use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsString;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use chrono::{NaiveDateTime, Utc};
 use monitoring_core::models::IpAddress;
+use os_info::{Bitness, Info, Type, Version};
 use rocket::http::Status;
-use rocket::serde::json::Json;
-use rocket::{get, post};
+use rocket::serde::json::{serde_json, Json};
+use rocket::{error, get, post, State};
 use rocket_db_pools::Connection;
-use serde::{Serialize, Deserialize};
-use service_lib::api_key::ApiKey;
+use service_lib::auth::{Auth, MetricsRead};
 use service_lib::database::MonitoringDb;
 use service_lib::models::battery_lifes;
+use service_lib::models::check_results::CheckResult;
 use service_lib::models::cpu_core_loads;
 use service_lib::models::cpu_informations;
 use service_lib::models::cpu_loads;
+use service_lib::models::disk_io_infos::DiskIoInfo;
+use service_lib::models::extra_sections::ExtraSection;
+use service_lib::models::fan_infos::FanInfo;
 use service_lib::models::filesystem_infos;
 use service_lib::models::load_averages;
 use service_lib::models::memory_infos;
 use service_lib::models::network_addresses;
+use service_lib::models::network_rates::NetworkRate;
 use service_lib::models::network_statistics;
 use service_lib::models::networks;
 use service_lib::models::os_infos;
+use service_lib::models::process_infos::{self, ProcessInfo};
 use service_lib::models::socket_statistics;
 use service_lib::models::swap_infos;
 use service_lib::models::system_informations::{self, SystemInformation};
+use service_lib::models::temperature_infos::TemperatureInfo;
 use service_lib::profile_key::ProfileKey;
-use service_lib::read_key::ReadKey;
 use sqlx::pool::PoolConnection;
-use sqlx::Postgres;
+use sqlx::{Connection as _, PgConnection, Postgres};
 
-#[derive(Serialize, Deserialize)]
-pub struct LatestSystemInfo {
-    pub system_info: SystemInformation,
-}
+use crate::alerting;
+use crate::prometheus::{IngestResult, IngestStats};
+use crate::rates::{counter_rates, CounterSample};
+
+/// Collection times further ahead of the receive time in seconds are logged as clock skew.
+const MAX_CLOCK_SKEW: i64 = 300;
 
 #[get("/system-info/<profile_id>")]
 pub async fn get_latest_entry(
-    _a_key: ApiKey<'_>,
-    _r_key: ReadKey<'_>,
+    auth: Auth<MetricsRead>,
     mut db: Connection<MonitoringDb>,
     profile_id: u32,
-) -> Result<Json<LatestSystemInfo>, Status> {
-    Err(Status::NotImplemented)
+) -> Result<Json<monitoring_core::models::SystemInformation>, Status> {
+    if !auth.allows_profile(profile_id as i32) {
+        return Err(Status::Forbidden);
+    }
+
+    let system_info = match SystemInformation::get_latest(&mut db, profile_id as i32).await {
+        Ok(Some(system_info)) => system_info,
+        Ok(None) => return Err(Status::NotFound),
+        Err(why) => {
+            error!("Failed to get latest system info for profile '{profile_id}': {why}");
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    match load_system_info(system_info, &mut db).await {
+        Ok(info) => Ok(Json(info)),
+        Err(why) => {
+            error!("Failed to load system info for profile '{profile_id}': {why}");
+            Err(Status::InternalServerError)
+        }
+    }
 }
 
 #[post("/system-info/<profile_id>", data = "<info>")]
 pub async fn save_system_info(
-    _a_key: ApiKey<'_>,
     _p_key: ProfileKey<'_>,
     mut db: Connection<MonitoringDb>,
+    stats: &State<IngestStats>,
     profile_id: u32,
     info: Json<monitoring_core::models::SystemInformation>,
 ) -> Status {
+    let started = Instant::now();
+
     let hostname = match info.hostname.clone().into_string() {
         Ok(h) => h,
         Err(_) => {
             rocket::error!("Failed to convert hostname.");
+            stats.record(IngestResult::BadRequest, started.elapsed());
             return Status::BadRequest;
         }
     };
 
-    if let Ok(system_info_model) = system_informations::SystemInformation::new(
+    if let Some(sequence) = info.sequence {
+        match SystemInformation::exists_by_sequence(&mut db, profile_id as i32, sequence as i64)
+            .await
+        {
+            Ok(true) => {
+                rocket::info!(
+                    "Skipped already stored system info '{sequence}' for profile '{profile_id}'."
+                );
+                stats.record(IngestResult::Ok, started.elapsed());
+                return Status::Ok;
+            }
+            Ok(false) => {}
+            Err(why) => {
+                rocket::error!(
+                    "Failed to check system info '{sequence}' for profile '{profile_id}': {why}."
+                );
+                stats.record(IngestResult::Error, started.elapsed());
+                return Status::InternalServerError;
+            }
+        }
+    }
+
+    let receive_date = Utc::now().naive_utc();
+    let create_date = info.collected_at.unwrap_or(receive_date);
+
+    if create_date - receive_date > chrono::Duration::seconds(MAX_CLOCK_SKEW) {
+        rocket::warn!(
+            "System info for profile '{profile_id}' was collected in the future ({create_date}, received {receive_date}), the device clock is probably off."
+        );
+    }
+
+    if let Err(why) = insert_system_info(
         profile_id as i32,
         &hostname,
-        info.uptime.as_secs() as i64,
-        info.boot_time,
-        Utc::now().naive_utc(),
+        &info,
+        create_date,
+        receive_date,
+        &mut db,
     )
-    .insert(&mut *db)
     .await
     {
-        if let Some(os) = &info.os_info {
-            if let Err(why) = os_infos::OsInfo::new(
-                system_info_model.id_system_information,
-                &os.os_type().to_string(),
-                &os.version().to_string(),
-                os.edition().and_then(|s| Some(String::from(s))),
-                os.codename().and_then(|s| Some(String::from(s))),
-                &os.bitness().to_string(),
-                os.architecture().and_then(|s| Some(String::from(s))),
-            )
-            .insert(&mut *db)
-            .await
-            {
-                rocket::error!("Failed to insert os info: {why}.");
-            }
+        // A retry of the snapshot can pass the check above while the first upload is inserted.
+        if let (Some(sequence), true) = (info.sequence, is_unique_violation(&why)) {
+            rocket::info!(
+                "Skipped already stored system info '{sequence}' for profile '{profile_id}'."
+            );
+            stats.record(IngestResult::Ok, started.elapsed());
+            return Status::Ok;
         }
 
-        if let Err(why) =
-            insert_cpu_data(system_info_model.id_system_information, &info, &mut *db).await
-        {
-            rocket::error!("Failed to insert cpu infos: {why}.");
-        }
+        rocket::error!("Failed to insert system info for profile '{profile_id}': {why}.");
+        stats.record(IngestResult::Error, started.elapsed());
+        return Status::InternalServerError;
+    }
 
-        if let Some(l_avg) = &info.load_avg {
-            if let Err(why) = load_averages::LoadAverage::new(
-                system_info_model.id_system_information,
-                l_avg.one,
-                l_avg.five,
-                l_avg.fifteen,
-            )
-            .insert(&mut *db)
-            .await
-            {
-                rocket::error!("Failed to insert load averages: {why}.");
-            }
-        }
+    stats.record(IngestResult::Ok, started.elapsed());
+    rocket::info!("Inserted new system info for profile '{profile_id}'.");
 
-        if let Some(mem) = &info.memory {
-            if let Err(why) = memory_infos::MemoryInfo::new(
-                system_info_model.id_system_information,
-                mem.free as i64,
-                mem.total as i64,
-            )
-            .insert(&mut *db)
-            .await
-            {
-                rocket::error!("Failed to insert memory info: {why}.");
-            }
-        }
+    if let Err(why) = alerting::evaluate_profile(&mut db, profile_id as i32).await {
+        rocket::error!("Failed to evaluate alert rules for profile '{profile_id}': {why}");
+    }
 
-        if let Some(swap) = &info.swap {
-            if let Err(why) = swap_infos::SwapInfo::new(
-                system_info_model.id_system_information,
-                swap.free as i64,
-                swap.total as i64,
-            )
-            .insert(&mut *db)
-            .await
-            {
-                rocket::error!("Failed to insert swap info: {why}.");
-            }
-        }
+    Status::Ok
+}
 
-        if let Some(battery_life) = info.battery_life {
-            if let Err(why) = battery_lifes::BatteryLife::new(
-                system_info_model.id_system_information,
-                battery_life.remaining_capacity,
-                battery_life.remaining_time.as_secs() as i64,
-            )
-            .insert(&mut *db)
-            .await
-            {
-                rocket::error!("Failed to insert battery life info: {why}.");
-            }
-        }
+fn is_unique_violation(why: &sqlx::Error) -> bool {
+    why.as_database_error()
+        .and_then(|x| x.code())
+        .is_some_and(|code| code == "23505")
+}
+
+/// Writes the whole snapshot in a single transaction, nothing is stored if any insert fails.
+async fn insert_system_info(
+    profile_id: i32,
+    hostname: &str,
+    info: &monitoring_core::models::SystemInformation,
+    create_date: NaiveDateTime,
+    receive_date: NaiveDateTime,
+    db: &mut PgConnection,
+) -> Result<i32, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    let system_info_model = system_informations::SystemInformation::new(
+        profile_id,
+        hostname,
+        info.uptime.as_secs() as i64,
+        info.boot_time,
+        create_date,
+        info.sequence.map(|x| x as i64),
+        receive_date,
+    )
+    .insert(&mut tx)
+    .await?;
+    let id_system_info = system_info_model.id_system_information;
+
+    if let Some(os) = &info.os_info {
+        os_infos::OsInfo::new(
+            id_system_info,
+            &os.os_type().to_string(),
+            &os.version().to_string(),
+            os.edition().map(String::from),
+            os.codename().map(String::from),
+            &os.bitness().to_string(),
+            os.architecture().map(String::from),
+            serde_json::to_value(os).ok(),
+        )
+        .insert(&mut tx)
+        .await?;
+    }
+
+    insert_cpu_data(id_system_info, info, &mut tx).await?;
+
+    if let Some(l_avg) = &info.load_avg {
+        load_averages::LoadAverage::new(id_system_info, l_avg.one, l_avg.five, l_avg.fifteen)
+            .insert(&mut tx)
+            .await?;
+    }
+
+    if let Some(mem) = &info.memory {
+        // Older agents don't send the available memory.
+        let available = if mem.available > 0 {
+            mem.available
+        } else {
+            mem.free
+        };
+
+        memory_infos::MemoryInfo::new(
+            id_system_info,
+            mem.free as i64,
+            mem.total as i64,
+            available as i64,
+            mem.buffers as i64,
+            mem.cached as i64,
+            mem.shared as i64,
+            mem.slab as i64,
+            mem.dirty as i64,
+            mem.writeback as i64,
+            mem.hugepages_total as i64,
+            mem.hugepages_free as i64,
+        )
+        .insert(&mut tx)
+        .await?;
+    }
+
+    if let Some(swap) = &info.swap {
+        swap_infos::SwapInfo::new(id_system_info, swap.free as i64, swap.total as i64)
+            .insert(&mut tx)
+            .await?;
+    }
 
-        for mount in info.mounts.iter() {
-            if let Err(why) = filesystem_infos::FilesystemInfo::new(
-                system_info_model.id_system_information,
+    if let Some(battery_life) = info.battery_life {
+        battery_lifes::BatteryLife::new(
+            id_system_info,
+            battery_life.remaining_capacity,
+            battery_life.remaining_time.as_secs() as i64,
+        )
+        .insert(&mut tx)
+        .await?;
+    }
+
+    let mounts = info
+        .mounts
+        .iter()
+        .map(|mount| {
+            filesystem_infos::FilesystemInfo::new(
+                id_system_info,
                 mount.files as i32,
                 mount.files_total as i32,
+                mount.files_avail as i32,
                 mount.free as i64,
                 mount.avail as i64,
                 mount.total as i64,
@@ -156,67 +268,175 @@ pub async fn save_system_info(
                 &mount.fs_mounted_from,
                 &mount.fs_mounted_on,
             )
-            .insert(&mut *db)
-            .await
-            {
-                rocket::error!("Failed to insert mount info: {why}.");
-            }
-        }
+        })
+        .collect();
+    filesystem_infos::FilesystemInfo::insert_many(&mut tx, mounts).await?;
 
-        if let Err(why) =
-            insert_network_data(system_info_model.id_system_information, &info, &mut *db).await
-        {
-            rocket::error!("Failed to insert network infos: {why}.");
-        }
+    insert_network_data(profile_id, id_system_info, info, create_date, &mut tx).await?;
 
-        if let Some(sock) = &info.socket_stats {
-            if let Err(why) = socket_statistics::SocketStatistic::new(
-                system_info_model.id_system_information,
-                sock.tcp_sockets_in_use as i32,
-                sock.tcp_sockets_orphaned as i32,
-                sock.udp_sockets_in_use as i32,
-                sock.tcp6_sockets_in_use as i32,
-                sock.udp6_sockets_in_use as i32,
+    if let Some(sock) = &info.socket_stats {
+        socket_statistics::SocketStatistic::new(
+            id_system_info,
+            sock.tcp_sockets_in_use as i32,
+            sock.tcp_sockets_orphaned as i32,
+            sock.udp_sockets_in_use as i32,
+            sock.tcp6_sockets_in_use as i32,
+            sock.udp6_sockets_in_use as i32,
+        )
+        .insert(&mut tx)
+        .await?;
+    }
+
+    let disks = info
+        .disk_io
+        .iter()
+        .map(|disk| {
+            DiskIoInfo::new(
+                id_system_info,
+                &disk.name,
+                disk.read_bytes,
+                disk.write_bytes,
+                disk.read_ops,
+                disk.write_ops,
+                disk.in_flight as i64,
+                disk.io_time,
             )
-            .insert(&mut *db)
-            .await
-            {
-                rocket::error!("Failed to insert load averages: {why}.");
-            }
-        }
+        })
+        .collect();
+    DiskIoInfo::insert_many(&mut tx, disks).await?;
 
-        rocket::info!("Inserted new system info for profile '{profile_id}'.");
-        return Status::Ok;
+    if let Some(processes) = &info.processes {
+        let rankings = [
+            (process_infos::RANKING_CPU, &processes.by_cpu),
+            (process_infos::RANKING_MEMORY, &processes.by_memory),
+        ];
+        let models = rankings
+            .iter()
+            .flat_map(|(ranking, processes)| {
+                processes
+                    .iter()
+                    .map(|process| to_process_model(id_system_info, ranking, process))
+            })
+            .collect();
+
+        ProcessInfo::insert_many(&mut tx, models).await?;
     }
 
-    Status::InternalServerError
+    if let Some(thermals) = &info.thermals {
+        let sensors = thermals
+            .temperatures
+            .iter()
+            .map(|sensor| {
+                TemperatureInfo::new(
+                    id_system_info,
+                    &sensor.chip,
+                    &sensor.label,
+                    sensor.current,
+                    sensor.high,
+                    sensor.critical,
+                )
+            })
+            .collect();
+        TemperatureInfo::insert_many(&mut tx, sensors).await?;
+
+        let fans = thermals
+            .fans
+            .iter()
+            .map(|fan| {
+                FanInfo::new(
+                    id_system_info,
+                    &fan.chip,
+                    &fan.label,
+                    fan.rpm.map(i64::from),
+                )
+            })
+            .collect();
+        FanInfo::insert_many(&mut tx, fans).await?;
+    }
+
+    let checks = info
+        .checks
+        .iter()
+        .map(|check| {
+            CheckResult::new(
+                id_system_info,
+                &check.name,
+                check.status.as_str(),
+                &check.output,
+                serde_json::to_value(&check.perfdata).unwrap_or_default(),
+                check.executed_at,
+                check.duration,
+            )
+        })
+        .collect();
+    CheckResult::insert_many(&mut tx, checks).await?;
+
+    let sections = info
+        .extra
+        .iter()
+        .map(|(name, value)| ExtraSection::new(id_system_info, name, value.clone()))
+        .collect();
+    ExtraSection::insert_many(&mut tx, sections).await?;
+
+    tx.commit().await?;
+
+    Ok(id_system_info)
+}
+
+fn to_process_model(
+    id_system_info: i32,
+    ranking: &str,
+    process: &monitoring_core::models::Process,
+) -> ProcessInfo {
+    ProcessInfo::new(
+        id_system_info,
+        ranking,
+        process.pid as i32,
+        &process.name,
+        &process.cmdline,
+        &process.user,
+        &process.state,
+        process.threads as i64,
+        process.open_fds.map(|x| x as i64),
+        process.cpu_usage,
+        process.rss as i64,
+    )
 }
 
 async fn insert_network_data(
+    profile_id: i32,
     id_system_info: i32,
-    info: &Json<monitoring_core::models::SystemInformation>,
-    db: &mut PoolConnection<Postgres>,
+    info: &monitoring_core::models::SystemInformation,
+    create_date: NaiveDateTime,
+    db: &mut PgConnection,
 ) -> Result<(), sqlx::Error> {
-    for (interface, network) in info.networks.iter() {
-        let network_model = networks::Network::new(id_system_info, &interface)
-            .insert(&mut *db)
-            .await?;
+    let network_models = networks::Network::insert_many(
+        &mut *db,
+        info.networks
+            .keys()
+            .map(|interface| networks::Network::new(id_system_info, interface))
+            .collect(),
+    )
+    .await?;
+
+    let previous = previous_network_samples(profile_id, create_date, &mut *db).await?;
 
+    let mut addresses = Vec::new();
+    let mut statistics = Vec::new();
+    let mut rates = Vec::new();
+
+    // `insert_many` keeps the order, so the models line up with the interfaces.
+    for ((interface, network), network_model) in info.networks.iter().zip(&network_models) {
         for addr in network.addrs.iter() {
-            if let Err(why) = network_addresses::NetworkAddress::new(
+            addresses.push(network_addresses::NetworkAddress::new(
                 network_model.id_network,
                 &ip_to_string(addr.addr.clone()),
                 &ip_to_string(addr.netmask.clone()),
-            )
-            .insert(&mut *db)
-            .await
-            {
-                rocket::error!("Failed to insert mount info: {why}.");
-            }
+            ));
         }
 
         if let Some(netstat) = info.net_stats.get(&network.name) {
-            if let Err(why) = network_statistics::NetworkStatistic::new(
+            statistics.push(network_statistics::NetworkStatistic::new(
                 id_system_info,
                 network_model.id_network,
                 netstat.rx_bytes as i64,
@@ -225,18 +445,77 @@ async fn insert_network_data(
                 netstat.tx_packets as i64,
                 netstat.rx_errors as i64,
                 netstat.tx_errors as i64,
-            )
-            .insert(&mut *db)
-            .await
+            ));
+
+            let current = CounterSample {
+                date: create_date,
+                boot_time: info.boot_time,
+                counters: [
+                    netstat.rx_bytes,
+                    netstat.tx_bytes,
+                    netstat.rx_packets,
+                    netstat.tx_packets,
+                    netstat.rx_errors,
+                    netstat.tx_errors,
+                ],
+            };
+
+            if let Some([rx_bytes, tx_bytes, rx_packets, tx_packets, rx_errors, tx_errors]) =
+                counter_rates(previous.get(interface), &current)
             {
-                rocket::error!("Failed to insert network statistics: {why}.");
+                rates.push(NetworkRate::new(
+                    id_system_info,
+                    network_model.id_network,
+                    rx_bytes,
+                    tx_bytes,
+                    rx_packets,
+                    tx_packets,
+                    rx_errors,
+                    tx_errors,
+                ));
             }
         }
     }
 
+    network_addresses::NetworkAddress::insert_many(&mut *db, addresses).await?;
+    network_statistics::NetworkStatistic::insert_many(&mut *db, statistics).await?;
+    NetworkRate::insert_many(&mut *db, rates).await?;
+
     Ok(())
 }
 
+/// Counters of the interfaces in the previous snapshot of the profile, an interface without
+/// counters there has no rates.
+async fn previous_network_samples(
+    profile_id: i32,
+    before: NaiveDateTime,
+    db: &mut PgConnection,
+) -> Result<HashMap<String, CounterSample<6>>, sqlx::Error> {
+    let statistics =
+        network_statistics::NetworkStatistic::get_all_previous(&mut *db, profile_id, before)
+            .await?;
+
+    Ok(statistics
+        .into_iter()
+        .map(|(date, boot_time, interface, netstat)| {
+            let sample = CounterSample {
+                date,
+                boot_time,
+                counters: [
+                    netstat.rx_bytes as u64,
+                    netstat.tx_bytes as u64,
+                    netstat.rx_packages as u64,
+                    netstat.tx_packages as u64,
+                    netstat.rx_errors as u64,
+                    netstat.tx_errors as u64,
+                ],
+            };
+
+            (interface, sample)
+        })
+        .collect())
+}
+
 fn ip_to_string(ip: IpAddress) -> String {
     match ip {
         IpAddress::Empty => String::new(),
@@ -248,19 +527,13 @@ fn ip_to_string(ip: IpAddress) -> String {
 
 async fn insert_cpu_data(
     id_system_info: i32,
-    info: &Json<monitoring_core::models::SystemInformation>,
-    db: &mut PoolConnection<Postgres>,
+    info: &monitoring_core::models::SystemInformation,
+    db: &mut PgConnection,
 ) -> Result<(), sqlx::Error> {
     if let Some(cpu) = &info.cpu {
-        let aggregate_load = cpu_loads::CpuLoad::new(
-            cpu.aggregate_load.user,
-            cpu.aggregate_load.nice,
-            cpu.aggregate_load.system,
-            cpu.aggregate_load.interrupt,
-            cpu.aggregate_load.idle,
-        )
-        .insert(&mut *db)
-        .await?;
+        let aggregate_load = to_cpu_load_model(&cpu.aggregate_load)
+            .insert(&mut *db)
+            .await?;
 
         let cpu_info = cpu_informations::CpuInformation::new(
             id_system_info,
@@ -270,22 +543,641 @@ async fn insert_cpu_data(
         .insert(&mut *db)
         .await?;
 
-        for cpu_load in cpu.loads.iter() {
-            let core_load = cpu_loads::CpuLoad::new(
-                cpu_load.user,
-                cpu_load.nice,
-                cpu_load.system,
-                cpu_load.interrupt,
-                cpu_load.idle,
-            )
-            .insert(&mut *db)
-            .await?;
-
+        let core_loads = cpu_loads::CpuLoad::insert_many(
+            &mut *db,
+            cpu.loads.iter().map(to_cpu_load_model).collect(),
+        )
+        .await?
+        .into_iter()
+        .map(|core_load| {
             cpu_core_loads::CpuCoreLoad::new(cpu_info.id_cpu_information, core_load.id_cpu_load)
-                .insert(&mut *db)
-                .await?;
-        }
+        })
+        .collect();
+
+        cpu_core_loads::CpuCoreLoad::insert_many(&mut *db, core_loads).await?;
     }
 
     Ok(())
 }
+
+fn to_cpu_load_model(load: &monitoring_core::models::CpuLoad) -> cpu_loads::CpuLoad {
+    cpu_loads::CpuLoad::new(load.user, load.nice, load.system, load.interrupt, load.idle)
+}
+
+pub(crate) async fn load_system_info(
+    system_info: SystemInformation,
+    db: &mut PoolConnection<Postgres>,
+) -> Result<monitoring_core::models::SystemInformation, sqlx::Error> {
+    let id_system_info = system_info.id_system_information;
+
+    let os_info = os_infos::OsInfo::get_by_system_information(&mut *db, id_system_info)
+        .await?
+        .map(|os| match os.info.clone().map(serde_json::from_value) {
+            Some(Ok(info)) => info,
+            Some(Err(why)) => {
+                rocket::warn!("Failed to read os info '{}': {why}", os.id_os_info);
+                to_os_info(os)
+            }
+            None => to_os_info(os),
+        });
+    let cpu = load_cpu_data(id_system_info, &mut *db).await?;
+    let load_avg = load_averages::LoadAverage::get_by_system_information(&mut *db, id_system_info)
+        .await?
+        .map(to_load_average);
+    let memory = memory_infos::MemoryInfo::get_by_system_information(&mut *db, id_system_info)
+        .await?
+        .map(to_memory);
+    let swap = swap_infos::SwapInfo::get_by_system_information(&mut *db, id_system_info)
+        .await?
+        .map(to_swap);
+    let battery_life =
+        battery_lifes::BatteryLife::get_by_system_information(&mut *db, id_system_info)
+            .await?
+            .map(to_battery_life);
+    let mounts =
+        filesystem_infos::FilesystemInfo::get_all_by_system_information(&mut *db, id_system_info)
+            .await?
+            .into_iter()
+            .map(to_filesystem)
+            .collect();
+    let (networks, net_stats) = load_network_data(id_system_info, &mut *db).await?;
+    let socket_stats =
+        socket_statistics::SocketStatistic::get_by_system_information(&mut *db, id_system_info)
+            .await?
+            .map(to_socket_statistics);
+    let processes = load_process_data(id_system_info, &mut *db).await?;
+    let disk_io = DiskIoInfo::get_all_by_system_information(&mut *db, id_system_info)
+        .await?
+        .into_iter()
+        .map(to_disk_io)
+        .collect();
+    let thermals = load_thermal_data(id_system_info, &mut *db).await?;
+    let checks = load_check_data(id_system_info, &mut *db).await?;
+    let extra = ExtraSection::get_all_by_system_information(&mut *db, id_system_info)
+        .await?
+        .into_iter()
+        .map(|section| (section.name, section.value))
+        .collect();
+
+    Ok(monitoring_core::models::SystemInformation {
+        hostname: OsString::from(system_info.hostname),
+        os_info,
+        cpu,
+        load_avg,
+        memory,
+        swap,
+        battery_life,
+        mounts,
+        networks,
+        net_stats,
+        socket_stats,
+        processes,
+        disk_io,
+        thermals,
+        checks,
+        extra,
+        uptime: Duration::from_secs(system_info.uptime as u64),
+        boot_time: system_info.boot_time,
+        collected_at: Some(system_info.create_date),
+        sequence: system_info.sequence.map(|x| x as u64),
+    })
+}
+
+/// Loads the sections exported by `/metrics` of several snapshots with one query per table.
+/// The os info, network addresses, processes and extra sections are left empty.
+pub(crate) async fn load_metric_system_infos(
+    system_infos: Vec<SystemInformation>,
+    db: &mut PoolConnection<Postgres>,
+) -> Result<Vec<monitoring_core::models::SystemInformation>, sqlx::Error> {
+    let ids: Vec<i32> = system_infos
+        .iter()
+        .map(|x| x.id_system_information)
+        .collect();
+
+    let mut cpus = load_cpu_datas(&ids, &mut *db).await?;
+    let mut load_avgs: HashMap<i32, _> =
+        load_averages::LoadAverage::get_all_by_system_informations(&mut *db, &ids)
+            .await?
+            .into_iter()
+            .map(|x| (x.system_information_id, x))
+            .collect();
+    let mut memories: HashMap<i32, _> =
+        memory_infos::MemoryInfo::get_all_by_system_informations(&mut *db, &ids)
+            .await?
+            .into_iter()
+            .map(|x| (x.system_information_id, x))
+            .collect();
+    let mut swaps: HashMap<i32, _> =
+        swap_infos::SwapInfo::get_all_by_system_informations(&mut *db, &ids)
+            .await?
+            .into_iter()
+            .map(|x| (x.system_information_id, x))
+            .collect();
+    let mut batteries: HashMap<i32, _> =
+        battery_lifes::BatteryLife::get_all_by_system_informations(&mut *db, &ids)
+            .await?
+            .into_iter()
+            .map(|x| (x.system_information_id, x))
+            .collect();
+    let mut socket_stats: HashMap<i32, _> =
+        socket_statistics::SocketStatistic::get_all_by_system_informations(&mut *db, &ids)
+            .await?
+            .into_iter()
+            .map(|x| (x.system_information_id, x))
+            .collect();
+    let mut mounts = group_by(
+        filesystem_infos::FilesystemInfo::get_all_by_system_informations(&mut *db, &ids).await?,
+        |x| x.system_information_id,
+    );
+    let mut net_stats = group_by(
+        network_statistics::NetworkStatistic::get_all_by_system_informations(&mut *db, &ids)
+            .await?,
+        |(_, x)| x.system_information_id,
+    );
+    let mut disk_io = group_by(
+        DiskIoInfo::get_all_by_system_informations(&mut *db, &ids).await?,
+        |x| x.system_information_id,
+    );
+    let mut temperatures = group_by(
+        TemperatureInfo::get_all_by_system_informations(&mut *db, &ids).await?,
+        |x| x.system_information_id,
+    );
+    let mut fans = group_by(
+        FanInfo::get_all_by_system_informations(&mut *db, &ids).await?,
+        |x| x.system_information_id,
+    );
+    let mut checks = group_by(
+        CheckResult::get_all_by_system_informations(&mut *db, &ids).await?,
+        |x| x.system_information_id,
+    );
+
+    Ok(system_infos
+        .into_iter()
+        .map(|system_info| {
+            let id = system_info.id_system_information;
+
+            monitoring_core::models::SystemInformation {
+                hostname: OsString::from(system_info.hostname),
+                os_info: None,
+                cpu: cpus.remove(&id),
+                load_avg: load_avgs.remove(&id).map(to_load_average),
+                memory: memories.remove(&id).map(to_memory),
+                swap: swaps.remove(&id).map(to_swap),
+                battery_life: batteries.remove(&id).map(to_battery_life),
+                mounts: mounts
+                    .remove(&id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(to_filesystem)
+                    .collect(),
+                networks: HashMap::new(),
+                net_stats: net_stats
+                    .remove(&id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(name, netstat)| (name, to_network_statistics(netstat)))
+                    .collect(),
+                socket_stats: socket_stats.remove(&id).map(to_socket_statistics),
+                processes: None,
+                disk_io: disk_io
+                    .remove(&id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(to_disk_io)
+                    .collect(),
+                thermals: to_thermals(
+                    temperatures.remove(&id).unwrap_or_default(),
+                    fans.remove(&id).unwrap_or_default(),
+                ),
+                checks: checks
+                    .remove(&id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(to_check)
+                    .collect(),
+                extra: BTreeMap::new(),
+                uptime: Duration::from_secs(system_info.uptime as u64),
+                boot_time: system_info.boot_time,
+                collected_at: Some(system_info.create_date),
+                sequence: system_info.sequence.map(|x| x as u64),
+            }
+        })
+        .collect())
+}
+
+fn group_by<T>(models: Vec<T>, key: impl Fn(&T) -> i32) -> HashMap<i32, Vec<T>> {
+    let mut groups: HashMap<i32, Vec<T>> = HashMap::new();
+
+    for model in models {
+        groups.entry(key(&model)).or_default().push(model);
+    }
+
+    groups
+}
+
+fn to_load_average(l_avg: load_averages::LoadAverage) -> monitoring_core::models::LoadAverage {
+    monitoring_core::models::LoadAverage {
+        one: l_avg.one,
+        five: l_avg.five,
+        fifteen: l_avg.fifteen,
+    }
+}
+
+fn to_memory(mem: memory_infos::MemoryInfo) -> monitoring_core::models::Memory {
+    monitoring_core::models::Memory {
+        total: mem.total as u64,
+        free: mem.free as u64,
+        available: mem.available as u64,
+        buffers: mem.buffers as u64,
+        cached: mem.cached as u64,
+        shared: mem.shared as u64,
+        slab: mem.slab as u64,
+        dirty: mem.dirty as u64,
+        writeback: mem.writeback as u64,
+        hugepages_total: mem.hugepages_total as u64,
+        hugepages_free: mem.hugepages_free as u64,
+    }
+}
+
+fn to_swap(swap: swap_infos::SwapInfo) -> monitoring_core::models::Swap {
+    monitoring_core::models::Swap {
+        total: swap.total as u64,
+        free: swap.free as u64,
+    }
+}
+
+fn to_battery_life(battery: battery_lifes::BatteryLife) -> monitoring_core::models::BatteryLife {
+    monitoring_core::models::BatteryLife {
+        remaining_capacity: battery.remaining_capacity,
+        remaining_time: Duration::from_secs(battery.remaining_time as u64),
+    }
+}
+
+fn to_filesystem(mount: filesystem_infos::FilesystemInfo) -> monitoring_core::models::Filesystem {
+    monitoring_core::models::Filesystem {
+        files: mount.files as usize,
+        files_total: mount.files_total as usize,
+        files_avail: mount.files_avail as usize,
+        free: mount.free as u64,
+        avail: mount.avail as u64,
+        total: mount.total as u64,
+        name_max: mount.name_max as usize,
+        fs_type: mount.fs_type,
+        fs_mounted_from: mount.fs_mounted_from,
+        fs_mounted_on: mount.fs_mounted_on,
+    }
+}
+
+fn to_socket_statistics(
+    sock: socket_statistics::SocketStatistic,
+) -> monitoring_core::models::SocketStatistics {
+    monitoring_core::models::SocketStatistics {
+        tcp_sockets_in_use: sock.tcp_sockets_in_use as usize,
+        tcp_sockets_orphaned: sock.tcp_sockets_orphaned as usize,
+        udp_sockets_in_use: sock.udp_sockets_in_use as usize,
+        tcp6_sockets_in_use: sock.tcp6_sockets_in_use as usize,
+        udp6_sockets_in_use: sock.udp6_sockets_in_use as usize,
+    }
+}
+
+fn to_disk_io(disk: DiskIoInfo) -> monitoring_core::models::DiskIo {
+    monitoring_core::models::DiskIo {
+        name: disk.name,
+        read_bytes: disk.read_bytes,
+        write_bytes: disk.write_bytes,
+        read_ops: disk.read_ops,
+        write_ops: disk.write_ops,
+        in_flight: disk.in_flight as u64,
+        io_time: disk.io_time,
+    }
+}
+
+async fn load_process_data(
+    id_system_info: i32,
+    db: &mut PoolConnection<Postgres>,
+) -> Result<Option<monitoring_core::models::Processes>, sqlx::Error> {
+    let models = ProcessInfo::get_all_by_system_information(&mut *db, id_system_info).await?;
+
+    if models.is_empty() {
+        return Ok(None);
+    }
+
+    let mut processes = monitoring_core::models::Processes {
+        by_cpu: Vec::new(),
+        by_memory: Vec::new(),
+    };
+
+    for model in models {
+        let ranking = if model.ranking == process_infos::RANKING_CPU {
+            &mut processes.by_cpu
+        } else {
+            &mut processes.by_memory
+        };
+
+        ranking.push(monitoring_core::models::Process {
+            pid: model.pid as u32,
+            name: model.name,
+            cmdline: model.cmdline,
+            user: model.username,
+            state: model.state,
+            threads: model.threads as u64,
+            open_fds: model.open_fds.map(|x| x as u64),
+            cpu_usage: model.cpu_usage,
+            rss: model.rss as u64,
+        });
+    }
+
+    Ok(Some(processes))
+}
+
+async fn load_check_data(
+    id_system_info: i32,
+    db: &mut PoolConnection<Postgres>,
+) -> Result<Vec<monitoring_core::models::Check>, sqlx::Error> {
+    Ok(
+        CheckResult::get_all_by_system_information(&mut *db, id_system_info)
+            .await?
+            .into_iter()
+            .filter_map(to_check)
+            .collect(),
+    )
+}
+
+fn to_check(check: CheckResult) -> Option<monitoring_core::models::Check> {
+    let (status, perfdata) = match (check.status.parse(), serde_json::from_value(check.perfdata)) {
+        (Ok(status), Ok(perfdata)) => (status, perfdata),
+        _ => {
+            error!("Check result '{}' is invalid.", check.id_check_result);
+
+            return None;
+        }
+    };
+
+    Some(monitoring_core::models::Check {
+        name: check.name,
+        status,
+        output: check.output,
+        perfdata,
+        executed_at: check.executed_at,
+        duration: check.duration,
+    })
+}
+
+async fn load_thermal_data(
+    id_system_info: i32,
+    db: &mut PoolConnection<Postgres>,
+) -> Result<Option<monitoring_core::models::Thermals>, sqlx::Error> {
+    let temperatures =
+        TemperatureInfo::get_all_by_system_information(&mut *db, id_system_info).await?;
+    let fans = FanInfo::get_all_by_system_information(&mut *db, id_system_info).await?;
+
+    Ok(to_thermals(temperatures, fans))
+}
+
+fn to_thermals(
+    temperatures: Vec<TemperatureInfo>,
+    fans: Vec<FanInfo>,
+) -> Option<monitoring_core::models::Thermals> {
+    if temperatures.is_empty() && fans.is_empty() {
+        return None;
+    }
+
+    Some(monitoring_core::models::Thermals {
+        temperatures: temperatures
+            .into_iter()
+            .map(|sensor| monitoring_core::models::TemperatureSensor {
+                chip: sensor.chip,
+                label: sensor.label,
+                current: sensor.current,
+                high: sensor.high,
+                critical: sensor.critical,
+            })
+            .collect(),
+        fans: fans
+            .into_iter()
+            .map(|fan| monitoring_core::models::Fan {
+                chip: fan.chip,
+                label: fan.label,
+                rpm: fan.rpm.map(|x| x as u32),
+            })
+            .collect(),
+    })
+}
+
+async fn load_cpu_data(
+    id_system_info: i32,
+    db: &mut PoolConnection<Postgres>,
+) -> Result<Option<monitoring_core::models::CpuInformation>, sqlx::Error> {
+    let cpu_info =
+        match cpu_informations::CpuInformation::get_by_system_information(&mut *db, id_system_info)
+            .await?
+        {
+            Some(cpu_info) => cpu_info,
+            None => return Ok(None),
+        };
+
+    let aggregate_load = match cpu_loads::CpuLoad::get(&mut *db, cpu_info.aggregate_load_id).await?
+    {
+        Some(load) => load,
+        None => return Err(sqlx::Error::RowNotFound),
+    };
+
+    let loads = cpu_loads::CpuLoad::get_core_loads(&mut *db, cpu_info.id_cpu_information)
+        .await?
+        .into_iter()
+        .map(to_cpu_load)
+        .collect();
+
+    Ok(Some(monitoring_core::models::CpuInformation {
+        temperature: cpu_info.temperature,
+        loads,
+        aggregate_load: to_cpu_load(aggregate_load),
+    }))
+}
+
+/// Loads the cpu data of several snapshots by their id.
+async fn load_cpu_datas(
+    system_information_ids: &[i32],
+    db: &mut PoolConnection<Postgres>,
+) -> Result<HashMap<i32, monitoring_core::models::CpuInformation>, sqlx::Error> {
+    let cpu_infos = cpu_informations::CpuInformation::get_all_by_system_informations(
+        &mut *db,
+        system_information_ids,
+    )
+    .await?;
+
+    let aggregate_ids: Vec<i32> = cpu_infos.iter().map(|x| x.aggregate_load_id).collect();
+    let mut aggregate_loads: HashMap<i32, _> =
+        cpu_loads::CpuLoad::get_all(&mut *db, &aggregate_ids)
+            .await?
+            .into_iter()
+            .map(|load| (load.id_cpu_load, load))
+            .collect();
+
+    let cpu_ids: Vec<i32> = cpu_infos.iter().map(|x| x.id_cpu_information).collect();
+    let mut core_loads = group_by(
+        cpu_loads::CpuLoad::get_core_loads_by_cpu_informations(&mut *db, &cpu_ids).await?,
+        |(cpu_information_id, _)| *cpu_information_id,
+    );
+
+    let mut cpus = HashMap::new();
+
+    for cpu_info in cpu_infos {
+        let aggregate_load = match aggregate_loads.remove(&cpu_info.aggregate_load_id) {
+            Some(load) => load,
+            None => return Err(sqlx::Error::RowNotFound),
+        };
+
+        let loads = core_loads
+            .remove(&cpu_info.id_cpu_information)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(_, load)| to_cpu_load(load))
+            .collect();
+
+        cpus.insert(
+            cpu_info.system_information_id,
+            monitoring_core::models::CpuInformation {
+                temperature: cpu_info.temperature,
+                loads,
+                aggregate_load: to_cpu_load(aggregate_load),
+            },
+        );
+    }
+
+    Ok(cpus)
+}
+
+fn to_cpu_load(load: cpu_loads::CpuLoad) -> monitoring_core::models::CpuLoad {
+    monitoring_core::models::CpuLoad {
+        user: load.user,
+        nice: load.nice,
+        system: load.system,
+        interrupt: load.interrupt,
+        idle: load.idle,
+    }
+}
+
+#[allow(clippy::type_complexity)]
+async fn load_network_data(
+    id_system_info: i32,
+    db: &mut PoolConnection<Postgres>,
+) -> Result<
+    (
+        HashMap<String, monitoring_core::models::Network>,
+        HashMap<String, monitoring_core::models::NetworkStatistics>,
+    ),
+    sqlx::Error,
+> {
+    let mut networks = HashMap::new();
+    let mut net_stats = HashMap::new();
+
+    for network in
+        networks::Network::get_all_by_system_information(&mut *db, id_system_info).await?
+    {
+        let addrs =
+            network_addresses::NetworkAddress::get_all_by_network(&mut *db, network.id_network)
+                .await?
+                .into_iter()
+                .map(|addr| monitoring_core::models::NetworkAddress {
+                    addr: string_to_ip(&addr.address),
+                    netmask: string_to_ip(&addr.netmask),
+                })
+                .collect();
+
+        if let Some(netstat) =
+            network_statistics::NetworkStatistic::get_by_network(&mut *db, network.id_network)
+                .await?
+        {
+            net_stats.insert(network.name.clone(), to_network_statistics(netstat));
+        }
+
+        networks.insert(
+            network.name.clone(),
+            monitoring_core::models::Network {
+                name: network.name,
+                addrs,
+            },
+        );
+    }
+
+    Ok((networks, net_stats))
+}
+
+fn to_network_statistics(
+    netstat: network_statistics::NetworkStatistic,
+) -> monitoring_core::models::NetworkStatistics {
+    monitoring_core::models::NetworkStatistics {
+        rx_bytes: netstat.rx_bytes as u64,
+        tx_bytes: netstat.tx_bytes as u64,
+        rx_packets: netstat.rx_packages as u64,
+        tx_packets: netstat.tx_packages as u64,
+        rx_errors: netstat.rx_errors as u64,
+        tx_errors: netstat.tx_errors as u64,
+    }
+}
+
+fn string_to_ip(ip: &str) -> IpAddress {
+    match ip {
+        "" => IpAddress::Empty,
+        "Unsupported" => IpAddress::Unsupported,
+        ip => match ip.parse::<IpAddr>() {
+            Ok(IpAddr::V4(v4)) => IpAddress::V4(v4),
+            Ok(IpAddr::V6(v6)) => IpAddress::V6(v6),
+            Err(_) => IpAddress::Unsupported,
+        },
+    }
+}
+
+/// Rebuilds the os info from its display strings, for snapshots stored without the serialized info.
+fn to_os_info(os: os_infos::OsInfo) -> Info {
+    let os_type = match os.os_type.as_str() {
+        "Alpaquita Linux" => Type::Alpaquita,
+        "Alpine Linux" => Type::Alpine,
+        "Amazon Linux AMI" => Type::Amazon,
+        "Arch Linux" => Type::Arch,
+        "Artix Linux" => Type::Artix,
+        "DragonFly BSD" => Type::DragonFly,
+        "Garuda Linux" => Type::Garuda,
+        "Gentoo Linux" => Type::Gentoo,
+        "illumos" => Type::Illumos,
+        "Mac OS" => Type::Macos,
+        "Midnight BSD" => Type::MidnightBSD,
+        "Linux Mint" => Type::Mint,
+        "Pop!_OS" => Type::Pop,
+        "Raspberry Pi OS" => Type::Raspbian,
+        "Red Hat Linux" => Type::Redhat,
+        "Red Hat Enterprise Linux" => Type::RedHatEnterprise,
+        "SUSE Linux Enterprise Server" => Type::SUSE,
+        other => serde_json::from_value(serde_json::Value::from(other)).unwrap_or(Type::Unknown),
+    };
+
+    let version = match os.version.as_str() {
+        "Unknown" => Version::Unknown,
+        "Rolling Release" => Version::Rolling(None),
+        version => match version
+            .strip_prefix("Rolling Release (")
+            .and_then(|date| date.strip_suffix(')'))
+        {
+            Some(date) => Version::Rolling(Some(String::from(date))),
+            None => Version::from_string(version),
+        },
+    };
+
+    let bitness = match os.bitness.as_str() {
+        "32-bit" => Bitness::X32,
+        "64-bit" => Bitness::X64,
+        _ => Bitness::Unknown,
+    };
+
+    let info = serde_json::json!({
+        "os_type": os_type,
+        "version": version,
+        "edition": os.edition,
+        "codename": os.codename,
+        "bitness": bitness,
+        "architecture": os.architecture,
+    });
+
+    serde_json::from_value(info).unwrap_or_else(|_| Info::with_type(os_type))
+}
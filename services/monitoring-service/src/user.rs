@@ -0,0 +1,470 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::{NaiveDateTime, Utc};
+use monitoring_core::api::models::{
+    self as api, ApiTokenInfo, InsertApiToken, InsertUser, NewApiToken, Scope,
+};
+use rocket::{
+    delete, error, fairing::AdHoc, get, http::Status, info, post, serde::json::Json, tokio::fs,
+    tokio::io::AsyncWriteExt, warn,
+};
+use rocket_db_pools::{Connection, Database};
+use service_lib::{
+    auth::{Admin, Auth},
+    credentials::{generate_token, hash_token},
+    database::MonitoringDb,
+    models::{api_tokens::ApiToken, device_profiles::DeviceProfile, users::User},
+};
+use sqlx::{Connection as _, PgConnection, PgPool};
+
+/// Name of the user created on the first start.
+const ADMIN_USER: &str = "admin";
+
+#[get("/users")]
+pub async fn get_users(
+    _auth: Auth<Admin>,
+    mut db: Connection<MonitoringDb>,
+) -> Result<Json<Vec<api::User>>, Status> {
+    match User::get_all(&mut db).await {
+        Ok(users) => Ok(Json(users.into_iter().map(to_api_user).collect())),
+        Err(why) => {
+            error!("Failed to get users from db: {why}");
+
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+#[post("/users", data = "<user>")]
+pub async fn save_user(
+    _auth: Auth<Admin>,
+    mut db: Connection<MonitoringDb>,
+    user: Json<InsertUser>,
+) -> Result<Json<api::User>, Status> {
+    if user.name.trim().is_empty() {
+        return Err(Status::BadRequest);
+    }
+
+    match User::get_by_name(&mut db, &user.name).await {
+        Ok(None) => {}
+        Ok(Some(_)) => return Err(Status::Conflict),
+        Err(why) => {
+            error!("Failed to get user '{}': {why}", user.name);
+
+            return Err(Status::InternalServerError);
+        }
+    }
+
+    if let Some(discord_id) = user.discord_id {
+        match User::get_by_discord_id(&mut db, discord_id).await {
+            Ok(None) => {}
+            Ok(Some(_)) => return Err(Status::Conflict),
+            Err(why) => {
+                error!("Failed to get user of discord user '{discord_id}': {why}");
+
+                return Err(Status::InternalServerError);
+            }
+        }
+    }
+
+    match User::new(&user.name, user.discord_id, Utc::now().naive_utc())
+        .insert(&mut db)
+        .await
+    {
+        Ok(user) => Ok(Json(to_api_user(user))),
+        Err(why) => {
+            error!("Failed to create new user: {why}");
+
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+/// Creates a token for the user, the token itself is only part of this response.
+#[post("/users/<user_id>/tokens", data = "<token>")]
+pub async fn save_api_token(
+    _auth: Auth<Admin>,
+    mut db: Connection<MonitoringDb>,
+    user_id: u32,
+    token: Json<InsertApiToken>,
+) -> Result<Json<NewApiToken>, Status> {
+    // Admin tokens grant every profile, a restriction would be misleading.
+    if token.scopes.is_empty()
+        || (token.scopes.contains(&Scope::Admin) && token.profile_ids.is_some())
+    {
+        return Err(Status::BadRequest);
+    }
+
+    match User::get(&mut db, user_id as i32).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return Err(Status::NotFound),
+        Err(why) => {
+            error!("Failed to get user '{user_id}': {why}");
+
+            return Err(Status::InternalServerError);
+        }
+    }
+
+    for profile_id in token.profile_ids.iter().flatten() {
+        match DeviceProfile::get(&mut db, *profile_id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return Err(Status::BadRequest),
+            Err(why) => {
+                error!("Failed to get profile '{profile_id}': {why}");
+
+                return Err(Status::InternalServerError);
+            }
+        }
+    }
+
+    let plain = generate_token();
+    let api_token = ApiToken::new(
+        user_id as i32,
+        &token.name,
+        &hash_token(&plain),
+        &token.scopes,
+        token.profile_ids.clone(),
+        Utc::now().naive_utc(),
+        token.expire_date,
+    );
+
+    match api_token.insert(&mut db).await {
+        Ok(api_token) => {
+            info!(
+                "Created api token '{}' for user '{user_id}'.",
+                api_token.id_api_token
+            );
+
+            Ok(Json(NewApiToken {
+                id_api_token: api_token.id_api_token,
+                user_id: api_token.user_id,
+                token: plain,
+            }))
+        }
+        Err(why) => {
+            error!("Failed to create api token for user '{user_id}': {why}");
+
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+#[get("/users/<user_id>/tokens")]
+pub async fn get_api_tokens(
+    _auth: Auth<Admin>,
+    mut db: Connection<MonitoringDb>,
+    user_id: u32,
+) -> Result<Json<Vec<ApiTokenInfo>>, Status> {
+    match ApiToken::get_all_by_user(&mut db, user_id as i32).await {
+        Ok(tokens) => Ok(Json(tokens.into_iter().filter_map(to_api_token).collect())),
+        Err(why) => {
+            error!("Failed to get api tokens of user '{user_id}': {why}");
+
+            Err(Status::InternalServerError)
+        }
+    }
+}
+
+#[delete("/tokens/<token_id>")]
+pub async fn revoke_api_token(
+    _auth: Auth<Admin>,
+    mut db: Connection<MonitoringDb>,
+    token_id: u32,
+) -> Status {
+    match ApiToken::revoke(&mut db, token_id as i32, Utc::now().naive_utc()).await {
+        Ok(true) => Status::Ok,
+        Ok(false) => Status::NotFound,
+        Err(why) => {
+            error!("Failed to revoke api token '{token_id}': {why}");
+
+            Status::InternalServerError
+        }
+    }
+}
+
+/// Creates the admin user with an admin token if there is no valid admin token.
+/// The migrations already create users for the Discord ids of older data, so the users can't tell.
+/// The token is `admin_token` if given, otherwise a generated one which is written to
+/// `token_file`, readable only by the owner, so it never shows up in the logs.
+pub fn stage(admin_token: Option<String>, token_file: PathBuf) -> AdHoc {
+    AdHoc::try_on_ignite("Admin user", |rocket| async move {
+        let pool = match MonitoringDb::fetch(&rocket) {
+            Some(db) => PgPool::clone(db),
+            None => {
+                error!("Failed to get database for the admin user!");
+                return Err(rocket);
+            }
+        };
+
+        match create_admin(&pool, admin_token, &token_file).await {
+            Ok(false) => Ok(rocket),
+            Ok(true) => {
+                warn!(
+                    "Created an admin token for the user '{ADMIN_USER}', it was written to '{}', store it and delete the file!",
+                    token_file.display()
+                );
+                Ok(rocket)
+            }
+            Err(why) => {
+                error!("Failed to create the admin user: {why:#}");
+                Err(rocket)
+            }
+        }
+    })
+}
+
+/// Returns true if the admin token was generated.
+async fn create_admin(
+    pool: &PgPool,
+    admin_token: Option<String>,
+    token_file: &Path,
+) -> anyhow::Result<bool> {
+    let mut db = pool.acquire().await?;
+
+    let now = Utc::now().naive_utc();
+
+    if ApiToken::count_valid_admin(&mut db, now).await? > 0 {
+        return Ok(false);
+    }
+
+    let (token, generated) = match admin_token {
+        Some(token) => (token, false),
+        None => (generate_token(), true),
+    };
+
+    // The file is written first, a token nobody can read would lock everyone out.
+    if generated {
+        write_token_file(token_file, &token)
+            .await
+            .with_context(|| format!("Failed to write '{}'", token_file.display()))?;
+    }
+
+    if let Err(why) = insert_admin(&mut db, &token, now).await {
+        if generated {
+            let _ = fs::remove_file(token_file).await;
+        }
+
+        return Err(why.into());
+    }
+
+    info!("Created an admin token for the user '{ADMIN_USER}'.");
+
+    Ok(generated)
+}
+
+/// Writes the token to a new file only the owner can read, an existing file isn't replaced.
+async fn write_token_file(path: &Path, token: &str) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .await?;
+
+    file.write_all(format!("{token}\n").as_bytes()).await?;
+    file.sync_all().await
+}
+
+async fn insert_admin(
+    db: &mut PgConnection,
+    token: &str,
+    create_date: NaiveDateTime,
+) -> sqlx::Result<()> {
+    let mut tx = db.begin().await?;
+
+    // The admin user stays when its tokens are revoked, it only gets a new one then.
+    let user = match User::get_by_name(&mut tx, ADMIN_USER).await? {
+        Some(user) => user,
+        None => {
+            User::new(ADMIN_USER, None, create_date)
+                .insert(&mut tx)
+                .await?
+        }
+    };
+    ApiToken::new(
+        user.id_user,
+        ADMIN_USER,
+        &hash_token(token),
+        &[Scope::Admin],
+        None,
+        create_date,
+        None,
+    )
+    .insert(&mut tx)
+    .await?;
+
+    tx.commit().await
+}
+
+fn to_api_user(user: User) -> api::User {
+    api::User {
+        id_user: user.id_user,
+        name: user.name,
+        discord_id: user.discord_id,
+        create_date: user.create_date,
+    }
+}
+
+fn to_api_token(token: ApiToken) -> Option<ApiTokenInfo> {
+    let scopes = match token.scopes.iter().map(|x| x.parse()).collect() {
+        Ok(scopes) => scopes,
+        Err(_) => {
+            error!("Api token '{}' is invalid.", token.id_api_token);
+
+            return None;
+        }
+    };
+
+    Some(ApiTokenInfo {
+        id_api_token: token.id_api_token,
+        user_id: token.user_id,
+        name: token.name,
+        scopes,
+        profile_ids: token.profile_ids,
+        create_date: token.create_date,
+        expire_date: token.expire_date,
+        revoke_date: token.revoke_date,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs as std_fs, os::unix::fs::PermissionsExt};
+
+    use rocket::{error::ErrorKind, Ignite, Rocket};
+    use sqlx::{Connection as _, Executor};
+
+    use super::*;
+
+    /// Server the test databases are created on.
+    fn server_url() -> String {
+        env::var("MONITORING_TEST_DATABASE_URL")
+            .unwrap_or_else(|_| String::from("postgres://postgres@localhost"))
+    }
+
+    /// Creates an empty database with all migrations applied and returns its url.
+    async fn migrated_database(name: &str) -> String {
+        let mut server = PgConnection::connect(&format!("{}/postgres", server_url()))
+            .await
+            .unwrap();
+        server
+            .execute(format!("DROP DATABASE IF EXISTS {name};").as_str())
+            .await
+            .unwrap();
+        server
+            .execute(format!("CREATE DATABASE {name};").as_str())
+            .await
+            .unwrap();
+
+        let url = format!("{}/{name}", server_url());
+        let mut db = PgConnection::connect(&url).await.unwrap();
+
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../migrations");
+        let mut migrations: Vec<_> = std_fs::read_dir(dir)
+            .unwrap()
+            .map(|x| x.unwrap().path())
+            .collect();
+        migrations.sort();
+
+        for migration in migrations {
+            let sql = std_fs::read_to_string(&migration).unwrap();
+            db.execute(sql.as_str()).await.unwrap();
+        }
+
+        url
+    }
+
+    async fn drop_database(name: &str) {
+        let mut server = PgConnection::connect(&format!("{}/postgres", server_url()))
+            .await
+            .unwrap();
+        server
+            .execute(format!("DROP DATABASE IF EXISTS {name} WITH (FORCE);").as_str())
+            .await
+            .unwrap();
+    }
+
+    async fn ignite(url: &str, token_file: &Path) -> Result<Rocket<Ignite>, rocket::Error> {
+        let figment = rocket::Config::figment().merge(("databases.monitoring_db.url", url));
+
+        rocket::custom(figment)
+            .attach(MonitoringDb::init())
+            .attach(stage(None, token_file.to_path_buf()))
+            .ignite()
+            .await
+    }
+
+    async fn read_token(db: &mut PgConnection, token_file: &Path) -> Option<ApiToken> {
+        let token = std_fs::read_to_string(token_file).unwrap();
+
+        ApiToken::get_valid_by_hash(db, &hash_token(token.trim()), Utc::now().naive_utc())
+            .await
+            .unwrap()
+    }
+
+    #[rocket::async_test]
+    async fn stage_creates_an_admin_token_after_the_migrations() {
+        let name = "monitoring_test_admin_stage";
+        let url = migrated_database(name).await;
+        let dir = tempfile::tempdir().unwrap();
+        let token_file = dir.path().join("admin-token");
+        let mut db = PgConnection::connect(&url).await.unwrap();
+
+        // The migrations create users for the Discord ids of the test profile.
+        assert!(!User::get_all(&mut db).await.unwrap().is_empty());
+
+        ignite(&url, &token_file).await.unwrap();
+
+        let token = read_token(&mut db, &token_file).await.unwrap();
+        assert!(token.has_scope(Scope::Admin));
+        assert_eq!(
+            std_fs::metadata(&token_file).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+
+        // A valid admin token exists, no other one is created.
+        std_fs::remove_file(&token_file).unwrap();
+        ignite(&url, &token_file).await.unwrap();
+        assert!(!token_file.exists());
+
+        // The admin user gets a new token once its token is revoked.
+        ApiToken::revoke(&mut db, token.id_api_token, Utc::now().naive_utc())
+            .await
+            .unwrap();
+        ignite(&url, &token_file).await.unwrap();
+
+        let renewed = read_token(&mut db, &token_file).await.unwrap();
+        assert_eq!(renewed.user_id, token.user_id);
+
+        db.close().await.unwrap();
+        drop_database(name).await;
+    }
+
+    #[rocket::async_test]
+    async fn stage_keeps_an_existing_token_file() {
+        let name = "monitoring_test_admin_file";
+        let url = migrated_database(name).await;
+        let dir = tempfile::tempdir().unwrap();
+        let token_file = dir.path().join("admin-token");
+        std_fs::write(&token_file, "old").unwrap();
+
+        match ignite(&url, &token_file).await {
+            Ok(_) => panic!("The stage replaced the token file."),
+            Err(why) => assert!(matches!(why.kind(), ErrorKind::FailedFairings(_))),
+        }
+        assert_eq!(std_fs::read_to_string(&token_file).unwrap(), "old");
+
+        let mut db = PgConnection::connect(&url).await.unwrap();
+        assert_eq!(
+            ApiToken::count_valid_admin(&mut db, Utc::now().naive_utc())
+                .await
+                .unwrap(),
+            0
+        );
+
+        db.close().await.unwrap();
+        drop_database(name).await;
+    }
+}
@@ -0,0 +1,123 @@
+use std::marker::PhantomData;
+
+use chrono::Utc;
+use monitoring_core::api::models::Scope;
+use rocket::{
+    error,
+    http::Status,
+    request::{FromRequest, Outcome},
+    Request,
+};
+use rocket_db_pools::Connection;
+
+use crate::{credentials::hash_token, database::MonitoringDb, models::api_tokens::ApiToken};
+
+/// An api token from the `Authorization: Bearer <token>` header which grants the scope `S`.
+/// Routes of a single profile still have to check [`Auth::allows_profile`].
+pub struct Auth<S> {
+    pub token: ApiToken,
+    scope: PhantomData<S>,
+}
+
+/// The scope an [`Auth`] guard requires.
+pub trait RequiredScope {
+    const SCOPE: Scope;
+}
+
+pub struct ProfilesRead;
+pub struct ProfilesWrite;
+pub struct MetricsRead;
+pub struct AlertsManage;
+pub struct ErrorsManage;
+pub struct Admin;
+
+impl RequiredScope for ProfilesRead {
+    const SCOPE: Scope = Scope::ProfilesRead;
+}
+
+impl RequiredScope for ProfilesWrite {
+    const SCOPE: Scope = Scope::ProfilesWrite;
+}
+
+impl RequiredScope for MetricsRead {
+    const SCOPE: Scope = Scope::MetricsRead;
+}
+
+impl RequiredScope for AlertsManage {
+    const SCOPE: Scope = Scope::AlertsManage;
+}
+
+impl RequiredScope for ErrorsManage {
+    const SCOPE: Scope = Scope::ErrorsManage;
+}
+
+impl RequiredScope for Admin {
+    const SCOPE: Scope = Scope::Admin;
+}
+
+#[derive(Debug)]
+pub enum AuthError {
+    MissingToken,
+    InvalidToken,
+    MissingScope,
+    DbAccessError,
+}
+
+impl<S> Auth<S> {
+    /// The id of the user of the token, recorded as the user of changes.
+    pub fn user_id(&self) -> i64 {
+        i64::from(self.token.user_id)
+    }
+
+    pub fn allows_profile(&self, profile_id: i32) -> bool {
+        self.token.allows_profile(profile_id)
+    }
+
+    /// Returns true if the token is restricted to some profiles.
+    pub fn is_restricted(&self) -> bool {
+        self.token.profile_ids.is_some()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, S: RequiredScope + Send> FromRequest<'r> for Auth<S> {
+    type Error = AuthError;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let token = match req
+            .headers()
+            .get_one("Authorization")
+            .and_then(|x| x.strip_prefix("Bearer "))
+        {
+            Some(token) => token,
+            None => return Outcome::Failure((Status::Unauthorized, AuthError::MissingToken)),
+        };
+
+        let mut conn = match req.guard::<Connection<MonitoringDb>>().await {
+            rocket::outcome::Outcome::Success(s) => s,
+            _ => {
+                error!("Failed to get database!");
+                return Outcome::Failure((Status::InternalServerError, AuthError::DbAccessError));
+            }
+        };
+
+        let now = Utc::now().naive_utc();
+        let token = match ApiToken::get_valid_by_hash(&mut conn, &hash_token(token), now).await {
+            Ok(Some(token)) => token,
+            Ok(None) => return Outcome::Failure((Status::Unauthorized, AuthError::InvalidToken)),
+            Err(why) => {
+                error!("Failed to get api token: {why}");
+                return Outcome::Failure((Status::InternalServerError, AuthError::DbAccessError));
+            }
+        };
+
+        if !token.has_scope(S::SCOPE) {
+            return Outcome::Failure((Status::Forbidden, AuthError::MissingScope));
+        }
+
+        Outcome::Success(Auth {
+            token,
+            scope: PhantomData,
+        })
+    }
+}
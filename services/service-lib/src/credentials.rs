@@ -0,0 +1,53 @@
+use argon2::{
+    password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+/// Random bytes of a generated profile key or api token.
+const KEY_LENGTH: usize = 32;
+/// Prefix of the api tokens, so leaked tokens can be recognized.
+const TOKEN_PREFIX: &str = "mt_";
+
+/// Generates a random profile key, hex encoded.
+pub fn generate_key() -> String {
+    let mut bytes = [0u8; KEY_LENGTH];
+    OsRng.fill_bytes(&mut bytes);
+
+    bytes.iter().map(|x| format!("{x:02x}")).collect()
+}
+
+/// Hashes a key with argon2 and a random salt into a PHC string.
+pub fn hash_key(key: &str) -> password_hash::Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Ok(Argon2::default()
+        .hash_password(key.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Returns true if `key` matches the PHC string `hash`, invalid hashes never match.
+/// This is slow on purpose, call it from a blocking task.
+pub fn verify_key(key: &str, hash: &str) -> bool {
+    match PasswordHash::new(hash) {
+        Ok(hash) => Argon2::default()
+            .verify_password(key.as_bytes(), &hash)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Generates a random api token.
+pub fn generate_token() -> String {
+    format!("{TOKEN_PREFIX}{}", generate_key())
+}
+
+/// Hashes an api token with sha256, hex encoded.
+/// Tokens are random unlike passwords, so a fast hash is enough and lets the hash be looked up.
+pub fn hash_token(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|x| format!("{x:02x}"))
+        .collect()
+}
@@ -1,5 +1,5 @@
-pub mod api_key;
+pub mod auth;
+pub mod credentials;
 pub mod database;
 pub mod models;
 pub mod profile_key;
-pub mod read_key;
@@ -0,0 +1,130 @@
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, Postgres, Row};
+
+#[derive(Debug, Clone)]
+pub struct AlertRule {
+    pub id_alert_rule: i32,
+    pub device_profile_id: i32,
+    pub metric: String,
+    pub target: Option<String>,
+    pub comparison: String,
+    pub threshold: f64,
+    pub duration: i64,
+    pub create_user: i64,
+    pub create_date: NaiveDateTime,
+}
+
+impl AlertRule {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        device_profile_id: i32,
+        metric: &str,
+        target: Option<String>,
+        comparison: &str,
+        threshold: f64,
+        duration: i64,
+        create_user: i64,
+        create_date: NaiveDateTime,
+    ) -> Self {
+        Self {
+            id_alert_rule: 0,
+            device_profile_id,
+            metric: String::from(metric),
+            target,
+            comparison: String::from(comparison),
+            threshold,
+            duration,
+            create_user,
+            create_date,
+        }
+    }
+
+    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+        let row: (i32,) = sqlx::query_as(
+            "INSERT INTO alert_rules 
+            (device_profile_id, metric, target, comparison, threshold, duration, create_user, create_date) 
+            VALUES 
+            ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id_alert_rule;",
+        )
+        .bind(self.device_profile_id)
+        .bind(self.metric.clone())
+        .bind(self.target.clone())
+        .bind(self.comparison.clone())
+        .bind(self.threshold)
+        .bind(self.duration)
+        .bind(self.create_user)
+        .bind(self.create_date)
+        .fetch_one(db)
+        .await?;
+
+        Ok(Self {
+            id_alert_rule: row.0,
+            ..self
+        })
+    }
+
+    pub async fn get(db: &mut PoolConnection<Postgres>, id: i32) -> sqlx::Result<Option<Self>> {
+        let row = sqlx::query("SELECT * FROM alert_rules WHERE id_alert_rule = $1;")
+            .bind(id)
+            .fetch_optional(db)
+            .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    pub async fn get_all(db: &mut PoolConnection<Postgres>) -> sqlx::Result<Vec<Self>> {
+        sqlx::query("SELECT * FROM alert_rules ORDER BY id_alert_rule;")
+            .fetch_all(db)
+            .await?
+            .iter()
+            .map(Self::from_row)
+            .collect()
+    }
+
+    pub async fn get_all_by_profile(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * FROM alert_rules WHERE device_profile_id = $1 ORDER BY id_alert_rule;",
+        )
+        .bind(device_profile_id)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn delete(db: &mut PoolConnection<Postgres>, id: i32) -> sqlx::Result<bool> {
+        let result = sqlx::query("DELETE FROM alert_rules WHERE id_alert_rule = $1;")
+            .bind(id)
+            .execute(db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Self::from_row_offset(row, 0)
+    }
+
+    /// Reads the rule from a joined row where its columns start at `offset`.
+    pub(crate) fn from_row_offset(row: &PgRow, offset: usize) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_alert_rule: row.try_get(offset)?,
+            device_profile_id: row.try_get(offset + 1)?,
+            metric: row.try_get(offset + 2)?,
+            target: row.try_get(offset + 3)?,
+            comparison: row.try_get(offset + 4)?,
+            threshold: row.try_get(offset + 5)?,
+            duration: row.try_get(offset + 6)?,
+            create_user: row.try_get(offset + 7)?,
+            create_date: row.try_get(offset + 8)?,
+        })
+    }
+}
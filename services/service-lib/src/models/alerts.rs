@@ -0,0 +1,135 @@
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, Postgres, Row};
+
+use super::alert_rules::AlertRule;
+
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub id_alert: i32,
+    pub alert_rule_id: i32,
+    pub value: f64,
+    pub fired_date: NaiveDateTime,
+    pub resolved_date: Option<NaiveDateTime>,
+    pub resolved_value: Option<f64>,
+}
+
+impl Alert {
+    pub fn new(alert_rule_id: i32, value: f64, fired_date: NaiveDateTime) -> Self {
+        Self {
+            id_alert: 0,
+            alert_rule_id,
+            value,
+            fired_date,
+            resolved_date: None,
+            resolved_value: None,
+        }
+    }
+
+    /// Inserts the alert if its rule has no firing alert, `None` if another evaluation was faster.
+    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Option<Self>> {
+        let row: Option<(i32,)> = sqlx::query_as(
+            "INSERT INTO alerts 
+            (alert_rule_id, value, fired_date) 
+            VALUES 
+            ($1, $2, $3) 
+            ON CONFLICT (alert_rule_id) WHERE resolved_date IS NULL DO NOTHING 
+            RETURNING id_alert;",
+        )
+        .bind(self.alert_rule_id)
+        .bind(self.value)
+        .bind(self.fired_date)
+        .fetch_optional(db)
+        .await?;
+
+        Ok(row.map(|row| Self {
+            id_alert: row.0,
+            ..self
+        }))
+    }
+
+    pub async fn resolve(
+        self,
+        db: &mut PoolConnection<Postgres>,
+        resolved_date: NaiveDateTime,
+        resolved_value: f64,
+    ) -> sqlx::Result<Self> {
+        sqlx::query(
+            "UPDATE alerts SET resolved_date = $1, resolved_value = $2 
+            WHERE id_alert = $3 AND resolved_date IS NULL;",
+        )
+        .bind(resolved_date)
+        .bind(resolved_value)
+        .bind(self.id_alert)
+        .execute(db)
+        .await?;
+
+        Ok(Self {
+            resolved_date: Some(resolved_date),
+            resolved_value: Some(resolved_value),
+            ..self
+        })
+    }
+
+    /// Returns the alert of the rule which is still firing.
+    pub async fn get_active_by_rule(
+        db: &mut PoolConnection<Postgres>,
+        alert_rule_id: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        let row =
+            sqlx::query("SELECT * FROM alerts WHERE alert_rule_id = $1 AND resolved_date IS NULL;")
+                .bind(alert_rule_id)
+                .fetch_optional(db)
+                .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the alerts together with their rule and the name of the device.
+    /// `changed_since` keeps the alerts which fired or resolved at or after it.
+    pub async fn get_all_detailed(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: Option<i32>,
+        active_only: bool,
+        changed_since: Option<NaiveDateTime>,
+    ) -> sqlx::Result<Vec<(Self, AlertRule, String)>> {
+        sqlx::query(
+            "SELECT a.*, r.*, p.device_name 
+            FROM alerts a 
+            INNER JOIN alert_rules r ON r.id_alert_rule = a.alert_rule_id 
+            INNER JOIN device_profiles p ON p.id_device_profile = r.device_profile_id 
+            WHERE ($1::INT IS NULL OR r.device_profile_id = $1) 
+            AND (NOT $2 OR a.resolved_date IS NULL) 
+            AND ($3::TIMESTAMP IS NULL OR a.fired_date >= $3 OR a.resolved_date >= $3) 
+            ORDER BY a.fired_date DESC;",
+        )
+        .bind(device_profile_id)
+        .bind(active_only)
+        .bind(changed_since)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| {
+            Ok((
+                Self::from_row(row)?,
+                AlertRule::from_row_offset(row, 6)?,
+                row.try_get("device_name")?,
+            ))
+        })
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_alert: row.try_get(0)?,
+            alert_rule_id: row.try_get(1)?,
+            value: row.try_get(2)?,
+            fired_date: row.try_get(3)?,
+            resolved_date: row.try_get(4)?,
+            resolved_value: row.try_get(5)?,
+        })
+    }
+}
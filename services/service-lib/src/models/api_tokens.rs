@@ -0,0 +1,168 @@
+use chrono::NaiveDateTime;
+use monitoring_core::api::models::Scope;
+use sqlx::{postgres::PgRow, PgConnection, Row};
+
+/// A hashed api token of a user with the scopes it grants.
+#[derive(Debug, Clone)]
+pub struct ApiToken {
+    pub id_api_token: i32,
+    pub user_id: i32,
+    pub name: String,
+    /// Sha256 hash of the token, hex encoded.
+    pub token_hash: String,
+    pub scopes: Vec<String>,
+    /// The profiles the token is restricted to, `None` allows every profile.
+    pub profile_ids: Option<Vec<i32>>,
+    pub create_date: NaiveDateTime,
+    pub expire_date: Option<NaiveDateTime>,
+    pub revoke_date: Option<NaiveDateTime>,
+}
+
+impl ApiToken {
+    pub fn new(
+        user_id: i32,
+        name: &str,
+        token_hash: &str,
+        scopes: &[Scope],
+        profile_ids: Option<Vec<i32>>,
+        create_date: NaiveDateTime,
+        expire_date: Option<NaiveDateTime>,
+    ) -> Self {
+        Self {
+            id_api_token: 0,
+            user_id,
+            name: String::from(name),
+            token_hash: String::from(token_hash),
+            scopes: scopes.iter().map(|x| String::from(x.as_str())).collect(),
+            profile_ids,
+            create_date,
+            expire_date,
+            revoke_date: None,
+        }
+    }
+
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
+        let row: (i32,) = sqlx::query_as(
+            "INSERT INTO api_tokens 
+            (user_id, name, token_hash, scopes, profile_ids, create_date, expire_date) 
+            VALUES 
+            ($1, $2, $3, $4, $5, $6, $7) RETURNING id_api_token;",
+        )
+        .bind(self.user_id)
+        .bind(self.name.clone())
+        .bind(self.token_hash.clone())
+        .bind(self.scopes.clone())
+        .bind(self.profile_ids.clone())
+        .bind(self.create_date)
+        .bind(self.expire_date)
+        .fetch_one(db)
+        .await?;
+
+        Ok(Self {
+            id_api_token: row.0,
+            ..self
+        })
+    }
+
+    pub async fn get(db: &mut PgConnection, id: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query("SELECT * FROM api_tokens WHERE id_api_token = $1;")
+            .bind(id)
+            .fetch_optional(db)
+            .await?
+            .map(|row| Self::from_row(&row))
+            .transpose()
+    }
+
+    /// Returns the token with the hash if it is neither expired nor revoked at `now`.
+    pub async fn get_valid_by_hash(
+        db: &mut PgConnection,
+        token_hash: &str,
+        now: NaiveDateTime,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query(
+            "SELECT * FROM api_tokens 
+            WHERE token_hash = $1 
+            AND revoke_date IS NULL 
+            AND (expire_date IS NULL OR expire_date > $2);",
+        )
+        .bind(token_hash)
+        .bind(now)
+        .fetch_optional(db)
+        .await?
+        .map(|row| Self::from_row(&row))
+        .transpose()
+    }
+
+    /// Returns the number of admin tokens which are neither expired nor revoked at `now`.
+    pub async fn count_valid_admin(db: &mut PgConnection, now: NaiveDateTime) -> sqlx::Result<i64> {
+        let row: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM api_tokens 
+            WHERE $1 = ANY(scopes) 
+            AND revoke_date IS NULL 
+            AND (expire_date IS NULL OR expire_date > $2);",
+        )
+        .bind(Scope::Admin.as_str())
+        .bind(now)
+        .fetch_one(db)
+        .await?;
+
+        Ok(row.0)
+    }
+
+    pub async fn get_all_by_user(db: &mut PgConnection, user_id: i32) -> sqlx::Result<Vec<Self>> {
+        sqlx::query("SELECT * FROM api_tokens WHERE user_id = $1 ORDER BY create_date DESC;")
+            .bind(user_id)
+            .fetch_all(db)
+            .await?
+            .iter()
+            .map(Self::from_row)
+            .collect()
+    }
+
+    /// Revokes the token, returns false if there is no such token or it was already revoked.
+    pub async fn revoke(
+        db: &mut PgConnection,
+        id_api_token: i32,
+        revoke_date: NaiveDateTime,
+    ) -> sqlx::Result<bool> {
+        let result = sqlx::query(
+            "UPDATE api_tokens SET revoke_date = $2 
+            WHERE id_api_token = $1 AND revoke_date IS NULL;",
+        )
+        .bind(id_api_token)
+        .bind(revoke_date)
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns true if the token grants the scope, admin tokens grant every scope.
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes
+            .iter()
+            .any(|x| x == scope.as_str() || x == Scope::Admin.as_str())
+    }
+
+    /// Returns true if the token isn't restricted to other profiles.
+    pub fn allows_profile(&self, profile_id: i32) -> bool {
+        match &self.profile_ids {
+            Some(profile_ids) => profile_ids.contains(&profile_id),
+            None => true,
+        }
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_api_token: row.try_get(0)?,
+            user_id: row.try_get(1)?,
+            name: row.try_get(2)?,
+            token_hash: row.try_get(3)?,
+            scopes: row.try_get(4)?,
+            profile_ids: row.try_get(5)?,
+            create_date: row.try_get(6)?,
+            expire_date: row.try_get(7)?,
+            revoke_date: row.try_get(8)?,
+        })
+    }
+}
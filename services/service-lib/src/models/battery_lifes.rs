@@ -1,4 +1,5 @@
-use sqlx::{pool::PoolConnection, Postgres};
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, Row};
 
 pub struct BatteryLife {
     pub id_battery_life: i32,
@@ -17,9 +18,9 @@ impl BatteryLife {
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
-            "INSERT INTO memory_infos 
+            "INSERT INTO battery_lifes 
             (system_information_id, remaining_capacity, remaining_time) 
             VALUES 
             ($1, $2, $3) RETURNING id_battery_life;",
@@ -37,4 +38,69 @@ impl BatteryLife {
             remaining_time: self.remaining_time,
         })
     }
+
+    pub async fn get_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        let row = sqlx::query("SELECT * FROM battery_lifes WHERE system_information_id = $1;")
+            .bind(system_information_id)
+            .fetch_optional(db)
+            .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    pub async fn get_all_by_system_informations(
+        db: &mut PoolConnection<Postgres>,
+        system_information_ids: &[i32],
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM battery_lifes 
+            WHERE system_information_id = ANY($1);",
+        )
+        .bind(system_information_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, Self)>> {
+        sqlx::query(
+            "SELECT b.*, s.create_date 
+            FROM battery_lifes b 
+            INNER JOIN system_informations s ON s.id_system_information = b.system_information_id 
+            WHERE s.device_profile_id = $1 AND s.create_date BETWEEN $2 AND $3 
+            ORDER BY s.create_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("create_date")?, Self::from_row(row)?)))
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_battery_life: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            remaining_capacity: row.try_get::<f64, _>(2)? as f32,
+            remaining_time: row.try_get(3)?,
+        })
+    }
 }
@@ -0,0 +1,150 @@
+use chrono::NaiveDateTime;
+use sqlx::{
+    pool::PoolConnection, postgres::PgRow, types::JsonValue, PgConnection, Postgres, QueryBuilder,
+    Row,
+};
+
+use super::reserve_ids;
+
+/// Result of a check command of the agent, the performance data is stored as sent.
+pub struct CheckResult {
+    pub id_check_result: i32,
+    pub system_information_id: i32,
+    pub name: String,
+    pub status: String,
+    pub output: String,
+    pub perfdata: JsonValue,
+    pub executed_at: NaiveDateTime,
+    pub duration: f64,
+}
+
+impl CheckResult {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        system_information_id: i32,
+        name: &str,
+        status: &str,
+        output: &str,
+        perfdata: JsonValue,
+        executed_at: NaiveDateTime,
+        duration: f64,
+    ) -> Self {
+        Self {
+            id_check_result: 0,
+            system_information_id,
+            name: String::from(name),
+            status: String::from(status),
+            output: String::from(output),
+            perfdata,
+            executed_at,
+            duration,
+        }
+    }
+
+    pub async fn insert_many(db: &mut PgConnection, checks: Vec<Self>) -> sqlx::Result<Vec<Self>> {
+        if checks.is_empty() {
+            return Ok(checks);
+        }
+
+        let ids = reserve_ids(&mut *db, "check_results", "id_check_result", checks.len()).await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO check_results 
+            (id_check_result, system_information_id, name, status, output, perfdata, executed_at, duration) ",
+        );
+
+        query_builder.push_values(checks.iter().zip(&ids), |mut b, (check, id)| {
+            b.push_bind(*id)
+                .push_bind(check.system_information_id)
+                .push_bind(check.name.clone())
+                .push_bind(check.status.clone())
+                .push_bind(check.output.clone())
+                .push_bind(check.perfdata.clone())
+                .push_bind(check.executed_at)
+                .push_bind(check.duration);
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(checks
+            .into_iter()
+            .zip(ids)
+            .map(|(check, id)| Self {
+                id_check_result: id,
+                ..check
+            })
+            .collect())
+    }
+
+    pub async fn get_all_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM check_results 
+            WHERE system_information_id = $1 
+            ORDER BY id_check_result;",
+        )
+        .bind(system_information_id)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_all_by_system_informations(
+        db: &mut PoolConnection<Postgres>,
+        system_information_ids: &[i32],
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM check_results 
+            WHERE system_information_id = ANY($1) 
+            ORDER BY id_check_result;",
+        )
+        .bind(system_information_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, Self)>> {
+        sqlx::query(
+            "SELECT c.*, s.create_date 
+            FROM check_results c 
+            INNER JOIN system_informations s ON s.id_system_information = c.system_information_id 
+            WHERE s.device_profile_id = $1 AND s.create_date BETWEEN $2 AND $3 
+            ORDER BY s.create_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("create_date")?, Self::from_row(row)?)))
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_check_result: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            name: row.try_get(2)?,
+            status: row.try_get(3)?,
+            output: row.try_get(4)?,
+            perfdata: row.try_get(5)?,
+            executed_at: row.try_get(6)?,
+            duration: row.try_get(7)?,
+        })
+    }
+}
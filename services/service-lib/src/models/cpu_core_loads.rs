@@ -1,4 +1,6 @@
-use sqlx::{pool::PoolConnection, Postgres};
+use sqlx::{PgConnection, Postgres, QueryBuilder};
+
+use super::reserve_ids;
 
 pub struct CpuCoreLoad {
     pub id_cpu_core_load: i32,
@@ -15,7 +17,7 @@ impl CpuCoreLoad {
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
             "INSERT INTO cpu_core_loads 
             (cpu_information_id, cpu_load_id) 
@@ -33,4 +35,42 @@ impl CpuCoreLoad {
             cpu_load_id: self.cpu_load_id,
         })
     }
+
+    pub async fn insert_many(
+        db: &mut PgConnection,
+        core_loads: Vec<Self>,
+    ) -> sqlx::Result<Vec<Self>> {
+        if core_loads.is_empty() {
+            return Ok(core_loads);
+        }
+
+        let ids = reserve_ids(
+            &mut *db,
+            "cpu_core_loads",
+            "id_cpu_core_load",
+            core_loads.len(),
+        )
+        .await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO cpu_core_loads (id_cpu_core_load, cpu_information_id, cpu_load_id) ",
+        );
+
+        query_builder.push_values(core_loads.iter().zip(&ids), |mut b, (core_load, id)| {
+            b.push_bind(*id)
+                .push_bind(core_load.cpu_information_id)
+                .push_bind(core_load.cpu_load_id);
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(core_loads
+            .into_iter()
+            .zip(ids)
+            .map(|(core_load, id)| Self {
+                id_cpu_core_load: id,
+                ..core_load
+            })
+            .collect())
+    }
 }
@@ -1,14 +1,19 @@
-use sqlx::{pool::PoolConnection, Postgres};
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, Row};
 
 pub struct CpuInformation {
     pub id_cpu_information: i32,
     pub system_information_id: i32,
-    pub temperature: f32,
+    pub temperature: Option<f32>,
     pub aggregate_load_id: i32,
 }
 
 impl CpuInformation {
-    pub fn new(system_information_id: i32, temperature: f32, aggregate_load_id: i32) -> Self {
+    pub fn new(
+        system_information_id: i32,
+        temperature: Option<f32>,
+        aggregate_load_id: i32,
+    ) -> Self {
         Self {
             id_cpu_information: 0,
             system_information_id,
@@ -17,7 +22,7 @@ impl CpuInformation {
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
             "INSERT INTO cpu_informations 
             (system_information_id, temperature, aggregate_load_id) 
@@ -37,4 +42,69 @@ impl CpuInformation {
             aggregate_load_id: self.aggregate_load_id,
         })
     }
+
+    pub async fn get_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        let row = sqlx::query("SELECT * FROM cpu_informations WHERE system_information_id = $1;")
+            .bind(system_information_id)
+            .fetch_optional(db)
+            .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    pub async fn get_all_by_system_informations(
+        db: &mut PoolConnection<Postgres>,
+        system_information_ids: &[i32],
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM cpu_informations 
+            WHERE system_information_id = ANY($1);",
+        )
+        .bind(system_information_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, Self)>> {
+        sqlx::query(
+            "SELECT c.*, s.create_date 
+            FROM cpu_informations c 
+            INNER JOIN system_informations s ON s.id_system_information = c.system_information_id 
+            WHERE s.device_profile_id = $1 AND s.create_date BETWEEN $2 AND $3 
+            ORDER BY s.create_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("create_date")?, Self::from_row(row)?)))
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_cpu_information: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            temperature: row.try_get::<Option<f64>, _>(2)?.map(|x| x as f32),
+            aggregate_load_id: row.try_get(3)?,
+        })
+    }
 }
@@ -1,4 +1,7 @@
-use sqlx::{pool::PoolConnection, Postgres};
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, QueryBuilder, Row};
+
+use super::reserve_ids;
 
 pub struct CpuLoad {
     pub id_cpu_load: i32,
@@ -21,7 +24,7 @@ impl CpuLoad {
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
             "INSERT INTO cpu_loads 
             (\"user\", nice, system, interrupt, idle) 
@@ -45,4 +48,137 @@ impl CpuLoad {
             idle: self.idle,
         })
     }
+
+    pub async fn insert_many(db: &mut PgConnection, loads: Vec<Self>) -> sqlx::Result<Vec<Self>> {
+        if loads.is_empty() {
+            return Ok(loads);
+        }
+
+        let ids = reserve_ids(&mut *db, "cpu_loads", "id_cpu_load", loads.len()).await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO cpu_loads (id_cpu_load, \"user\", nice, system, interrupt, idle) ",
+        );
+
+        query_builder.push_values(loads.iter().zip(&ids), |mut b, (load, id)| {
+            b.push_bind(*id)
+                .push_bind(load.user)
+                .push_bind(load.nice)
+                .push_bind(load.system)
+                .push_bind(load.interrupt)
+                .push_bind(load.idle);
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(loads
+            .into_iter()
+            .zip(ids)
+            .map(|(load, id)| Self {
+                id_cpu_load: id,
+                ..load
+            })
+            .collect())
+    }
+
+    pub async fn get(db: &mut PoolConnection<Postgres>, id: i32) -> sqlx::Result<Option<Self>> {
+        let row = sqlx::query("SELECT * FROM cpu_loads WHERE id_cpu_load = $1;")
+            .bind(id)
+            .fetch_optional(db)
+            .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    pub async fn get_core_loads(
+        db: &mut PoolConnection<Postgres>,
+        cpu_information_id: i32,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT l.* 
+            FROM cpu_loads l 
+            INNER JOIN cpu_core_loads c ON c.cpu_load_id = l.id_cpu_load 
+            WHERE c.cpu_information_id = $1 
+            ORDER BY c.id_cpu_core_load;",
+        )
+        .bind(cpu_information_id)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_all(
+        db: &mut PoolConnection<Postgres>,
+        ids: &[i32],
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query("SELECT * FROM cpu_loads WHERE id_cpu_load = ANY($1);")
+            .bind(ids)
+            .fetch_all(db)
+            .await?
+            .iter()
+            .map(Self::from_row)
+            .collect()
+    }
+
+    /// Returns the core loads of the cpu informations in core order, together with the id of
+    /// their cpu information.
+    pub async fn get_core_loads_by_cpu_informations(
+        db: &mut PoolConnection<Postgres>,
+        cpu_information_ids: &[i32],
+    ) -> sqlx::Result<Vec<(i32, Self)>> {
+        sqlx::query(
+            "SELECT l.*, c.cpu_information_id 
+            FROM cpu_loads l 
+            INNER JOIN cpu_core_loads c ON c.cpu_load_id = l.id_cpu_load 
+            WHERE c.cpu_information_id = ANY($1) 
+            ORDER BY c.id_cpu_core_load;",
+        )
+        .bind(cpu_information_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("cpu_information_id")?, Self::from_row(row)?)))
+        .collect()
+    }
+
+    pub async fn get_aggregate_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, Self)>> {
+        sqlx::query(
+            "SELECT l.*, s.create_date 
+            FROM cpu_loads l 
+            INNER JOIN cpu_informations c ON c.aggregate_load_id = l.id_cpu_load 
+            INNER JOIN system_informations s ON s.id_system_information = c.system_information_id 
+            WHERE s.device_profile_id = $1 AND s.create_date BETWEEN $2 AND $3 
+            ORDER BY s.create_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("create_date")?, Self::from_row(row)?)))
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_cpu_load: row.try_get(0)?,
+            user: row.try_get::<f64, _>(1)? as f32,
+            nice: row.try_get::<f64, _>(2)? as f32,
+            system: row.try_get::<f64, _>(3)? as f32,
+            interrupt: row.try_get::<f64, _>(4)? as f32,
+            idle: row.try_get::<f64, _>(5)? as f32,
+        })
+    }
 }
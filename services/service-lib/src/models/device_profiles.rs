@@ -1,12 +1,13 @@
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
-use sqlx::{pool::PoolConnection, postgres::PgRow, Postgres, Row};
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, Row};
 
 #[derive(Serialize, Deserialize)]
 pub struct DeviceProfile {
     pub id_device_profile: i32,
     pub device_name: String,
-    pub profile_key: String,
+    /// Plaintext key of profiles created before the keys were hashed, cleared on startup.
+    pub profile_key: Option<String>,
     pub create_user: i64,
     pub create_date: NaiveDateTime,
     pub modify_user: Option<i64>,
@@ -14,17 +15,12 @@ pub struct DeviceProfile {
 }
 
 impl DeviceProfile {
-    pub fn new(
-        device_name: &str,
-        profile_key: &str,
-        create_user: i64,
-        create_date: NaiveDateTime,
-    ) -> Self {
+    pub fn new(device_name: &str, create_user: i64, create_date: NaiveDateTime) -> Self {
         Self {
             id_device_profile: 0,
             device_name: String::from(device_name),
-            profile_key: String::from(profile_key),
-            create_user: create_user as i64,
+            profile_key: None,
+            create_user,
             create_date,
             modify_date: None,
             modify_user: None,
@@ -41,7 +37,7 @@ impl DeviceProfile {
             return Ok(Some(Self::from_row(&profile_row)?));
         }
 
-        return Ok(None);
+        Ok(None)
     }
 
     pub async fn get_all(db: &mut PoolConnection<Postgres>) -> sqlx::Result<Vec<DeviceProfile>> {
@@ -49,20 +45,54 @@ impl DeviceProfile {
             .fetch_all(db)
             .await?
             .iter()
-            .map(|x| Self::from_row(x))
+            .map(Self::from_row)
             .collect::<sqlx::Result<Vec<DeviceProfile>>>()
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    /// Returns all profiles with the date of their latest snapshot.
+    pub async fn get_all_with_last_seen(
+        db: &mut PoolConnection<Postgres>,
+    ) -> sqlx::Result<Vec<(DeviceProfile, Option<NaiveDateTime>)>> {
+        sqlx::query(
+            "SELECT p.*, 
+            (SELECT MAX(s.receive_date) FROM system_informations s WHERE s.device_profile_id = p.id_device_profile) 
+            FROM device_profiles p 
+            ORDER BY p.id_device_profile;",
+        )
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((Self::from_row(row)?, row.try_get(7)?)))
+        .collect()
+    }
+
+    /// Returns the profiles which still have a plaintext key.
+    pub async fn get_all_with_plain_key(db: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query("SELECT * FROM device_profiles WHERE profile_key IS NOT NULL;")
+            .fetch_all(db)
+            .await?
+            .iter()
+            .map(Self::from_row)
+            .collect()
+    }
+
+    pub async fn clear_plain_key(db: &mut PgConnection, id: i32) -> sqlx::Result<()> {
+        sqlx::query("UPDATE device_profiles SET profile_key = NULL WHERE id_device_profile = $1;")
+            .bind(id)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
             "INSERT INTO device_profiles 
-            (id_device_profile, device_name, profile_key, create_user, create_date) 
+            (device_name, create_user, create_date) 
             VALUES 
-            ($1, $2, $3, $4, $5) RETURNING id_device_profile;",
+            ($1, $2, $3) RETURNING id_device_profile;",
         )
-        .bind(self.id_device_profile)
         .bind(self.device_name.clone())
-        .bind(self.profile_key.clone())
         .bind(self.create_user)
         .bind(self.create_date)
         .fetch_one(db)
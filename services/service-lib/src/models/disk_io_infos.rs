@@ -0,0 +1,151 @@
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, QueryBuilder, Row};
+
+use super::reserve_ids;
+
+pub struct DiskIoInfo {
+    pub id_disk_io_info: i32,
+    pub system_information_id: i32,
+    pub name: String,
+    pub read_bytes: f64,
+    pub write_bytes: f64,
+    pub read_ops: f64,
+    pub write_ops: f64,
+    pub in_flight: i64,
+    pub io_time: f64,
+}
+
+impl DiskIoInfo {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        system_information_id: i32,
+        name: &str,
+        read_bytes: f64,
+        write_bytes: f64,
+        read_ops: f64,
+        write_ops: f64,
+        in_flight: i64,
+        io_time: f64,
+    ) -> Self {
+        Self {
+            id_disk_io_info: 0,
+            system_information_id,
+            name: String::from(name),
+            read_bytes,
+            write_bytes,
+            read_ops,
+            write_ops,
+            in_flight,
+            io_time,
+        }
+    }
+
+    pub async fn insert_many(db: &mut PgConnection, disks: Vec<Self>) -> sqlx::Result<Vec<Self>> {
+        if disks.is_empty() {
+            return Ok(disks);
+        }
+
+        let ids = reserve_ids(&mut *db, "disk_io_infos", "id_disk_io_info", disks.len()).await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO disk_io_infos 
+            (id_disk_io_info, system_information_id, name, read_bytes, write_bytes, read_ops, write_ops, in_flight, io_time) ",
+        );
+
+        query_builder.push_values(disks.iter().zip(&ids), |mut b, (disk, id)| {
+            b.push_bind(*id)
+                .push_bind(disk.system_information_id)
+                .push_bind(disk.name.clone())
+                .push_bind(disk.read_bytes)
+                .push_bind(disk.write_bytes)
+                .push_bind(disk.read_ops)
+                .push_bind(disk.write_ops)
+                .push_bind(disk.in_flight)
+                .push_bind(disk.io_time);
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(disks
+            .into_iter()
+            .zip(ids)
+            .map(|(disk, id)| Self {
+                id_disk_io_info: id,
+                ..disk
+            })
+            .collect())
+    }
+
+    pub async fn get_all_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM disk_io_infos 
+            WHERE system_information_id = $1 
+            ORDER BY name;",
+        )
+        .bind(system_information_id)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_all_by_system_informations(
+        db: &mut PoolConnection<Postgres>,
+        system_information_ids: &[i32],
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM disk_io_infos 
+            WHERE system_information_id = ANY($1) 
+            ORDER BY name;",
+        )
+        .bind(system_information_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, Self)>> {
+        sqlx::query(
+            "SELECT d.*, s.create_date 
+            FROM disk_io_infos d 
+            INNER JOIN system_informations s ON s.id_system_information = d.system_information_id 
+            WHERE s.device_profile_id = $1 AND s.create_date BETWEEN $2 AND $3 
+            ORDER BY s.create_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("create_date")?, Self::from_row(row)?)))
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_disk_io_info: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            name: row.try_get(2)?,
+            read_bytes: row.try_get(3)?,
+            write_bytes: row.try_get(4)?,
+            read_ops: row.try_get(5)?,
+            write_ops: row.try_get(6)?,
+            in_flight: row.try_get(7)?,
+            io_time: row.try_get(8)?,
+        })
+    }
+}
@@ -1,37 +1,174 @@
 use chrono::NaiveDateTime;
-use sqlx::{pool::PoolConnection, Postgres};
+use sqlx::{pool::PoolConnection, postgres::PgRow, types::JsonValue, Postgres, Row};
+
+/// Conditions of [`ErrorLogFilter`], `$1` is the profile.
+const FILTER_CONDITIONS: &str = "device_profile_id = $1 
+    AND severity = ANY($2) 
+    AND ($3::TIMESTAMP IS NULL OR occurred_at >= $3) 
+    AND ($4::TIMESTAMP IS NULL OR occurred_at <= $4) 
+    AND ($5::BOOLEAN IS NULL OR (acknowledged_date IS NOT NULL) = $5)";
 
 #[derive(Debug, Clone)]
 pub struct ErrorLog {
     pub id_error_log: i32,
     pub device_profile_id: i32,
+    pub severity: String,
+    pub source: String,
     pub message: String,
+    /// Additional details sent by the device as a json object.
+    pub context: JsonValue,
+    /// Time the error occurred on the device.
+    pub occurred_at: NaiveDateTime,
     pub create_date: NaiveDateTime,
+    pub acknowledged_user: Option<i64>,
+    pub acknowledged_date: Option<NaiveDateTime>,
+}
+
+pub struct ErrorLogFilter {
+    pub severities: Vec<String>,
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+    /// Only acknowledged or only unacknowledged logs.
+    pub acknowledged: Option<bool>,
 }
 
 impl ErrorLog {
-    pub fn new(device_profile_id: u32, message: &str, create_date: NaiveDateTime) -> Self {
+    pub fn new(
+        device_profile_id: u32,
+        severity: &str,
+        source: &str,
+        message: &str,
+        context: JsonValue,
+        occurred_at: NaiveDateTime,
+        create_date: NaiveDateTime,
+    ) -> Self {
         Self {
             id_error_log: 0,
             device_profile_id: device_profile_id as i32,
+            severity: String::from(severity),
+            source: String::from(source),
             message: String::from(message),
+            context,
+            occurred_at,
             create_date,
+            acknowledged_user: None,
+            acknowledged_date: None,
         }
     }
 
     pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
-        let row: (i32,) = sqlx::query_as("INSERT INTO error_logs (device_profile_id, message, create_date) VALUES ($1, $2, $3) RETURNING id_error_log;")
+        let row: (i32,) = sqlx::query_as("INSERT INTO error_logs (device_profile_id, severity, source, message, context, occurred_at, create_date) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id_error_log;")
             .bind(self.device_profile_id)
+            .bind(self.severity.clone())
+            .bind(self.source.clone())
             .bind(self.message.clone())
+            .bind(self.context.clone())
+            .bind(self.occurred_at)
             .bind(self.create_date)
             .fetch_one(db)
             .await?;
 
         Ok(Self {
             id_error_log: row.0,
-            device_profile_id: self.device_profile_id,
-            message: self.message,
-            create_date: self.create_date,
+            ..self
+        })
+    }
+
+    pub async fn get(
+        db: &mut PoolConnection<Postgres>,
+        id_error_log: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        let row = sqlx::query("SELECT * FROM error_logs WHERE id_error_log = $1;")
+            .bind(id_error_log)
+            .fetch_optional(db)
+            .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the logs of the profile matching the filter, newest first.
+    pub async fn get_page(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        filter: &ErrorLogFilter,
+        limit: i64,
+        offset: i64,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(&format!(
+            "SELECT * FROM error_logs 
+            WHERE {FILTER_CONDITIONS} 
+            ORDER BY occurred_at DESC, id_error_log DESC 
+            LIMIT $6 OFFSET $7;"
+        ))
+        .bind(device_profile_id)
+        .bind(&filter.severities)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.acknowledged)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn count(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        filter: &ErrorLogFilter,
+    ) -> sqlx::Result<i64> {
+        let row: (i64,) = sqlx::query_as(&format!(
+            "SELECT COUNT(*) FROM error_logs WHERE {FILTER_CONDITIONS};"
+        ))
+        .bind(device_profile_id)
+        .bind(&filter.severities)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.acknowledged)
+        .fetch_one(db)
+        .await?;
+
+        Ok(row.0)
+    }
+
+    /// Acknowledges the log, returns false if it was already acknowledged.
+    pub async fn acknowledge(
+        db: &mut PoolConnection<Postgres>,
+        id_error_log: i32,
+        user: i64,
+        date: NaiveDateTime,
+    ) -> sqlx::Result<bool> {
+        let result = sqlx::query(
+            "UPDATE error_logs SET acknowledged_user = $1, acknowledged_date = $2 
+            WHERE id_error_log = $3 AND acknowledged_date IS NULL;",
+        )
+        .bind(user)
+        .bind(date)
+        .bind(id_error_log)
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_error_log: row.try_get(0)?,
+            device_profile_id: row.try_get(1)?,
+            message: row.try_get(2)?,
+            create_date: row.try_get(3)?,
+            severity: row.try_get(4)?,
+            source: row.try_get(5)?,
+            occurred_at: row.try_get(6)?,
+            context: row.try_get(7)?,
+            acknowledged_user: row.try_get(8)?,
+            acknowledged_date: row.try_get(9)?,
         })
     }
 }
@@ -0,0 +1,92 @@
+use sqlx::{
+    pool::PoolConnection, postgres::PgRow, types::JsonValue, PgConnection, Postgres, QueryBuilder,
+    Row,
+};
+
+use super::reserve_ids;
+
+/// A section of a custom collector, stored as it was sent.
+pub struct ExtraSection {
+    pub id_extra_section: i32,
+    pub system_information_id: i32,
+    pub name: String,
+    pub value: JsonValue,
+}
+
+impl ExtraSection {
+    pub fn new(system_information_id: i32, name: &str, value: JsonValue) -> Self {
+        Self {
+            id_extra_section: 0,
+            system_information_id,
+            name: String::from(name),
+            value,
+        }
+    }
+
+    pub async fn insert_many(
+        db: &mut PgConnection,
+        sections: Vec<Self>,
+    ) -> sqlx::Result<Vec<Self>> {
+        if sections.is_empty() {
+            return Ok(sections);
+        }
+
+        let ids = reserve_ids(
+            &mut *db,
+            "extra_sections",
+            "id_extra_section",
+            sections.len(),
+        )
+        .await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO extra_sections 
+            (id_extra_section, system_information_id, name, value) ",
+        );
+
+        query_builder.push_values(sections.iter().zip(&ids), |mut b, (section, id)| {
+            b.push_bind(*id)
+                .push_bind(section.system_information_id)
+                .push_bind(section.name.clone())
+                .push_bind(section.value.clone());
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(sections
+            .into_iter()
+            .zip(ids)
+            .map(|(section, id)| Self {
+                id_extra_section: id,
+                ..section
+            })
+            .collect())
+    }
+
+    pub async fn get_all_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM extra_sections 
+            WHERE system_information_id = $1 
+            ORDER BY id_extra_section;",
+        )
+        .bind(system_information_id)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_extra_section: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            name: row.try_get(2)?,
+            value: row.try_get(3)?,
+        })
+    }
+}
@@ -0,0 +1,130 @@
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, QueryBuilder, Row};
+
+use super::reserve_ids;
+
+pub struct FanInfo {
+    pub id_fan_info: i32,
+    pub system_information_id: i32,
+    pub chip: String,
+    pub label: String,
+    pub rpm: Option<i64>,
+}
+
+impl FanInfo {
+    pub fn new(system_information_id: i32, chip: &str, label: &str, rpm: Option<i64>) -> Self {
+        Self {
+            id_fan_info: 0,
+            system_information_id,
+            chip: String::from(chip),
+            label: String::from(label),
+            rpm,
+        }
+    }
+
+    /// Name of the fan used as alert rule target.
+    pub fn fan_name(&self) -> String {
+        format!("{}/{}", self.chip, self.label)
+    }
+
+    pub async fn insert_many(db: &mut PgConnection, fans: Vec<Self>) -> sqlx::Result<Vec<Self>> {
+        if fans.is_empty() {
+            return Ok(fans);
+        }
+
+        let ids = reserve_ids(&mut *db, "fan_infos", "id_fan_info", fans.len()).await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO fan_infos 
+            (id_fan_info, system_information_id, chip, label, rpm) ",
+        );
+
+        query_builder.push_values(fans.iter().zip(&ids), |mut b, (fan, id)| {
+            b.push_bind(*id)
+                .push_bind(fan.system_information_id)
+                .push_bind(fan.chip.clone())
+                .push_bind(fan.label.clone())
+                .push_bind(fan.rpm);
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(fans
+            .into_iter()
+            .zip(ids)
+            .map(|(fan, id)| Self {
+                id_fan_info: id,
+                ..fan
+            })
+            .collect())
+    }
+
+    pub async fn get_all_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM fan_infos 
+            WHERE system_information_id = $1 
+            ORDER BY id_fan_info;",
+        )
+        .bind(system_information_id)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_all_by_system_informations(
+        db: &mut PoolConnection<Postgres>,
+        system_information_ids: &[i32],
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM fan_infos 
+            WHERE system_information_id = ANY($1) 
+            ORDER BY id_fan_info;",
+        )
+        .bind(system_information_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, Self)>> {
+        sqlx::query(
+            "SELECT f.*, s.create_date 
+            FROM fan_infos f 
+            INNER JOIN system_informations s ON s.id_system_information = f.system_information_id 
+            WHERE s.device_profile_id = $1 AND s.create_date BETWEEN $2 AND $3 
+            ORDER BY s.create_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("create_date")?, Self::from_row(row)?)))
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_fan_info: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            chip: row.try_get(2)?,
+            label: row.try_get(3)?,
+            rpm: row.try_get(4)?,
+        })
+    }
+}
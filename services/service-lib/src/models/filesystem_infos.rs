@@ -1,10 +1,14 @@
-use sqlx::{pool::PoolConnection, Postgres};
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, QueryBuilder, Row};
+
+use super::reserve_ids;
 
 pub struct FilesystemInfo {
     pub id_filesystem_info: i32,
     pub system_information_id: i32,
     pub files: i32,
     pub files_total: i32,
+    pub files_avail: i32,
     pub free: i64,
     pub avail: i64,
     pub total: i64,
@@ -15,10 +19,12 @@ pub struct FilesystemInfo {
 }
 
 impl FilesystemInfo {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         system_information_id: i32,
         files: i32,
         files_total: i32,
+        files_avail: i32,
         free: i64,
         avail: i64,
         total: i64,
@@ -32,6 +38,7 @@ impl FilesystemInfo {
             system_information_id,
             files,
             files_total,
+            files_avail,
             free,
             avail,
             total,
@@ -42,15 +49,16 @@ impl FilesystemInfo {
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
                 "INSERT INTO filesystem_infos 
-                (system_information_id, files, files_total, free, avail, total, name_max, fs_type, fs_mounted_from, fs_mounted_on) 
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) 
+                (system_information_id, files, files_total, files_avail, free, avail, total, name_max, fs_type, fs_mounted_from, fs_mounted_on) 
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) 
                 RETURNING id_filesystem_info;")
             .bind(self.system_information_id)
             .bind(self.files)
             .bind(self.files_total)
+            .bind(self.files_avail)
             .bind(self.free)
             .bind(self.avail)
             .bind(self.total)
@@ -66,6 +74,7 @@ impl FilesystemInfo {
             system_information_id: self.system_information_id,
             files: self.files,
             files_total: self.files_total,
+            files_avail: self.files_avail,
             free: self.free,
             avail: self.avail,
             total: self.total,
@@ -75,4 +84,125 @@ impl FilesystemInfo {
             fs_mounted_on: self.fs_mounted_on,
         })
     }
+
+    pub async fn insert_many(db: &mut PgConnection, mounts: Vec<Self>) -> sqlx::Result<Vec<Self>> {
+        if mounts.is_empty() {
+            return Ok(mounts);
+        }
+
+        let ids = reserve_ids(
+            &mut *db,
+            "filesystem_infos",
+            "id_filesystem_info",
+            mounts.len(),
+        )
+        .await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO filesystem_infos 
+            (id_filesystem_info, system_information_id, files, files_total, files_avail, free, avail, total, name_max, fs_type, fs_mounted_from, fs_mounted_on) ",
+        );
+
+        query_builder.push_values(mounts.iter().zip(&ids), |mut b, (mount, id)| {
+            b.push_bind(*id)
+                .push_bind(mount.system_information_id)
+                .push_bind(mount.files)
+                .push_bind(mount.files_total)
+                .push_bind(mount.files_avail)
+                .push_bind(mount.free)
+                .push_bind(mount.avail)
+                .push_bind(mount.total)
+                .push_bind(mount.name_max)
+                .push_bind(mount.fs_type.clone())
+                .push_bind(mount.fs_mounted_from.clone())
+                .push_bind(mount.fs_mounted_on.clone());
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(mounts
+            .into_iter()
+            .zip(ids)
+            .map(|(mount, id)| Self {
+                id_filesystem_info: id,
+                ..mount
+            })
+            .collect())
+    }
+
+    pub async fn get_all_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT id_filesystem_info, system_information_id, files, files_total, files_avail, free, avail, total, name_max, fs_type, fs_mounted_from, fs_mounted_on 
+            FROM filesystem_infos 
+            WHERE system_information_id = $1 
+            ORDER BY id_filesystem_info;",
+        )
+        .bind(system_information_id)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_all_by_system_informations(
+        db: &mut PoolConnection<Postgres>,
+        system_information_ids: &[i32],
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT id_filesystem_info, system_information_id, files, files_total, files_avail, free, avail, total, name_max, fs_type, fs_mounted_from, fs_mounted_on 
+            FROM filesystem_infos 
+            WHERE system_information_id = ANY($1) 
+            ORDER BY id_filesystem_info;",
+        )
+        .bind(system_information_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, Self)>> {
+        sqlx::query(
+            "SELECT f.id_filesystem_info, f.system_information_id, f.files, f.files_total, f.files_avail, f.free, f.avail, f.total, f.name_max, f.fs_type, f.fs_mounted_from, f.fs_mounted_on, s.create_date 
+            FROM filesystem_infos f 
+            INNER JOIN system_informations s ON s.id_system_information = f.system_information_id 
+            WHERE s.device_profile_id = $1 AND s.create_date BETWEEN $2 AND $3 
+            ORDER BY s.create_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("create_date")?, Self::from_row(row)?)))
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_filesystem_info: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            files: row.try_get(2)?,
+            files_total: row.try_get(3)?,
+            files_avail: row.try_get(4)?,
+            free: row.try_get(5)?,
+            avail: row.try_get(6)?,
+            total: row.try_get(7)?,
+            name_max: row.try_get(8)?,
+            fs_type: row.try_get(9)?,
+            fs_mounted_from: row.try_get(10)?,
+            fs_mounted_on: row.try_get(11)?,
+        })
+    }
 }
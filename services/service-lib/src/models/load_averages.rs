@@ -1,4 +1,5 @@
-use sqlx::{pool::PoolConnection, Postgres};
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, Row};
 
 pub struct LoadAverage {
     pub id_load_average: i32,
@@ -19,7 +20,7 @@ impl LoadAverage {
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
             "INSERT INTO load_averages 
             (system_information_id, one, five, fifteen) 
@@ -41,4 +42,70 @@ impl LoadAverage {
             fifteen: self.fifteen,
         })
     }
+
+    pub async fn get_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        let row = sqlx::query("SELECT * FROM load_averages WHERE system_information_id = $1;")
+            .bind(system_information_id)
+            .fetch_optional(db)
+            .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    pub async fn get_all_by_system_informations(
+        db: &mut PoolConnection<Postgres>,
+        system_information_ids: &[i32],
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM load_averages 
+            WHERE system_information_id = ANY($1);",
+        )
+        .bind(system_information_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, Self)>> {
+        sqlx::query(
+            "SELECT l.*, s.create_date 
+            FROM load_averages l 
+            INNER JOIN system_informations s ON s.id_system_information = l.system_information_id 
+            WHERE s.device_profile_id = $1 AND s.create_date BETWEEN $2 AND $3 
+            ORDER BY s.create_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("create_date")?, Self::from_row(row)?)))
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_load_average: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            one: row.try_get::<f64, _>(2)? as f32,
+            five: row.try_get::<f64, _>(3)? as f32,
+            fifteen: row.try_get::<f64, _>(4)? as f32,
+        })
+    }
 }
@@ -1,40 +1,154 @@
-use sqlx::{pool::PoolConnection, Postgres};
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, Row};
 
 pub struct MemoryInfo {
     pub id_memory_info: i32,
     pub system_information_id: i32,
     pub free: i64,
     pub total: i64,
+    pub available: i64,
+    pub buffers: i64,
+    pub cached: i64,
+    pub shared: i64,
+    pub slab: i64,
+    pub dirty: i64,
+    pub writeback: i64,
+    pub hugepages_total: i64,
+    pub hugepages_free: i64,
 }
 
 impl MemoryInfo {
-    pub fn new(system_information_id: i32, free: i64, total: i64) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        system_information_id: i32,
+        free: i64,
+        total: i64,
+        available: i64,
+        buffers: i64,
+        cached: i64,
+        shared: i64,
+        slab: i64,
+        dirty: i64,
+        writeback: i64,
+        hugepages_total: i64,
+        hugepages_free: i64,
+    ) -> Self {
         Self {
             id_memory_info: 0,
             system_information_id,
             free,
             total,
+            available,
+            buffers,
+            cached,
+            shared,
+            slab,
+            dirty,
+            writeback,
+            hugepages_total,
+            hugepages_free,
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
             "INSERT INTO memory_infos 
-            (system_information_id, free, total) 
+            (system_information_id, free, total, available, buffers, cached, shared, slab, dirty, writeback, hugepages_total, hugepages_free) 
             VALUES 
-            ($1, $2, $3) RETURNING id_memory_info;",
+            ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) RETURNING id_memory_info;",
         )
         .bind(self.system_information_id)
         .bind(self.free)
         .bind(self.total)
+        .bind(self.available)
+        .bind(self.buffers)
+        .bind(self.cached)
+        .bind(self.shared)
+        .bind(self.slab)
+        .bind(self.dirty)
+        .bind(self.writeback)
+        .bind(self.hugepages_total)
+        .bind(self.hugepages_free)
         .fetch_one(db)
         .await?;
 
         Ok(Self {
             id_memory_info: row.0,
-            system_information_id: self.system_information_id,
-            free: self.free,
-            total: self.total,
+            ..self
+        })
+    }
+
+    pub async fn get_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        let row = sqlx::query("SELECT * FROM memory_infos WHERE system_information_id = $1;")
+            .bind(system_information_id)
+            .fetch_optional(db)
+            .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    pub async fn get_all_by_system_informations(
+        db: &mut PoolConnection<Postgres>,
+        system_information_ids: &[i32],
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM memory_infos 
+            WHERE system_information_id = ANY($1);",
+        )
+        .bind(system_information_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, Self)>> {
+        sqlx::query(
+            "SELECT m.*, s.create_date 
+            FROM memory_infos m 
+            INNER JOIN system_informations s ON s.id_system_information = m.system_information_id 
+            WHERE s.device_profile_id = $1 AND s.create_date BETWEEN $2 AND $3 
+            ORDER BY s.create_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("create_date")?, Self::from_row(row)?)))
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_memory_info: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            free: row.try_get(2)?,
+            total: row.try_get(3)?,
+            available: row.try_get(4)?,
+            buffers: row.try_get(5)?,
+            cached: row.try_get(6)?,
+            shared: row.try_get(7)?,
+            slab: row.try_get(8)?,
+            dirty: row.try_get(9)?,
+            writeback: row.try_get(10)?,
+            hugepages_total: row.try_get(11)?,
+            hugepages_free: row.try_get(12)?,
         })
     }
 }
@@ -1,16 +1,49 @@
+use sqlx::PgConnection;
+
+pub mod alert_rules;
+pub mod alerts;
+pub mod api_tokens;
 pub mod battery_lifes;
+pub mod check_results;
 pub mod cpu_core_loads;
 pub mod cpu_informations;
 pub mod cpu_loads;
 pub mod device_profiles;
+pub mod disk_io_infos;
 pub mod error_logs;
+pub mod extra_sections;
+pub mod fan_infos;
 pub mod filesystem_infos;
 pub mod load_averages;
 pub mod memory_infos;
 pub mod network_addresses;
+pub mod network_rates;
 pub mod network_statistics;
 pub mod networks;
 pub mod os_infos;
+pub mod probe_results;
+pub mod process_infos;
+pub mod profile_keys;
 pub mod socket_statistics;
 pub mod swap_infos;
 pub mod system_informations;
+pub mod temperature_infos;
+pub mod users;
+
+/// Takes `count` ids from the sequence of the serial primary key of `table`.
+/// Multi-row inserts write these ids themselves, postgres doesn't guarantee the order of `RETURNING`.
+pub(crate) async fn reserve_ids(
+    db: &mut PgConnection,
+    table: &str,
+    id_column: &str,
+    count: usize,
+) -> sqlx::Result<Vec<i32>> {
+    sqlx::query_scalar(
+        "SELECT nextval(pg_get_serial_sequence($1, $2))::INT FROM generate_series(1, $3);",
+    )
+    .bind(table)
+    .bind(id_column)
+    .bind(count as i32)
+    .fetch_all(db)
+    .await
+}
@@ -1,4 +1,6 @@
-use sqlx::{pool::PoolConnection, Postgres};
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, QueryBuilder, Row};
+
+use super::reserve_ids;
 
 pub struct NetworkAddress {
     pub id_network_address: i32,
@@ -17,7 +19,7 @@ impl NetworkAddress {
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
             "INSERT INTO network_addresses 
             (network_id, address, netmask) 
@@ -37,4 +39,67 @@ impl NetworkAddress {
             netmask: self.netmask,
         })
     }
+
+    pub async fn insert_many(
+        db: &mut PgConnection,
+        addresses: Vec<Self>,
+    ) -> sqlx::Result<Vec<Self>> {
+        if addresses.is_empty() {
+            return Ok(addresses);
+        }
+
+        let ids = reserve_ids(
+            &mut *db,
+            "network_addresses",
+            "id_network_address",
+            addresses.len(),
+        )
+        .await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO network_addresses (id_network_address, network_id, address, netmask) ",
+        );
+
+        query_builder.push_values(addresses.iter().zip(&ids), |mut b, (address, id)| {
+            b.push_bind(*id)
+                .push_bind(address.network_id)
+                .push_bind(address.address.clone())
+                .push_bind(address.netmask.clone());
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(addresses
+            .into_iter()
+            .zip(ids)
+            .map(|(address, id)| Self {
+                id_network_address: id,
+                ..address
+            })
+            .collect())
+    }
+
+    pub async fn get_all_by_network(
+        db: &mut PoolConnection<Postgres>,
+        network_id: i32,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * FROM network_addresses WHERE network_id = $1 ORDER BY id_network_address;",
+        )
+        .bind(network_id)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_network_address: row.try_get(0)?,
+            network_id: row.try_get(1)?,
+            address: row.try_get(2)?,
+            netmask: row.try_get(3)?,
+        })
+    }
 }
@@ -0,0 +1,148 @@
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, QueryBuilder, Row};
+
+use super::reserve_ids;
+
+/// Per second rates of the counters of a network since the previous snapshot of the profile.
+pub struct NetworkRate {
+    pub id_network_rate: i32,
+    pub system_information_id: i32,
+    pub network_id: i32,
+    pub rx_bytes: f64,
+    pub tx_bytes: f64,
+    pub rx_packets: f64,
+    pub tx_packets: f64,
+    pub rx_errors: f64,
+    pub tx_errors: f64,
+}
+
+impl NetworkRate {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        system_information_id: i32,
+        network_id: i32,
+        rx_bytes: f64,
+        tx_bytes: f64,
+        rx_packets: f64,
+        tx_packets: f64,
+        rx_errors: f64,
+        tx_errors: f64,
+    ) -> Self {
+        Self {
+            id_network_rate: 0,
+            system_information_id,
+            network_id,
+            rx_bytes,
+            tx_bytes,
+            rx_packets,
+            tx_packets,
+            rx_errors,
+            tx_errors,
+        }
+    }
+
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
+        let row: (i32,) = sqlx::query_as(
+            "INSERT INTO network_rates 
+            (system_information_id, network_id, rx_bytes, tx_bytes, rx_packets, tx_packets, rx_errors, tx_errors) 
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8) 
+            RETURNING id_network_rate;",
+        )
+        .bind(self.system_information_id)
+        .bind(self.network_id)
+        .bind(self.rx_bytes)
+        .bind(self.tx_bytes)
+        .bind(self.rx_packets)
+        .bind(self.tx_packets)
+        .bind(self.rx_errors)
+        .bind(self.tx_errors)
+        .fetch_one(db)
+        .await?;
+
+        Ok(Self {
+            id_network_rate: row.0,
+            ..self
+        })
+    }
+
+    pub async fn insert_many(db: &mut PgConnection, rates: Vec<Self>) -> sqlx::Result<Vec<Self>> {
+        if rates.is_empty() {
+            return Ok(rates);
+        }
+
+        let ids = reserve_ids(&mut *db, "network_rates", "id_network_rate", rates.len()).await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO network_rates 
+            (id_network_rate, system_information_id, network_id, rx_bytes, tx_bytes, rx_packets, tx_packets, rx_errors, tx_errors) ",
+        );
+
+        query_builder.push_values(rates.iter().zip(&ids), |mut b, (rate, id)| {
+            b.push_bind(*id)
+                .push_bind(rate.system_information_id)
+                .push_bind(rate.network_id)
+                .push_bind(rate.rx_bytes)
+                .push_bind(rate.tx_bytes)
+                .push_bind(rate.rx_packets)
+                .push_bind(rate.tx_packets)
+                .push_bind(rate.rx_errors)
+                .push_bind(rate.tx_errors);
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(rates
+            .into_iter()
+            .zip(ids)
+            .map(|(rate, id)| Self {
+                id_network_rate: id,
+                ..rate
+            })
+            .collect())
+    }
+
+    /// Returns the rates in the time range together with the name of their network.
+    pub async fn get_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, String, Self)>> {
+        sqlx::query(
+            "SELECT r.*, s.create_date, n.name 
+            FROM network_rates r 
+            INNER JOIN networks n ON n.id_network = r.network_id 
+            INNER JOIN system_informations s ON s.id_system_information = r.system_information_id 
+            WHERE s.device_profile_id = $1 AND s.create_date BETWEEN $2 AND $3 
+            ORDER BY s.create_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| {
+            Ok((
+                row.try_get("create_date")?,
+                row.try_get("name")?,
+                Self::from_row(row)?,
+            ))
+        })
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_network_rate: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            network_id: row.try_get(2)?,
+            rx_bytes: row.try_get(3)?,
+            tx_bytes: row.try_get(4)?,
+            rx_packets: row.try_get(5)?,
+            tx_packets: row.try_get(6)?,
+            rx_errors: row.try_get(7)?,
+            tx_errors: row.try_get(8)?,
+        })
+    }
+}
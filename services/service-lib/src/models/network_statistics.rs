@@ -1,4 +1,7 @@
-use sqlx::{pool::PoolConnection, Postgres};
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, QueryBuilder, Row};
+
+use super::reserve_ids;
 
 pub struct NetworkStatistic {
     pub id_network_statistics: i32,
@@ -13,6 +16,7 @@ pub struct NetworkStatistic {
 }
 
 impl NetworkStatistic {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         system_information_id: i32,
         network_id: i32,
@@ -36,7 +40,7 @@ impl NetworkStatistic {
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
                 "INSERT INTO networks_statistics 
                 (system_information_id, network_id, rx_bytes, tx_bytes, rx_packages, tx_packages, rx_errors, tx_errors) 
@@ -65,4 +69,169 @@ impl NetworkStatistic {
             tx_errors: self.tx_errors,
         })
     }
+
+    pub async fn insert_many(
+        db: &mut PgConnection,
+        statistics: Vec<Self>,
+    ) -> sqlx::Result<Vec<Self>> {
+        if statistics.is_empty() {
+            return Ok(statistics);
+        }
+
+        let ids = reserve_ids(
+            &mut *db,
+            "networks_statistics",
+            "id_network_statistics",
+            statistics.len(),
+        )
+        .await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO networks_statistics 
+            (id_network_statistics, system_information_id, network_id, rx_bytes, tx_bytes, rx_packages, tx_packages, rx_errors, tx_errors) ",
+        );
+
+        query_builder.push_values(statistics.iter().zip(&ids), |mut b, (netstat, id)| {
+            b.push_bind(*id)
+                .push_bind(netstat.system_information_id)
+                .push_bind(netstat.network_id)
+                .push_bind(netstat.rx_bytes)
+                .push_bind(netstat.tx_bytes)
+                .push_bind(netstat.rx_packages)
+                .push_bind(netstat.tx_packages)
+                .push_bind(netstat.rx_errors)
+                .push_bind(netstat.tx_errors);
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(statistics
+            .into_iter()
+            .zip(ids)
+            .map(|(netstat, id)| Self {
+                id_network_statistics: id,
+                ..netstat
+            })
+            .collect())
+    }
+
+    pub async fn get_by_network(
+        db: &mut PoolConnection<Postgres>,
+        network_id: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        let row = sqlx::query(
+            "SELECT id_network_statistics, system_information_id, network_id, rx_bytes, tx_bytes, rx_packages, tx_packages, rx_errors, tx_errors 
+            FROM networks_statistics 
+            WHERE network_id = $1;",
+        )
+            .bind(network_id)
+            .fetch_optional(db)
+            .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the statistics of the snapshots together with the name of their network.
+    pub async fn get_all_by_system_informations(
+        db: &mut PoolConnection<Postgres>,
+        system_information_ids: &[i32],
+    ) -> sqlx::Result<Vec<(String, Self)>> {
+        sqlx::query(
+            "SELECT t.id_network_statistics, t.system_information_id, t.network_id, t.rx_bytes, t.tx_bytes, t.rx_packages, t.tx_packages, t.rx_errors, t.tx_errors, n.name 
+            FROM networks_statistics t 
+            INNER JOIN networks n ON n.id_network = t.network_id 
+            WHERE t.system_information_id = ANY($1) 
+            ORDER BY t.network_id;",
+        )
+        .bind(system_information_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("name")?, Self::from_row(row)?)))
+        .collect()
+    }
+
+    /// Returns the statistics in the time range together with the name of their network.
+    pub async fn get_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, String, Self)>> {
+        sqlx::query(
+            "SELECT t.id_network_statistics, t.system_information_id, t.network_id, t.rx_bytes, t.tx_bytes, t.rx_packages, t.tx_packages, t.rx_errors, t.tx_errors, s.create_date, n.name 
+            FROM networks_statistics t 
+            INNER JOIN networks n ON n.id_network = t.network_id 
+            INNER JOIN system_informations s ON s.id_system_information = t.system_information_id 
+            WHERE s.device_profile_id = $1 AND s.create_date BETWEEN $2 AND $3 
+            ORDER BY s.create_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| {
+            Ok((
+                row.try_get("create_date")?,
+                row.try_get("name")?,
+                Self::from_row(row)?,
+            ))
+        })
+        .collect()
+    }
+
+    /// Returns the statistics of the latest snapshot of the profile collected before `before`,
+    /// together with the collection and boot time of that snapshot and the name of their network.
+    pub async fn get_all_previous(
+        db: &mut PgConnection,
+        device_profile_id: i32,
+        before: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, NaiveDateTime, String, Self)>> {
+        sqlx::query(
+            "SELECT t.id_network_statistics, t.system_information_id, t.network_id, t.rx_bytes, t.tx_bytes, t.rx_packages, t.tx_packages, t.rx_errors, t.tx_errors, s.create_date, s.boot_time, n.name 
+            FROM (
+                SELECT id_system_information, create_date, boot_time 
+                FROM system_informations 
+                WHERE device_profile_id = $1 AND create_date < $2 
+                ORDER BY create_date DESC 
+                LIMIT 1
+            ) s 
+            INNER JOIN networks_statistics t ON t.system_information_id = s.id_system_information 
+            INNER JOIN networks n ON n.id_network = t.network_id;",
+        )
+        .bind(device_profile_id)
+        .bind(before)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| {
+            Ok((
+                row.try_get("create_date")?,
+                row.try_get("boot_time")?,
+                row.try_get("name")?,
+                Self::from_row(row)?,
+            ))
+        })
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_network_statistics: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            network_id: row.try_get(2)?,
+            rx_bytes: row.try_get(3)?,
+            tx_bytes: row.try_get(4)?,
+            rx_packages: row.try_get(5)?,
+            tx_packages: row.try_get(6)?,
+            rx_errors: row.try_get(7)?,
+            tx_errors: row.try_get(8)?,
+        })
+    }
 }
@@ -1,4 +1,6 @@
-use sqlx::{pool::PoolConnection, Postgres};
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, QueryBuilder, Row};
+
+use super::reserve_ids;
 
 pub struct Network {
     pub id_network: i32,
@@ -15,7 +17,7 @@ impl Network {
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
             "INSERT INTO networks 
             (system_information_id, name) 
@@ -33,4 +35,56 @@ impl Network {
             name: self.name,
         })
     }
+
+    pub async fn insert_many(
+        db: &mut PgConnection,
+        networks: Vec<Self>,
+    ) -> sqlx::Result<Vec<Self>> {
+        if networks.is_empty() {
+            return Ok(networks);
+        }
+
+        let ids = reserve_ids(&mut *db, "networks", "id_network", networks.len()).await?;
+
+        let mut query_builder: QueryBuilder<Postgres> =
+            QueryBuilder::new("INSERT INTO networks (id_network, system_information_id, name) ");
+
+        query_builder.push_values(networks.iter().zip(&ids), |mut b, (network, id)| {
+            b.push_bind(*id)
+                .push_bind(network.system_information_id)
+                .push_bind(network.name.clone());
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(networks
+            .into_iter()
+            .zip(ids)
+            .map(|(network, id)| Self {
+                id_network: id,
+                ..network
+            })
+            .collect())
+    }
+
+    pub async fn get_all_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query("SELECT * FROM networks WHERE system_information_id = $1 ORDER BY id_network;")
+            .bind(system_information_id)
+            .fetch_all(db)
+            .await?
+            .iter()
+            .map(Self::from_row)
+            .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_network: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            name: row.try_get(2)?,
+        })
+    }
 }
@@ -1,4 +1,6 @@
-use sqlx::{pool::PoolConnection, Postgres};
+use sqlx::{
+    pool::PoolConnection, postgres::PgRow, types::JsonValue, PgConnection, Postgres, Row,
+};
 
 pub struct OsInfo {
     pub id_os_info: i32,
@@ -9,9 +11,12 @@ pub struct OsInfo {
     pub codename: Option<String>,
     pub bitness: String,
     pub architecture: Option<String>,
+    /// The serialized `os_info::Info`, `None` for snapshots stored before it was kept.
+    pub info: Option<JsonValue>,
 }
 
 impl OsInfo {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         system_info_id: i32,
         os_type: &str,
@@ -20,6 +25,7 @@ impl OsInfo {
         codename: Option<String>,
         bitness: &str,
         architecture: Option<String>,
+        info: Option<JsonValue>,
     ) -> Self {
         Self {
             id_os_info: 0,
@@ -30,15 +36,16 @@ impl OsInfo {
             codename,
             bitness: String::from(bitness),
             architecture,
+            info,
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
             "INSERT INTO os_infos 
-            (system_information_id, os_type, version, edition, codename, bitness, architecture) 
+            (system_information_id, os_type, version, edition, codename, bitness, architecture, info) 
             VALUES 
-            ($1, $2, $3, $4, $5, $6, $7) RETURNING id_os_info;",
+            ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id_os_info;",
         )
         .bind(self.system_information_id)
         .bind(self.os_type.clone())
@@ -47,6 +54,7 @@ impl OsInfo {
         .bind(self.codename.clone())
         .bind(self.bitness.clone())
         .bind(self.architecture.clone())
+        .bind(self.info.clone())
         .fetch_one(db)
         .await?;
 
@@ -59,6 +67,37 @@ impl OsInfo {
             codename: self.codename,
             bitness: self.bitness,
             architecture: self.architecture,
+            info: self.info,
+        })
+    }
+
+    pub async fn get_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        let row = sqlx::query("SELECT * FROM os_infos WHERE system_information_id = $1;")
+            .bind(system_information_id)
+            .fetch_optional(db)
+            .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_os_info: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            os_type: row.try_get(2)?,
+            version: row.try_get(3)?,
+            edition: row.try_get(4)?,
+            codename: row.try_get(5)?,
+            bitness: row.try_get(6)?,
+            architecture: row.try_get(7)?,
+            info: row.try_get(8)?,
         })
     }
 }
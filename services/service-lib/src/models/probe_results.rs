@@ -0,0 +1,164 @@
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, QueryBuilder, Row};
+
+use super::reserve_ids;
+
+pub struct ProbeResult {
+    pub id_probe_result: i32,
+    pub device_profile_id: i32,
+    pub name: String,
+    pub kind: String,
+    pub target: String,
+    pub success: bool,
+    pub latency: Option<f64>,
+    pub status_code: Option<i32>,
+    pub body_match: Option<bool>,
+    pub cert_expiry_days: Option<i64>,
+    pub error: Option<String>,
+    pub executed_at: NaiveDateTime,
+    pub receive_date: NaiveDateTime,
+}
+
+impl ProbeResult {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        device_profile_id: i32,
+        name: &str,
+        kind: &str,
+        target: &str,
+        success: bool,
+        latency: Option<f64>,
+        status_code: Option<i32>,
+        body_match: Option<bool>,
+        cert_expiry_days: Option<i64>,
+        error: Option<String>,
+        executed_at: NaiveDateTime,
+        receive_date: NaiveDateTime,
+    ) -> Self {
+        Self {
+            id_probe_result: 0,
+            device_profile_id,
+            name: String::from(name),
+            kind: String::from(kind),
+            target: String::from(target),
+            success,
+            latency,
+            status_code,
+            body_match,
+            cert_expiry_days,
+            error,
+            executed_at,
+            receive_date,
+        }
+    }
+
+    pub async fn insert_many(db: &mut PgConnection, probes: Vec<Self>) -> sqlx::Result<Vec<Self>> {
+        if probes.is_empty() {
+            return Ok(probes);
+        }
+
+        let ids = reserve_ids(&mut *db, "probe_results", "id_probe_result", probes.len()).await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO probe_results 
+            (id_probe_result, device_profile_id, name, kind, target, success, latency, status_code, body_match, cert_expiry_days, error, executed_at, receive_date) ",
+        );
+
+        query_builder.push_values(probes.iter().zip(&ids), |mut b, (probe, id)| {
+            b.push_bind(*id)
+                .push_bind(probe.device_profile_id)
+                .push_bind(probe.name.clone())
+                .push_bind(probe.kind.clone())
+                .push_bind(probe.target.clone())
+                .push_bind(probe.success)
+                .push_bind(probe.latency)
+                .push_bind(probe.status_code)
+                .push_bind(probe.body_match)
+                .push_bind(probe.cert_expiry_days)
+                .push_bind(probe.error.clone())
+                .push_bind(probe.executed_at)
+                .push_bind(probe.receive_date);
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(probes
+            .into_iter()
+            .zip(ids)
+            .map(|(probe, id)| Self {
+                id_probe_result: id,
+                ..probe
+            })
+            .collect())
+    }
+
+    /// Results of a probe of the profile received between `from` and `to`, oldest first.
+    pub async fn get_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        name: &str,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM probe_results 
+            WHERE device_profile_id = $1 AND name = $2 AND receive_date BETWEEN $3 AND $4 
+            ORDER BY receive_date;",
+        )
+        .bind(device_profile_id)
+        .bind(name)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    /// The latest result of a probe of the profile received at or before `until`.
+    pub async fn get_latest(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        name: &str,
+        until: NaiveDateTime,
+    ) -> sqlx::Result<Option<Self>> {
+        let row = sqlx::query(
+            "SELECT * 
+            FROM probe_results 
+            WHERE device_profile_id = $1 AND name = $2 AND receive_date <= $3 
+            ORDER BY receive_date DESC 
+            LIMIT 1;",
+        )
+        .bind(device_profile_id)
+        .bind(name)
+        .bind(until)
+        .fetch_optional(db)
+        .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_probe_result: row.try_get(0)?,
+            device_profile_id: row.try_get(1)?,
+            name: row.try_get(2)?,
+            kind: row.try_get(3)?,
+            target: row.try_get(4)?,
+            success: row.try_get(5)?,
+            latency: row.try_get(6)?,
+            status_code: row.try_get(7)?,
+            body_match: row.try_get(8)?,
+            cert_expiry_days: row.try_get(9)?,
+            error: row.try_get(10)?,
+            executed_at: row.try_get(11)?,
+            receive_date: row.try_get(12)?,
+        })
+    }
+}
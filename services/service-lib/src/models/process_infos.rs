@@ -0,0 +1,138 @@
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, QueryBuilder, Row};
+
+use super::reserve_ids;
+
+/// Ranking a process was stored for, a process can be in both.
+pub static RANKING_CPU: &str = "cpu";
+pub static RANKING_MEMORY: &str = "memory";
+
+pub struct ProcessInfo {
+    pub id_process_info: i32,
+    pub system_information_id: i32,
+    pub ranking: String,
+    pub pid: i32,
+    pub name: String,
+    pub cmdline: String,
+    pub username: String,
+    pub state: String,
+    pub threads: i64,
+    pub open_fds: Option<i64>,
+    pub cpu_usage: f32,
+    pub rss: i64,
+}
+
+impl ProcessInfo {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        system_information_id: i32,
+        ranking: &str,
+        pid: i32,
+        name: &str,
+        cmdline: &str,
+        username: &str,
+        state: &str,
+        threads: i64,
+        open_fds: Option<i64>,
+        cpu_usage: f32,
+        rss: i64,
+    ) -> Self {
+        Self {
+            id_process_info: 0,
+            system_information_id,
+            ranking: String::from(ranking),
+            pid,
+            name: String::from(name),
+            cmdline: String::from(cmdline),
+            username: String::from(username),
+            state: String::from(state),
+            threads,
+            open_fds,
+            cpu_usage,
+            rss,
+        }
+    }
+
+    pub async fn insert_many(
+        db: &mut PgConnection,
+        processes: Vec<Self>,
+    ) -> sqlx::Result<Vec<Self>> {
+        if processes.is_empty() {
+            return Ok(processes);
+        }
+
+        let ids = reserve_ids(
+            &mut *db,
+            "process_infos",
+            "id_process_info",
+            processes.len(),
+        )
+        .await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO process_infos 
+            (id_process_info, system_information_id, ranking, pid, name, cmdline, username, state, threads, open_fds, cpu_usage, rss) ",
+        );
+
+        query_builder.push_values(processes.iter().zip(&ids), |mut b, (process, id)| {
+            b.push_bind(*id)
+                .push_bind(process.system_information_id)
+                .push_bind(process.ranking.clone())
+                .push_bind(process.pid)
+                .push_bind(process.name.clone())
+                .push_bind(process.cmdline.clone())
+                .push_bind(process.username.clone())
+                .push_bind(process.state.clone())
+                .push_bind(process.threads)
+                .push_bind(process.open_fds)
+                .push_bind(process.cpu_usage)
+                .push_bind(process.rss);
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(processes
+            .into_iter()
+            .zip(ids)
+            .map(|(process, id)| Self {
+                id_process_info: id,
+                ..process
+            })
+            .collect())
+    }
+
+    /// Returns the processes of both rankings in the order they were stored.
+    pub async fn get_all_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM process_infos 
+            WHERE system_information_id = $1 
+            ORDER BY id_process_info;",
+        )
+        .bind(system_information_id)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_process_info: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            ranking: row.try_get(2)?,
+            pid: row.try_get(3)?,
+            name: row.try_get(4)?,
+            cmdline: row.try_get(5)?,
+            username: row.try_get(6)?,
+            state: row.try_get(7)?,
+            threads: row.try_get(8)?,
+            open_fds: row.try_get(9)?,
+            cpu_usage: row.try_get(10)?,
+            rss: row.try_get(11)?,
+        })
+    }
+}
@@ -0,0 +1,146 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgRow, PgConnection, Row};
+
+/// A hashed key of a device profile.
+/// A profile can have multiple valid keys while the old key of a rotation is in its grace period.
+#[derive(Debug, Clone)]
+pub struct ProfileKey {
+    pub id_profile_key: i32,
+    pub device_profile_id: i32,
+    /// Argon2 hash of the key as PHC string.
+    pub key_hash: String,
+    pub create_user: i64,
+    pub create_date: NaiveDateTime,
+    /// End of the grace period after a rotation, `None` until the key is rotated.
+    pub expire_date: Option<NaiveDateTime>,
+    pub revoke_date: Option<NaiveDateTime>,
+}
+
+impl ProfileKey {
+    pub fn new(
+        device_profile_id: i32,
+        key_hash: &str,
+        create_user: i64,
+        create_date: NaiveDateTime,
+    ) -> Self {
+        Self {
+            id_profile_key: 0,
+            device_profile_id,
+            key_hash: String::from(key_hash),
+            create_user,
+            create_date,
+            expire_date: None,
+            revoke_date: None,
+        }
+    }
+
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
+        let row: (i32,) = sqlx::query_as(
+            "INSERT INTO profile_keys 
+            (device_profile_id, key_hash, create_user, create_date) 
+            VALUES 
+            ($1, $2, $3, $4) RETURNING id_profile_key;",
+        )
+        .bind(self.device_profile_id)
+        .bind(self.key_hash.clone())
+        .bind(self.create_user)
+        .bind(self.create_date)
+        .fetch_one(db)
+        .await?;
+
+        Ok(Self {
+            id_profile_key: row.0,
+            ..self
+        })
+    }
+
+    /// Returns the keys of the profile which are neither expired nor revoked at `now`.
+    pub async fn get_valid(
+        db: &mut PgConnection,
+        device_profile_id: i32,
+        now: NaiveDateTime,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * FROM profile_keys 
+            WHERE device_profile_id = $1 
+            AND revoke_date IS NULL 
+            AND (expire_date IS NULL OR expire_date > $2) 
+            ORDER BY create_date DESC;",
+        )
+        .bind(device_profile_id)
+        .bind(now)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_all_by_profile(
+        db: &mut PgConnection,
+        device_profile_id: i32,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * FROM profile_keys WHERE device_profile_id = $1 ORDER BY create_date DESC;",
+        )
+        .bind(device_profile_id)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    /// Lets the valid keys of the profile expire at `expire_date`,
+    /// keys that already expire earlier keep their date.
+    pub async fn expire_valid(
+        db: &mut PgConnection,
+        device_profile_id: i32,
+        expire_date: NaiveDateTime,
+    ) -> sqlx::Result<u64> {
+        let result = sqlx::query(
+            "UPDATE profile_keys SET expire_date = $2 
+            WHERE device_profile_id = $1 
+            AND revoke_date IS NULL 
+            AND (expire_date IS NULL OR expire_date > $2);",
+        )
+        .bind(device_profile_id)
+        .bind(expire_date)
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Revokes the key, returns false if the profile has no such key or it was already revoked.
+    pub async fn revoke(
+        db: &mut PgConnection,
+        device_profile_id: i32,
+        id_profile_key: i32,
+        revoke_date: NaiveDateTime,
+    ) -> sqlx::Result<bool> {
+        let result = sqlx::query(
+            "UPDATE profile_keys SET revoke_date = $3 
+            WHERE device_profile_id = $1 AND id_profile_key = $2 AND revoke_date IS NULL;",
+        )
+        .bind(device_profile_id)
+        .bind(id_profile_key)
+        .bind(revoke_date)
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_profile_key: row.try_get(0)?,
+            device_profile_id: row.try_get(1)?,
+            key_hash: row.try_get(2)?,
+            create_user: row.try_get(3)?,
+            create_date: row.try_get(4)?,
+            expire_date: row.try_get(5)?,
+            revoke_date: row.try_get(6)?,
+        })
+    }
+}
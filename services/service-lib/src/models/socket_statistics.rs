@@ -1,4 +1,4 @@
-use sqlx::{pool::PoolConnection, Postgres};
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, Row};
 
 pub struct SocketStatistic {
     pub id_socket_statistics: i32,
@@ -30,7 +30,7 @@ impl SocketStatistic {
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
         let row: (i32,) = sqlx::query_as(
                 "INSERT INTO socket_statistics 
                 (system_information_id, tcp_sockets_in_use, tcp_sockets_orphaned, udp_sockets_in_use, tcp6_sockets_in_use, udp6_sockets_in_use) 
@@ -55,4 +55,49 @@ impl SocketStatistic {
             udp6_sockets_in_use: self.udp6_sockets_in_use,
         })
     }
+
+    pub async fn get_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        let row = sqlx::query("SELECT * FROM socket_statistics WHERE system_information_id = $1;")
+            .bind(system_information_id)
+            .fetch_optional(db)
+            .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    pub async fn get_all_by_system_informations(
+        db: &mut PoolConnection<Postgres>,
+        system_information_ids: &[i32],
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM socket_statistics 
+            WHERE system_information_id = ANY($1);",
+        )
+        .bind(system_information_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_socket_statistics: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            tcp_sockets_in_use: row.try_get(2)?,
+            tcp_sockets_orphaned: row.try_get(3)?,
+            udp_sockets_in_use: row.try_get(4)?,
+            tcp6_sockets_in_use: row.try_get(5)?,
+            udp6_sockets_in_use: row.try_get(6)?,
+        })
+    }
 }
@@ -1,6 +1,6 @@
 use chrono::NaiveDateTime;
-use serde::{Serialize, Deserialize};
-use sqlx::{pool::PoolConnection, Postgres, postgres::PgRow, Row};
+use serde::{Deserialize, Serialize};
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, Row};
 
 #[derive(Serialize, Deserialize)]
 pub struct SystemInformation {
@@ -9,7 +9,10 @@ pub struct SystemInformation {
     pub hostname: String,
     pub uptime: i64,
     pub boot_time: NaiveDateTime,
+    /// Collection time reported by the device, the receive time for older agents.
     pub create_date: NaiveDateTime,
+    pub sequence: Option<i64>,
+    pub receive_date: NaiveDateTime,
 }
 
 impl SystemInformation {
@@ -19,6 +22,8 @@ impl SystemInformation {
         uptime: i64,
         boot_time: NaiveDateTime,
         create_date: NaiveDateTime,
+        sequence: Option<i64>,
+        receive_date: NaiveDateTime,
     ) -> Self {
         Self {
             id_system_information: 0,
@@ -27,16 +32,20 @@ impl SystemInformation {
             uptime,
             boot_time,
             create_date,
+            sequence,
+            receive_date,
         }
     }
 
-    pub async fn insert(self, db: &mut PoolConnection<Postgres>) -> sqlx::Result<Self> {
-        let row: (i32,) = sqlx::query_as("INSERT INTO system_informations (device_profile_id, hostname, uptime, boot_time, create_date) VALUES ($1, $2, $3, $4, $5) RETURNING id_system_information;")
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
+        let row: (i32,) = sqlx::query_as("INSERT INTO system_informations (device_profile_id, hostname, uptime, boot_time, create_date, sequence, receive_date) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id_system_information;")
             .bind(self.device_profile_id)
             .bind(self.hostname.clone())
             .bind(self.uptime)
             .bind(self.boot_time)
             .bind(self.create_date)
+            .bind(self.sequence)
+            .bind(self.receive_date)
             .fetch_one(db)
             .await?;
 
@@ -47,27 +56,121 @@ impl SystemInformation {
             uptime: self.uptime,
             boot_time: self.boot_time,
             create_date: self.create_date,
+            sequence: self.sequence,
+            receive_date: self.receive_date,
         })
     }
 
-    pub async fn get_lastest(db: &mut PoolConnection<Postgres>, profile_id: i32) -> sqlx::Result<Option<Self>> {
-        let sql = 
-            "SELECT * 
+    pub async fn exists_by_sequence(
+        db: &mut PgConnection,
+        device_profile_id: i32,
+        sequence: i64,
+    ) -> sqlx::Result<bool> {
+        let row: (bool,) = sqlx::query_as(
+            "SELECT EXISTS (SELECT 1 FROM system_informations WHERE device_profile_id = $1 AND sequence = $2);",
+        )
+        .bind(device_profile_id)
+        .bind(sequence)
+        .fetch_one(db)
+        .await?;
+
+        Ok(row.0)
+    }
+
+    pub async fn get_latest(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+    ) -> sqlx::Result<Option<Self>> {
+        let sql = "SELECT * 
              FROM system_informations 
-             WHERE profile_id = $1 
-             ORDER BY CreateDate DESC
+             WHERE device_profile_id = $1 
+             ORDER BY create_date DESC
              LIMIT 1;";
-    
+
         let row = sqlx::query(sql)
-            .bind(profile_id)
+            .bind(device_profile_id)
             .fetch_optional(db)
             .await?;
- 
-         if let Some(row) = row {
-             return Ok(Some(Self::from_row(&row)?));
-         }
- 
-         return Ok(None);
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    /// The latest snapshot of each of the profiles in their order, profiles without snapshots are
+    /// left out.
+    pub async fn get_latest_by_profiles(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_ids: &[i32],
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT s.* 
+            FROM UNNEST($1::INTEGER[]) WITH ORDINALITY p (id, position) 
+            CROSS JOIN LATERAL (
+                SELECT * 
+                FROM system_informations 
+                WHERE device_profile_id = p.id 
+                ORDER BY create_date DESC 
+                LIMIT 1
+            ) s 
+            ORDER BY p.position;",
+        )
+        .bind(device_profile_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    /// The latest snapshot of the profile received at or before `until`.
+    pub async fn get_latest_received(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        until: NaiveDateTime,
+    ) -> sqlx::Result<Option<Self>> {
+        let row = sqlx::query(
+            "SELECT * 
+            FROM system_informations 
+            WHERE device_profile_id = $1 AND receive_date <= $2 
+            ORDER BY receive_date DESC 
+            LIMIT 1;",
+        )
+        .bind(device_profile_id)
+        .bind(until)
+        .fetch_optional(db)
+        .await?;
+
+        if let Some(row) = row {
+            return Ok(Some(Self::from_row(&row)?));
+        }
+
+        Ok(None)
+    }
+
+    /// Snapshots of the profile received between `from` and `to`, oldest first.
+    pub async fn get_received_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM system_informations 
+            WHERE device_profile_id = $1 AND receive_date BETWEEN $2 AND $3 
+            ORDER BY receive_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
     }
 
     fn from_row(row: &PgRow) -> sqlx::Result<Self> {
@@ -78,6 +181,8 @@ impl SystemInformation {
             uptime: row.try_get(3)?,
             boot_time: row.try_get(4)?,
             create_date: row.try_get(5)?,
+            sequence: row.try_get(6)?,
+            receive_date: row.try_get(7)?,
         })
     }
 }
@@ -0,0 +1,151 @@
+use chrono::NaiveDateTime;
+use sqlx::{pool::PoolConnection, postgres::PgRow, PgConnection, Postgres, QueryBuilder, Row};
+
+use super::reserve_ids;
+
+pub struct TemperatureInfo {
+    pub id_temperature_info: i32,
+    pub system_information_id: i32,
+    pub chip: String,
+    pub label: String,
+    pub current: Option<f32>,
+    pub high: Option<f32>,
+    pub critical: Option<f32>,
+}
+
+impl TemperatureInfo {
+    pub fn new(
+        system_information_id: i32,
+        chip: &str,
+        label: &str,
+        current: Option<f32>,
+        high: Option<f32>,
+        critical: Option<f32>,
+    ) -> Self {
+        Self {
+            id_temperature_info: 0,
+            system_information_id,
+            chip: String::from(chip),
+            label: String::from(label),
+            current,
+            high,
+            critical,
+        }
+    }
+
+    /// Name of the sensor used as alert rule target.
+    pub fn sensor_name(&self) -> String {
+        format!("{}/{}", self.chip, self.label)
+    }
+
+    pub async fn insert_many(db: &mut PgConnection, sensors: Vec<Self>) -> sqlx::Result<Vec<Self>> {
+        if sensors.is_empty() {
+            return Ok(sensors);
+        }
+
+        let ids = reserve_ids(
+            &mut *db,
+            "temperature_infos",
+            "id_temperature_info",
+            sensors.len(),
+        )
+        .await?;
+
+        let mut query_builder: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO temperature_infos 
+            (id_temperature_info, system_information_id, chip, label, current, high, critical) ",
+        );
+
+        query_builder.push_values(sensors.iter().zip(&ids), |mut b, (sensor, id)| {
+            b.push_bind(*id)
+                .push_bind(sensor.system_information_id)
+                .push_bind(sensor.chip.clone())
+                .push_bind(sensor.label.clone())
+                .push_bind(sensor.current.map(|x| x as f64))
+                .push_bind(sensor.high.map(|x| x as f64))
+                .push_bind(sensor.critical.map(|x| x as f64));
+        });
+
+        query_builder.build().execute(&mut *db).await?;
+
+        Ok(sensors
+            .into_iter()
+            .zip(ids)
+            .map(|(sensor, id)| Self {
+                id_temperature_info: id,
+                ..sensor
+            })
+            .collect())
+    }
+
+    pub async fn get_all_by_system_information(
+        db: &mut PoolConnection<Postgres>,
+        system_information_id: i32,
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM temperature_infos 
+            WHERE system_information_id = $1 
+            ORDER BY id_temperature_info;",
+        )
+        .bind(system_information_id)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_all_by_system_informations(
+        db: &mut PoolConnection<Postgres>,
+        system_information_ids: &[i32],
+    ) -> sqlx::Result<Vec<Self>> {
+        sqlx::query(
+            "SELECT * 
+            FROM temperature_infos 
+            WHERE system_information_id = ANY($1) 
+            ORDER BY id_temperature_info;",
+        )
+        .bind(system_information_ids)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(Self::from_row)
+        .collect()
+    }
+
+    pub async fn get_range(
+        db: &mut PoolConnection<Postgres>,
+        device_profile_id: i32,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> sqlx::Result<Vec<(NaiveDateTime, Self)>> {
+        sqlx::query(
+            "SELECT t.*, s.create_date 
+            FROM temperature_infos t 
+            INNER JOIN system_informations s ON s.id_system_information = t.system_information_id 
+            WHERE s.device_profile_id = $1 AND s.create_date BETWEEN $2 AND $3 
+            ORDER BY s.create_date;",
+        )
+        .bind(device_profile_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(|row| Ok((row.try_get("create_date")?, Self::from_row(row)?)))
+        .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_temperature_info: row.try_get(0)?,
+            system_information_id: row.try_get(1)?,
+            chip: row.try_get(2)?,
+            label: row.try_get(3)?,
+            current: row.try_get::<Option<f64>, _>(4)?.map(|x| x as f32),
+            high: row.try_get::<Option<f64>, _>(5)?.map(|x| x as f32),
+            critical: row.try_get::<Option<f64>, _>(6)?.map(|x| x as f32),
+        })
+    }
+}
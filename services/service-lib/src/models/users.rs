@@ -0,0 +1,87 @@
+use chrono::NaiveDateTime;
+use sqlx::{postgres::PgRow, PgConnection, Row};
+
+/// A user of the service api, it authenticates with its api tokens.
+#[derive(Debug, Clone)]
+pub struct User {
+    pub id_user: i32,
+    pub name: String,
+    pub create_date: NaiveDateTime,
+    /// The Discord user the bot acts for with the tokens of this user.
+    pub discord_id: Option<i64>,
+}
+
+impl User {
+    pub fn new(name: &str, discord_id: Option<i64>, create_date: NaiveDateTime) -> Self {
+        Self {
+            id_user: 0,
+            name: String::from(name),
+            create_date,
+            discord_id,
+        }
+    }
+
+    pub async fn insert(self, db: &mut PgConnection) -> sqlx::Result<Self> {
+        let row: (i32,) = sqlx::query_as(
+            "INSERT INTO users (name, create_date, discord_id) VALUES ($1, $2, $3) RETURNING id_user;",
+        )
+        .bind(self.name.clone())
+        .bind(self.create_date)
+        .bind(self.discord_id)
+        .fetch_one(db)
+        .await?;
+
+        Ok(Self {
+            id_user: row.0,
+            ..self
+        })
+    }
+
+    pub async fn get(db: &mut PgConnection, id: i32) -> sqlx::Result<Option<Self>> {
+        sqlx::query("SELECT * FROM users WHERE id_user = $1;")
+            .bind(id)
+            .fetch_optional(db)
+            .await?
+            .map(|row| Self::from_row(&row))
+            .transpose()
+    }
+
+    pub async fn get_by_name(db: &mut PgConnection, name: &str) -> sqlx::Result<Option<Self>> {
+        sqlx::query("SELECT * FROM users WHERE name = $1;")
+            .bind(name)
+            .fetch_optional(db)
+            .await?
+            .map(|row| Self::from_row(&row))
+            .transpose()
+    }
+
+    pub async fn get_by_discord_id(
+        db: &mut PgConnection,
+        discord_id: i64,
+    ) -> sqlx::Result<Option<Self>> {
+        sqlx::query("SELECT * FROM users WHERE discord_id = $1;")
+            .bind(discord_id)
+            .fetch_optional(db)
+            .await?
+            .map(|row| Self::from_row(&row))
+            .transpose()
+    }
+
+    pub async fn get_all(db: &mut PgConnection) -> sqlx::Result<Vec<Self>> {
+        sqlx::query("SELECT * FROM users ORDER BY id_user;")
+            .fetch_all(db)
+            .await?
+            .iter()
+            .map(Self::from_row)
+            .collect()
+    }
+
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id_user: row.try_get(0)?,
+            name: row.try_get(1)?,
+            create_date: row.try_get(2)?,
+            discord_id: row.try_get(3)?,
+        })
+    }
+}
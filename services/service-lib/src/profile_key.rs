@@ -1,14 +1,21 @@
+use chrono::Utc;
 use rocket::{
     error,
     http::Status,
     request::{FromRequest, Outcome},
+    tokio::task,
     Request,
 };
 use rocket_db_pools::Connection;
 
-use crate::{database::MonitoringDb, models::device_profiles::DeviceProfile};
+use crate::{
+    credentials::verify_key,
+    database::MonitoringDb,
+    models::{device_profiles::DeviceProfile, profile_keys::ProfileKey as ProfileKeyModel},
+};
 
-pub struct ProfileKey<'r>(&'r str);
+/// The `x-profile-key` header of a device, `<key id>.<secret>` with the id of the profile key.
+pub struct ProfileKey<'r>(pub &'r str);
 
 #[derive(Debug)]
 pub enum ProfileKeyError {
@@ -50,24 +57,85 @@ impl<'r> FromRequest<'r> for ProfileKey<'r> {
             }
         };
 
-        if let Ok(profile) = DeviceProfile::get(&mut *conn, id as i32).await {
-            if let Some(profile) = profile {
-                fn is_valid(key: &str, valid_key: &str) -> bool {
-                    key == valid_key
-                }
+        match DeviceProfile::get(&mut conn, id as i32).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return Outcome::Failure((Status::BadRequest, ProfileKeyError::InvalidId)),
+            Err(_) => return Outcome::Failure((Status::BadRequest, ProfileKeyError::IdNotPresent)),
+        }
+
+        let key = match req.headers().get_one("x-profile-key") {
+            Some(key) => key,
+            None => return Outcome::Failure((Status::Unauthorized, ProfileKeyError::MissingKey)),
+        };
+
+        let (key_id, secret) = match parse_key(key) {
+            Some(parsed) => parsed,
+            None => return Outcome::Failure((Status::Unauthorized, ProfileKeyError::InvalidKey)),
+        };
 
-                return match req.headers().get_one("x-profile-key") {
-                    None => Outcome::Failure((Status::BadRequest, ProfileKeyError::InvalidKey)),
-                    Some(key) if is_valid(key, &profile.profile_key) => {
-                        Outcome::Success(ProfileKey(key))
-                    }
-                    Some(_) => Outcome::Failure((Status::BadRequest, ProfileKeyError::InvalidKey)),
-                };
+        let now = Utc::now().naive_utc();
+        let keys = match ProfileKeyModel::get_valid(&mut conn, id as i32, now).await {
+            Ok(keys) => keys,
+            Err(why) => {
+                error!("Failed to get keys of profile '{id}': {why}");
+                return Outcome::Failure((
+                    Status::InternalServerError,
+                    ProfileKeyError::DbAccessError,
+                ));
             }
+        };
 
-            return Outcome::Failure((Status::BadRequest, ProfileKeyError::InvalidId));
+        // Only the key with the id is verified, so a request costs at most one hash.
+        // Keys without an id predate them and are checked against the oldest valid key.
+        let hash = match key_id {
+            Some(key_id) => keys.into_iter().find(|x| x.id_profile_key == key_id),
+            None => keys.into_iter().last(),
+        };
+        let hash = match hash {
+            Some(key) => key.key_hash,
+            None => return Outcome::Failure((Status::Unauthorized, ProfileKeyError::InvalidKey)),
+        };
+
+        // Argon2 is slow on purpose, verifying on the async workers would stall other requests.
+        let candidate = String::from(secret);
+        let is_valid = task::spawn_blocking(move || verify_key(&candidate, &hash))
+            .await
+            .unwrap_or(false);
+
+        if is_valid {
+            return Outcome::Success(ProfileKey(key));
         }
 
-        Outcome::Failure((Status::BadRequest, ProfileKeyError::IdNotPresent))
+        Outcome::Failure((Status::Unauthorized, ProfileKeyError::InvalidKey))
+    }
+}
+
+/// Splits a `<key id>.<secret>` key, keys without an id are returned whole as the secret.
+/// `None` if the key id isn't a number.
+fn parse_key(key: &str) -> Option<(Option<i32>, &str)> {
+    match key.split_once('.') {
+        Some((key_id, secret)) => Some((Some(key_id.parse().ok()?), secret)),
+        None => Some((None, key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_key_splits_the_key_id() {
+        assert_eq!(parse_key("12.abc123"), Some((Some(12), "abc123")));
+    }
+
+    #[test]
+    fn parse_key_keeps_keys_without_id() {
+        assert_eq!(parse_key("abc123"), Some((None, "abc123")));
+    }
+
+    #[test]
+    fn parse_key_rejects_invalid_ids() {
+        assert_eq!(parse_key("x.abc123"), None);
+        assert_eq!(parse_key(".abc123"), None);
     }
 }